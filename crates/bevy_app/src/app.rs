@@ -1,13 +1,15 @@
-use crate::{CoreStage, Plugin, PluginGroup, StartupSchedule, StartupStage};
+use crate::{
+    AppChannelReceiver, CoreStage, Plugin, PluginGroup, StartupSchedule, StartupStage, SubApp,
+};
 pub use bevy_derive::AppLabel;
 use bevy_ecs::{
-    event::{Event, Events},
+    event::{Event, EventRetention, EventSender, Events, ExternalEvents},
     prelude::FromWorld,
     schedule::{
-        IntoSystemDescriptor, Schedule, ShouldRun, Stage, StageLabel, State, StateData, SystemSet,
-        SystemStage,
+        IntoSystemDescriptor, Schedule, ShouldRun, Stage, StageLabel, State, StateData, SubState,
+        SystemSet, SystemStage,
     },
-    system::Resource,
+    system::{apply_async_world_requests, Resource},
     world::World,
 };
 use bevy_utils::{tracing::debug, HashMap, HashSet};
@@ -22,10 +24,8 @@ bevy_utils::define_label!(
     AppLabelId,
 );
 
-/// The [`Resource`] that stores the [`App`]'s [`TypeRegistry`](bevy_reflect::TypeRegistry).
 #[cfg(feature = "bevy_reflect")]
-#[derive(Resource, Clone, bevy_derive::Deref, bevy_derive::DerefMut, Default)]
-pub struct AppTypeRegistry(pub bevy_reflect::TypeRegistryArc);
+pub use bevy_ecs::reflect::AppTypeRegistry;
 
 pub(crate) enum AppError {
     DuplicatePlugin { plugin_name: String },
@@ -70,7 +70,7 @@ pub struct App {
     pub runner: Box<dyn Fn(App)>,
     /// A container of [`Stage`]s set to be run in a linear order.
     pub schedule: Schedule,
-    sub_apps: HashMap<AppLabelId, SubApp>,
+    pub(crate) sub_apps: HashMap<AppLabelId, SubApp>,
     plugin_registry: Vec<Box<dyn Plugin>>,
     plugin_name_added: HashSet<String>,
 }
@@ -85,22 +85,6 @@ impl Debug for App {
     }
 }
 
-/// Each `SubApp` has its own [`Schedule`] and [`World`], enabling a separation of concerns.
-struct SubApp {
-    app: App,
-    runner: Box<dyn Fn(&mut World, &mut App)>,
-}
-
-impl Debug for SubApp {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "SubApp {{ app: ")?;
-        f.debug_map()
-            .entries(self.app.sub_apps.iter().map(|(k, v)| (k, v)))
-            .finish()?;
-        write!(f, "}}")
-    }
-}
-
 impl Default for App {
     fn default() -> Self {
         let mut app = App::empty();
@@ -109,6 +93,7 @@ impl Default for App {
 
         app.add_default_stages()
             .add_event::<AppExit>()
+            .add_system_to_stage(CoreStage::Update, apply_async_world_requests.at_start())
             .add_system_to_stage(CoreStage::Last, World::clear_trackers);
 
         #[cfg(feature = "bevy_ci_testing")]
@@ -151,7 +136,7 @@ impl App {
         let _bevy_frame_update_span = info_span!("frame").entered();
         self.schedule.run(&mut self.world);
         for sub_app in self.sub_apps.values_mut() {
-            (sub_app.runner)(&mut self.world, &mut sub_app.app);
+            sub_app.update(&mut self.world);
         }
     }
 
@@ -601,6 +586,26 @@ impl App {
             .add_system_set_to_stage(stage, State::<T>::get_driver())
     }
 
+    /// Adds a [`SubState`], which exists while its parent state has certain values.
+    /// This adds a driver for the sub-state to [`CoreStage::Update`], where the parent state must
+    /// be driven, as with [`add_state`](Self::add_state).
+    pub fn add_sub_state<S>(&mut self) -> &mut Self
+    where
+        S: SubState,
+    {
+        self.add_sub_state_to_stage::<S>(CoreStage::Update)
+    }
+
+    /// Adds a [`SubState`], with its driver in the given stage.
+    /// The parent state must be driven in that stage too, see
+    /// [`add_state_to_stage`](Self::add_state_to_stage).
+    pub fn add_sub_state_to_stage<S>(&mut self, stage: impl StageLabel) -> &mut Self
+    where
+        S: SubState,
+    {
+        self.add_system_set_to_stage(stage, State::<S>::get_sub_state_driver())
+    }
+
     /// Adds utility stages to the [`Schedule`], giving it a standardized structure.
     ///
     /// Adding those stages is necessary to make some core engine features work, like
@@ -681,6 +686,74 @@ impl App {
         self
     }
 
+    /// Setup the application to manage events of type `T`, kept as long as `retention` asks.
+    ///
+    /// Like [`add_event`](Self::add_event), but the [`Events::<T>`] drop their events according to
+    /// the given [`EventRetention`] instead of after two updates. If the events were already
+    /// added, their retention is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::{prelude::*, event::EventRetention};
+    /// #
+    /// # struct MyEvent;
+    /// # let mut app = App::new();
+    /// #
+    /// // Keep events until every `EventReader<MyEvent>` read them, for at most 60 frames.
+    /// app.add_event_with_retention::<MyEvent>(EventRetention::UntilRead {
+    ///     max_updates: Some(60),
+    /// });
+    /// ```
+    pub fn add_event_with_retention<T>(&mut self, retention: EventRetention) -> &mut Self
+    where
+        T: Event,
+    {
+        self.add_event::<T>();
+        self.world
+            .resource_mut::<Events<T>>()
+            .set_retention(retention);
+        self
+    }
+
+    /// Setup the application to receive events of type `T` from other threads, and returns an
+    /// [`EventSender`] for them.
+    ///
+    /// This adds the events with [`add_event`](Self::add_event) and an [`ExternalEvents::<T>`]
+    /// resource, whose [`drain_system`](ExternalEvents::drain_system) moves the events sent
+    /// since the last frame into [`Events::<T>`] in [`CoreStage::First`], right after they are
+    /// updated. Calling it again returns another sender for the same events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::prelude::*;
+    /// #
+    /// # struct Message(String);
+    /// # let mut app = App::new();
+    /// #
+    /// let sender = app.add_external_event::<Message>();
+    /// std::thread::spawn(move || {
+    ///     sender.send(Message("hello".to_string())).ok();
+    /// });
+    /// ```
+    pub fn add_external_event<T>(&mut self) -> EventSender<T>
+    where
+        T: Event,
+    {
+        self.add_event::<T>();
+        if !self.world.contains_resource::<ExternalEvents<T>>() {
+            self.init_resource::<ExternalEvents<T>>()
+                .add_system_to_stage(
+                    CoreStage::First,
+                    ExternalEvents::<T>::drain_system.after(Events::<T>::update_system),
+                );
+        }
+        self.world.resource::<ExternalEvents<T>>().sender()
+    }
+
     /// Inserts a [`Resource`] to the current [`App`] and overwrites any [`Resource`] previously added of the same type.
     ///
     /// A [`Resource`] in Bevy represents globally unique data. [`Resource`]s must be added to Bevy apps
@@ -986,22 +1059,33 @@ impl App {
     /// The provided function `f` is called by the [`update`](Self::update) method. The [`World`]
     /// parameter represents the main app world, while the [`App`] parameter is just a mutable
     /// reference to the `SubApp` itself.
+    ///
+    /// Unlike with [`insert_sub_app`](Self::insert_sub_app), `f` is responsible for running the
+    /// schedule of the sub-app.
     pub fn add_sub_app(
         &mut self,
         label: impl AppLabel,
         app: App,
         sub_app_runner: impl Fn(&mut World, &mut App) + 'static,
     ) -> &mut Self {
-        self.sub_apps.insert(
-            label.as_label(),
-            SubApp {
-                app,
-                runner: Box::new(sub_app_runner),
-            },
-        );
+        self.insert_sub_app(label, SubApp::with_runner(app, sub_app_runner))
+    }
+
+    /// Adds a [`SubApp`] to the current [`App`], replacing any sub-app with the same `label`.
+    pub fn insert_sub_app(&mut self, label: impl AppLabel, sub_app: SubApp) -> &mut Self {
+        self.sub_apps.insert(label.as_label(), sub_app);
         self
     }
 
+    /// Removes the [`SubApp`] with the given `label` from the current [`App`], if it exists.
+    ///
+    /// A pipelined sub-app is returned once its schedule finished running.
+    pub fn remove_sub_app(&mut self, label: impl AppLabel) -> Option<SubApp> {
+        let mut sub_app = self.sub_apps.remove(&label.as_label())?;
+        sub_app.finish();
+        Some(sub_app)
+    }
+
     /// Retrieves a `SubApp` stored inside this [`App`].
     ///
     /// # Panics
@@ -1016,11 +1100,17 @@ impl App {
 
     /// Retrieves a `SubApp` inside this [`App`] with the given label, if it exists. Otherwise returns
     /// an [`Err`] containing the given label.
+    ///
+    /// If the `SubApp` is [pipelined](SubApp::pipelined), this waits for its schedule to finish
+    /// running.
     pub fn get_sub_app_mut(&mut self, label: impl AppLabel) -> Result<&mut App, AppLabelId> {
         let label = label.as_label();
         self.sub_apps
             .get_mut(&label)
-            .map(|sub_app| &mut sub_app.app)
+            .map(|sub_app| {
+                sub_app.finish();
+                &mut sub_app.app
+            })
             .ok_or(label)
     }
 
@@ -1038,12 +1128,55 @@ impl App {
 
     /// Retrieves a `SubApp` inside this [`App`] with the given label, if it exists. Otherwise returns
     /// an [`Err`] containing the given label.
+    ///
+    /// The world and schedule of a [pipelined](SubApp::pipelined) `SubApp` are empty while its
+    /// schedule runs, see [`get_sub_app_mut`](Self::get_sub_app_mut).
     pub fn get_sub_app(&self, label: impl AppLabel) -> Result<&App, impl AppLabel> {
         self.sub_apps
             .get(&label.as_label())
             .map(|sub_app| &sub_app.app)
             .ok_or(label)
     }
+
+    /// Adds a channel to send values of type `T` from the current [`App`] to the `SubApp` with
+    /// the given `label`.
+    ///
+    /// The [`AppChannelSender<T>`] resource is inserted in the main world, replacing any previous
+    /// one, and the [`AppChannelReceiver<T>`] resource is added to the world of the `SubApp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `SubApp` doesn't exist.
+    pub fn add_channel_to_sub_app<T: Send + 'static>(&mut self, label: impl AppLabel) -> &mut Self {
+        let sender = self
+            .sub_app_mut(label)
+            .world
+            .get_resource_or_insert_with(AppChannelReceiver::<T>::default)
+            .sender();
+        self.insert_resource(sender)
+    }
+
+    /// Adds a channel to send values of type `T` from the `SubApp` with the given `label` to the
+    /// current [`App`].
+    ///
+    /// The [`AppChannelSender<T>`] resource is inserted in the world of the `SubApp`, replacing
+    /// any previous one, and the [`AppChannelReceiver<T>`] resource is added to the main world.
+    /// Several sub-apps can send to the same receiver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `SubApp` doesn't exist.
+    pub fn add_channel_from_sub_app<T: Send + 'static>(
+        &mut self,
+        label: impl AppLabel,
+    ) -> &mut Self {
+        let sender = self
+            .world
+            .get_resource_or_insert_with(AppChannelReceiver::<T>::default)
+            .sender();
+        self.sub_app_mut(label).insert_resource(sender);
+        self
+    }
 }
 
 fn run_once(mut app: App) {
@@ -1061,6 +1194,8 @@ pub struct AppExit;
 
 #[cfg(test)]
 mod tests {
+    use bevy_ecs::event::Events;
+
     use crate::{App, Plugin};
 
     struct PluginA;
@@ -1083,6 +1218,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn external_events_are_drained_in_first() {
+        struct Message(u32);
+
+        let mut app = App::new();
+        let sender = app.add_external_event::<Message>();
+        let other_sender = app.add_external_event::<Message>();
+        std::thread::spawn(move || sender.send(Message(1)).unwrap())
+            .join()
+            .unwrap();
+        other_sender.send(Message(2)).unwrap();
+
+        app.update();
+        let events = app.world.resource::<Events<Message>>();
+        let messages: Vec<_> = events
+            .get_reader()
+            .iter(events)
+            .map(|message| message.0)
+            .collect();
+        assert_eq!(messages, vec![1, 2]);
+    }
+
     #[test]
     fn can_add_two_plugins() {
         App::new().add_plugin(PluginA).add_plugin(PluginB);
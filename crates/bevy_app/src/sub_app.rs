@@ -0,0 +1,325 @@
+use crate::App;
+use bevy_ecs::{
+    schedule::{Schedule, Stage},
+    system::Resource,
+    world::World,
+};
+use crossbeam_channel::{Receiver, Sender};
+use std::fmt::Debug;
+
+/// A secondary application with its own [`World`] and [`Schedule`], stored in an [`App`].
+///
+/// Sub-apps are updated by [`App::update`] after the main schedule runs. The extract function
+/// first copies the data the sub-app needs from the main world into its own world, then the
+/// schedule of the sub-app runs.
+///
+/// A [pipelined](SubApp::pipelined) sub-app runs its schedule on its own thread, in parallel with
+/// the next update of the main app. Use [`App::add_channel_to_sub_app`] and
+/// [`App::add_channel_from_sub_app`] to send data between the main app and its sub-apps.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_app::{prelude::*, AppLabel, SubApp};
+/// # use bevy_ecs::prelude::*;
+/// #
+/// #[derive(AppLabel)]
+/// struct SimulationApp;
+///
+/// #[derive(Resource, Clone, Default)]
+/// struct Inputs(Vec<u32>);
+///
+/// let mut simulation = App::empty();
+/// simulation.add_stage("simulate", SystemStage::parallel());
+///
+/// let mut app = App::new();
+/// app.init_resource::<Inputs>().insert_sub_app(
+///     SimulationApp,
+///     SubApp::new(simulation, |main_world, simulation| {
+///         let inputs = main_world.resource::<Inputs>().clone();
+///         simulation.world.insert_resource(inputs);
+///     })
+///     .pipelined(),
+/// );
+/// app.update();
+/// ```
+pub struct SubApp {
+    /// The [`App`] holding the world and schedule of the sub-app.
+    ///
+    /// While a pipelined sub-app runs, its world and schedule are replaced by empty ones. Use
+    /// [`SubApp::finish`] or [`App::sub_app_mut`] to wait for them.
+    pub app: App,
+    extract: Box<dyn Fn(&mut World, &mut App)>,
+    /// `false` if the extract function also runs the schedule, see [`App::add_sub_app`].
+    run_schedule: bool,
+    pipelined: bool,
+    worker: Option<Worker>,
+}
+
+impl Debug for SubApp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SubApp {{ app: ")?;
+        f.debug_map()
+            .entries(self.app.sub_apps.iter().map(|(k, v)| (k, v)))
+            .finish()?;
+        write!(f, "}}")
+    }
+}
+
+impl SubApp {
+    /// Creates a sub-app from `app`, whose world is filled by `extract` from the main world
+    /// before its schedule runs.
+    pub fn new(app: App, extract: impl Fn(&mut World, &mut App) + 'static) -> Self {
+        Self {
+            app,
+            extract: Box::new(extract),
+            run_schedule: true,
+            pipelined: false,
+            worker: None,
+        }
+    }
+
+    /// Creates a sub-app from `app`, whose `runner` is responsible for running its schedule.
+    pub(crate) fn with_runner(app: App, runner: impl Fn(&mut World, &mut App) + 'static) -> Self {
+        Self {
+            run_schedule: false,
+            ..Self::new(app, runner)
+        }
+    }
+
+    /// Runs the schedule of the sub-app on its own thread, in parallel with the next update of
+    /// the main app.
+    ///
+    /// The sub-app can't use non-send resources. On `wasm32`, the schedule still runs on the
+    /// main thread.
+    pub fn pipelined(mut self) -> Self {
+        assert!(
+            self.run_schedule,
+            "Sub-apps added with `App::add_sub_app` run their schedule from their runner and cannot be pipelined"
+        );
+        self.pipelined = true;
+        self
+    }
+
+    /// Returns `true` if the schedule of the sub-app runs on its own thread.
+    pub fn is_pipelined(&self) -> bool {
+        self.pipelined
+    }
+
+    /// Extracts the data of the sub-app from `main_world`, and runs its schedule.
+    ///
+    /// A pipelined sub-app first waits for its previous run to finish, and returns without
+    /// waiting for this one.
+    pub fn update(&mut self, main_world: &mut World) {
+        self.finish();
+        (self.extract)(main_world, &mut self.app);
+        if !self.run_schedule {
+            return;
+        }
+        if self.pipelined && cfg!(not(target_arch = "wasm32")) {
+            self.worker
+                .get_or_insert_with(Worker::spawn)
+                .start(&mut self.app);
+        } else {
+            self.app.schedule.run(&mut self.app.world);
+        }
+    }
+
+    /// Waits for the schedule of a pipelined sub-app to finish running, and puts its world and
+    /// schedule back in [`SubApp::app`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the schedule panicked.
+    pub fn finish(&mut self) {
+        if let Some(worker) = &mut self.worker {
+            worker.finish(&mut self.app);
+        }
+    }
+}
+
+/// A thread running the schedule of a pipelined [`SubApp`].
+struct Worker {
+    sender: Sender<(World, Schedule)>,
+    receiver: Receiver<(World, Schedule)>,
+    /// The world and schedule put in the sub-app while the real ones are on the thread.
+    spare: Option<(World, Schedule)>,
+}
+
+impl Worker {
+    fn spawn() -> Self {
+        let (sender, worker_receiver) = crossbeam_channel::bounded::<(World, Schedule)>(1);
+        let (worker_sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::Builder::new()
+            .name("sub-app".to_string())
+            .spawn(move || {
+                for (mut world, mut schedule) in worker_receiver {
+                    schedule.run(&mut world);
+                    if worker_sender.send((world, schedule)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn the thread of a pipelined sub-app");
+        Self {
+            sender,
+            receiver,
+            spare: Some(Default::default()),
+        }
+    }
+
+    fn start(&mut self, app: &mut App) {
+        let (mut world, mut schedule) = self.spare.take().unwrap();
+        std::mem::swap(&mut app.world, &mut world);
+        std::mem::swap(&mut app.schedule, &mut schedule);
+        self.sender
+            .send((world, schedule))
+            .expect("The thread of a pipelined sub-app panicked");
+    }
+
+    fn finish(&mut self, app: &mut App) {
+        if self.spare.is_some() {
+            return;
+        }
+        let (mut world, mut schedule) = self
+            .receiver
+            .recv()
+            .expect("The thread of a pipelined sub-app panicked");
+        std::mem::swap(&mut app.world, &mut world);
+        std::mem::swap(&mut app.schedule, &mut schedule);
+        self.spare = Some((world, schedule));
+    }
+}
+
+/// Sends values of type `T` to another app, which reads them with its [`AppChannelReceiver<T>`].
+///
+/// This resource is added by [`App::add_channel_to_sub_app`] and
+/// [`App::add_channel_from_sub_app`].
+#[derive(Resource)]
+pub struct AppChannelSender<T: Send + 'static>(Sender<T>);
+
+impl<T: Send + 'static> Clone for AppChannelSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Send + 'static> AppChannelSender<T> {
+    /// Sends a `value` to the other app.
+    pub fn send(&self, value: T) {
+        // The receiving end also holds a sender, so the channel can't be disconnected.
+        self.0.send(value).unwrap();
+    }
+}
+
+/// Receives the values of type `T` sent by other apps with [`AppChannelSender<T>`].
+///
+/// This resource is added by [`App::add_channel_to_sub_app`] and
+/// [`App::add_channel_from_sub_app`].
+///
+/// The schedule of a pipelined sub-app runs concurrently with the next update of the main app,
+/// so values sent by the main app can arrive while the sub-app reads them. To get the values of
+/// exactly one main app update, read them from the extract function of the [`SubApp`], which runs
+/// between the runs of the sub-app schedule.
+#[derive(Resource)]
+pub struct AppChannelReceiver<T: Send + 'static> {
+    sender: Sender<T>,
+    receiver: Receiver<T>,
+}
+
+impl<T: Send + 'static> Default for AppChannelReceiver<T> {
+    fn default() -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+impl<T: Send + 'static> AppChannelReceiver<T> {
+    /// Creates an [`AppChannelSender`] sending to this receiver.
+    pub fn sender(&self) -> AppChannelSender<T> {
+        AppChannelSender(self.sender.clone())
+    }
+
+    /// Returns the oldest value that was not received yet, if any.
+    pub fn try_recv(&self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Iterates over the values that were not received yet, from the oldest.
+    pub fn try_iter(&self) -> impl Iterator<Item = T> + '_ {
+        self.receiver.try_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as bevy_app, App, AppChannelReceiver, AppChannelSender, AppLabel, SubApp};
+    use bevy_ecs::prelude::*;
+
+    #[derive(AppLabel)]
+    struct Simulation;
+
+    #[derive(Resource, Default)]
+    struct Frame(u32);
+
+    #[derive(Resource)]
+    struct Inputs(Vec<&'static str>);
+
+    fn simulate(
+        frame: Res<Frame>,
+        inputs: Res<Inputs>,
+        sender: Res<AppChannelSender<(u32, Option<String>)>>,
+    ) {
+        assert_eq!(inputs.0, ["input"]);
+        let thread = std::thread::current().name().map(ToString::to_string);
+        sender.send((frame.0, thread));
+    }
+
+    #[test]
+    fn pipelined_sub_app() {
+        let mut simulation = App::empty();
+        simulation.add_stage("simulate", SystemStage::single(simulate));
+
+        let mut app = App::new();
+        app.init_resource::<Frame>()
+            .add_system(
+                |mut frame: ResMut<Frame>, sender: Res<AppChannelSender<_>>| {
+                    frame.0 += 1;
+                    sender.send("input");
+                },
+            )
+            .insert_sub_app(
+                Simulation,
+                SubApp::new(simulation, |main_world, simulation| {
+                    let frame = main_world.resource::<Frame>().0;
+                    simulation.world.insert_resource(Frame(frame));
+                    // Reading the channel here gets the inputs of this update only, as the
+                    // main app sends the next ones while the schedule runs.
+                    let inputs = simulation
+                        .world
+                        .resource::<AppChannelReceiver<&'static str>>()
+                        .try_iter()
+                        .collect();
+                    simulation.world.insert_resource(Inputs(inputs));
+                })
+                .pipelined(),
+            )
+            .add_channel_to_sub_app::<&'static str>(Simulation)
+            .add_channel_from_sub_app::<(u32, Option<String>)>(Simulation);
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.sub_app_mut(Simulation).world.resource::<Frame>().0, 3);
+        let frames: Vec<_> = app
+            .world
+            .resource::<AppChannelReceiver<(u32, Option<String>)>>()
+            .try_iter()
+            .collect();
+        let thread = Some("sub-app".to_string());
+        assert_eq!(
+            frames,
+            [(1, thread.clone()), (2, thread.clone()), (3, thread)]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use bevy_app::{App, Plugin};
+use bevy_ecs::{
+    system::{In, IntoPipeSystem, ResMut, System},
+    world::{World, WorldMemoryTotals},
+};
+
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+
+/// Adds "archetype count", "fragmented archetypes", "component bytes", "allocated component
+/// bytes" and "resource bytes" diagnostics to an App
+///
+/// The byte measurements come from [`World::memory_totals`]. [`World::memory_stats`] can be used
+/// directly to see which archetypes and tables make up these numbers.
+pub struct StorageDiagnosticsPlugin {
+    /// Archetypes with at least one entity but fewer than this many are counted as fragmented
+    pub fragmentation_threshold: usize,
+}
+
+impl Default for StorageDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            fragmentation_threshold: 8,
+        }
+    }
+}
+
+impl Plugin for StorageDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(Self::setup_system)
+            .add_system(Self::diagnostic_system(self.fragmentation_threshold));
+    }
+}
+
+impl StorageDiagnosticsPlugin {
+    pub const ARCHETYPE_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(63917805487210804034717667648156013995);
+    pub const FRAGMENTED_ARCHETYPES: DiagnosticId =
+        DiagnosticId::from_u128(142992540813071497864040213746161550040);
+    pub const COMPONENT_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(196985281964158205732600266388349301613);
+    pub const ALLOCATED_COMPONENT_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(152291184855003602412552296246363083387);
+    pub const RESOURCE_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(113253634115707389287486936342633097807);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::ARCHETYPE_COUNT,
+            "archetype_count",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::FRAGMENTED_ARCHETYPES,
+            "fragmented_archetypes",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::COMPONENT_BYTES,
+            "component_bytes",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::ALLOCATED_COMPONENT_BYTES,
+            "allocated_component_bytes",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(Self::RESOURCE_BYTES, "resource_bytes", 20));
+    }
+
+    /// Measures the storage of the world, then records the measurements.
+    ///
+    /// The world is only read, so the system runs in parallel with other systems that don't
+    /// write to [`Diagnostics`].
+    pub fn diagnostic_system(fragmentation_threshold: usize) -> impl System<In = (), Out = ()> {
+        (move |world: &World| StorageMeasurements {
+            archetype_count: world.archetypes().len(),
+            fragmented_archetypes: world
+                .archetypes()
+                .iter()
+                .filter(|archetype| {
+                    !archetype.is_empty() && archetype.len() < fragmentation_threshold
+                })
+                .count(),
+            totals: world.memory_totals(),
+        })
+        .pipe(Self::record_measurements)
+    }
+
+    fn record_measurements(
+        In(measurements): In<StorageMeasurements>,
+        mut diagnostics: ResMut<Diagnostics>,
+    ) {
+        let StorageMeasurements {
+            archetype_count,
+            fragmented_archetypes,
+            totals,
+        } = measurements;
+        diagnostics.add_measurement(Self::ARCHETYPE_COUNT, || archetype_count as f64);
+        diagnostics.add_measurement(Self::FRAGMENTED_ARCHETYPES, || fragmented_archetypes as f64);
+        diagnostics.add_measurement(Self::COMPONENT_BYTES, || totals.component_bytes as f64);
+        diagnostics.add_measurement(Self::ALLOCATED_COMPONENT_BYTES, || {
+            totals.allocated_component_bytes as f64
+        });
+        diagnostics.add_measurement(Self::RESOURCE_BYTES, || totals.resource_bytes as f64);
+    }
+}
+
+/// The measurements passed from the read-only half of the diagnostic system to the half
+/// writing them to [`Diagnostics`].
+struct StorageMeasurements {
+    archetype_count: usize,
+    fragmented_archetypes: usize,
+    totals: WorldMemoryTotals,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiagnosticsPlugin;
+    use bevy_ecs::{component::Component, system::Resource};
+    use std::any::TypeId;
+
+    #[derive(Component)]
+    struct Position(#[allow(dead_code)] [f32; 3]);
+
+    #[derive(Component)]
+    struct Marker;
+
+    #[derive(Resource)]
+    struct Settings(#[allow(dead_code)] u64);
+
+    #[test]
+    fn diagnostic_system_only_reads_the_world() {
+        let mut world = World::new();
+        world.init_resource::<Diagnostics>();
+        let mut system = StorageDiagnosticsPlugin::diagnostic_system(8);
+        system.initialize(&mut world);
+        assert!(!system.is_exclusive());
+
+        let diagnostics_id = world
+            .components()
+            .get_resource_id(TypeId::of::<Diagnostics>())
+            .unwrap();
+        let position_id = world.init_component::<Position>();
+        let access = system.component_access();
+        assert!(access.has_read_all());
+        assert!(access.has_write(diagnostics_id));
+        assert!(!access.has_write(position_id));
+    }
+
+    #[test]
+    fn storage_diagnostics() {
+        let mut app = App::new();
+        app.add_plugin(DiagnosticsPlugin)
+            .add_plugin(StorageDiagnosticsPlugin {
+                fragmentation_threshold: 2,
+            })
+            .insert_resource(Settings(0));
+        for _ in 0..4 {
+            app.world.spawn(Position([0.0; 3]));
+        }
+        app.world.spawn((Position([0.0; 3]), Marker));
+        app.update();
+
+        let expected_bytes = app.world.memory_totals();
+        let diagnostics = app.world.resource::<Diagnostics>();
+        let value = |id| diagnostics.get(id).unwrap().value().unwrap();
+        assert_eq!(
+            value(StorageDiagnosticsPlugin::ARCHETYPE_COUNT),
+            app.world.archetypes().len() as f64
+        );
+        assert_eq!(value(StorageDiagnosticsPlugin::FRAGMENTED_ARCHETYPES), 1.0);
+        assert_eq!(
+            value(StorageDiagnosticsPlugin::COMPONENT_BYTES),
+            expected_bytes.component_bytes as f64
+        );
+        assert!(
+            value(StorageDiagnosticsPlugin::COMPONENT_BYTES)
+                >= (5 * std::mem::size_of::<Position>()) as f64
+        );
+        assert!(
+            value(StorageDiagnosticsPlugin::ALLOCATED_COMPONENT_BYTES)
+                >= value(StorageDiagnosticsPlugin::COMPONENT_BYTES)
+        );
+        assert!(
+            value(StorageDiagnosticsPlugin::RESOURCE_BYTES)
+                >= std::mem::size_of::<Settings>() as f64
+        );
+    }
+}
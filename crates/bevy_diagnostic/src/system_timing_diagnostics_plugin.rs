@@ -0,0 +1,262 @@
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::{
+    schedule::{StageLabel, StageLabelId, SystemTimings},
+    system::{ResMut, Resource},
+};
+use bevy_utils::{get_short_name, HashMap};
+use std::borrow::Cow;
+
+use crate::{Diagnostic, DiagnosticId, Diagnostics, MAX_DIAGNOSTIC_NAME_WIDTH};
+
+/// Adds a "system time" diagnostic for every system and every stage to an App
+///
+/// Run durations are collected through the [`SystemTimings`] resource and published, in
+/// milliseconds, at the end of every frame. A system added to several stages gets a diagnostic
+/// for each of them. Use [`SystemTimingDiagnostics`] to find the diagnostic of a system or
+/// stage, or the slowest systems.
+///
+/// Diagnostics are named after the short name of their system or stage, cut to
+/// [`MAX_DIAGNOSTIC_NAME_WIDTH`] characters.
+pub struct SystemTimingDiagnosticsPlugin {
+    /// The number of measurements kept for each system and stage
+    pub max_history_length: usize,
+}
+
+impl Default for SystemTimingDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            max_history_length: 20,
+        }
+    }
+}
+
+impl Plugin for SystemTimingDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemTimings>()
+            .insert_resource(SystemTimingDiagnostics {
+                max_history_length: self.max_history_length,
+                systems: HashMap::default(),
+                stages: HashMap::default(),
+            })
+            .add_system_to_stage(CoreStage::Last, Self::diagnostic_system);
+    }
+}
+
+impl SystemTimingDiagnosticsPlugin {
+    pub fn diagnostic_system(
+        mut diagnostics: ResMut<Diagnostics>,
+        mut timings: ResMut<SystemTimings>,
+        mut ids: ResMut<SystemTimingDiagnostics>,
+    ) {
+        let max_history_length = ids.max_history_length;
+        for timing in timings.drain_systems() {
+            let id = *ids
+                .systems
+                .entry((timing.stage, timing.name))
+                .or_insert_with_key(|(_, name)| {
+                    let id = DiagnosticId::default();
+                    diagnostics.add(
+                        Diagnostic::new(
+                            id,
+                            diagnostic_name(get_short_name(name)),
+                            max_history_length,
+                        )
+                        .with_suffix("ms"),
+                    );
+                    id
+                });
+            diagnostics.add_measurement(id, || timing.duration.as_secs_f64() * 1000.0);
+        }
+        for (label, duration) in timings.drain_stages() {
+            let id = *ids.stages.entry(label).or_insert_with(|| {
+                let id = DiagnosticId::default();
+                diagnostics.add(
+                    Diagnostic::new(
+                        id,
+                        diagnostic_name(format!("stage {}", label.as_str())),
+                        max_history_length,
+                    )
+                    .with_suffix("ms"),
+                );
+                id
+            });
+            diagnostics.add_measurement(id, || duration.as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+/// Cuts `name` to [`MAX_DIAGNOSTIC_NAME_WIDTH`] characters, keeping its end.
+fn diagnostic_name(name: String) -> String {
+    let len = name.chars().count();
+    if len <= MAX_DIAGNOSTIC_NAME_WIDTH {
+        return name;
+    }
+    std::iter::once('…')
+        .chain(name.chars().skip(len - MAX_DIAGNOSTIC_NAME_WIDTH + 1))
+        .collect()
+}
+
+/// The diagnostics published by the [`SystemTimingDiagnosticsPlugin`]
+#[derive(Resource, Debug)]
+pub struct SystemTimingDiagnostics {
+    max_history_length: usize,
+    systems: HashMap<(Option<StageLabelId>, Cow<'static, str>), DiagnosticId>,
+    stages: HashMap<StageLabelId, DiagnosticId>,
+}
+
+/// Run time statistics of a system or stage, in milliseconds, over the diagnostic history
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingStats {
+    pub id: DiagnosticId,
+    /// The full name of the system, or the name of the diagnostic of the stage
+    pub name: Cow<'static, str>,
+    /// The stage the system ran in, `None` for stages and systems run outside of a `Schedule`
+    pub stage: Option<StageLabelId>,
+    pub min: f64,
+    pub average: f64,
+    pub max: f64,
+}
+
+impl TimingStats {
+    fn from_diagnostic(
+        diagnostic: &Diagnostic,
+        name: Cow<'static, str>,
+        stage: Option<StageLabelId>,
+    ) -> Option<Self> {
+        let average = diagnostic.average()?;
+        let (min, max) = diagnostic
+            .values()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(*value), max.max(*value))
+            });
+        Some(TimingStats {
+            id: diagnostic.id,
+            name,
+            stage,
+            min,
+            average,
+            max,
+        })
+    }
+}
+
+impl SystemTimingDiagnostics {
+    /// Returns the diagnostic of the system with the given full name in the stage, once it has
+    /// run
+    pub fn system_id(&self, stage: impl StageLabel, name: &str) -> Option<DiagnosticId> {
+        self.systems
+            .get(&(Some(stage.as_label()), Cow::Owned(name.to_owned())))
+            .copied()
+    }
+
+    /// Returns the diagnostic of the stage, once it has run
+    pub fn stage_id(&self, label: impl StageLabel) -> Option<DiagnosticId> {
+        self.stages.get(&label.as_label()).copied()
+    }
+
+    /// Returns the run time statistics of the system with the given full name in the stage
+    pub fn system_stats(
+        &self,
+        diagnostics: &Diagnostics,
+        stage: impl StageLabel,
+        name: &str,
+    ) -> Option<TimingStats> {
+        let stage = stage.as_label();
+        let id = self.system_id(stage, name)?;
+        TimingStats::from_diagnostic(
+            diagnostics.get(id)?,
+            Cow::Owned(name.to_owned()),
+            Some(stage),
+        )
+    }
+
+    /// Returns the run time statistics of the stage
+    pub fn stage_stats(
+        &self,
+        diagnostics: &Diagnostics,
+        label: impl StageLabel,
+    ) -> Option<TimingStats> {
+        let diagnostic = diagnostics.get(self.stage_id(label)?)?;
+        TimingStats::from_diagnostic(diagnostic, diagnostic.name.clone(), None)
+    }
+
+    /// Returns the `count` systems with the highest average run time, slowest first
+    pub fn slowest_systems(&self, diagnostics: &Diagnostics, count: usize) -> Vec<TimingStats> {
+        let mut stats: Vec<_> = self
+            .systems
+            .iter()
+            .filter_map(|((stage, name), id)| {
+                TimingStats::from_diagnostic(diagnostics.get(*id)?, name.clone(), *stage)
+            })
+            .collect();
+        stats.sort_by(|a, b| b.average.total_cmp(&a.average));
+        stats.truncate(count);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiagnosticsPlugin;
+
+    fn work() {}
+
+    fn a_system_with_a_name_longer_than_the_diagnostic_name_width() {}
+
+    #[test]
+    fn system_timing_diagnostics() {
+        let mut app = App::new();
+        app.add_plugin(DiagnosticsPlugin)
+            .add_plugin(SystemTimingDiagnosticsPlugin::default())
+            .add_system(work)
+            .add_system_to_stage(CoreStage::PostUpdate, work)
+            .add_system(a_system_with_a_name_longer_than_the_diagnostic_name_width);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let ids = app.world.resource::<SystemTimingDiagnostics>();
+        let diagnostics = app.world.resource::<Diagnostics>();
+        let work_name = concat!(module_path!(), "::work");
+        let update = ids
+            .system_stats(diagnostics, CoreStage::Update, work_name)
+            .unwrap();
+        let post_update = ids
+            .system_stats(diagnostics, CoreStage::PostUpdate, work_name)
+            .unwrap();
+        assert_ne!(update.id, post_update.id);
+        assert_eq!(update.stage, Some(CoreStage::Update.as_label()));
+        assert!(update.min <= update.average && update.average <= update.max);
+        assert!(ids
+            .system_stats(diagnostics, CoreStage::First, work_name)
+            .is_none());
+        assert!(ids.stage_stats(diagnostics, CoreStage::Update).is_some());
+
+        let slowest = ids.slowest_systems(diagnostics, 2);
+        assert_eq!(slowest.len(), 2);
+        assert!(slowest[0].average >= slowest[1].average);
+        let all = ids.slowest_systems(diagnostics, usize::MAX);
+        assert_eq!(
+            all.iter().filter(|stats| stats.name == work_name).count(),
+            2
+        );
+
+        for diagnostic in diagnostics.iter() {
+            assert!(diagnostic.name.chars().count() <= MAX_DIAGNOSTIC_NAME_WIDTH);
+        }
+        let long = diagnostics
+            .get(
+                ids.system_id(
+                    CoreStage::Update,
+                    concat!(
+                        module_path!(),
+                        "::a_system_with_a_name_longer_than_the_diagnostic_name_width"
+                    ),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(long.name, "…_than_the_diagnostic_name_width");
+    }
+}
@@ -273,6 +273,14 @@ impl BundleInfo {
         &self.storage_types
     }
 
+    /// Returns `true` if any component of the bundle has an `on_add` or `on_insert` hook.
+    pub(crate) fn has_insert_hooks(&self, components: &Components) -> bool {
+        self.component_ids
+            .iter()
+            .filter_map(|&id| components.get_info(id))
+            .any(|info| info.hooks().on_add.is_some() || info.hooks().on_insert.is_some())
+    }
+
     pub(crate) fn get_bundle_inserter<'a, 'b>(
         &'b self,
         entities: &'a mut Entities,
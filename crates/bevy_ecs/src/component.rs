@@ -2,8 +2,10 @@
 
 use crate::{
     change_detection::MAX_CHANGE_AGE,
+    entity::{Entity, EntityMap},
     storage::{SparseSetIndex, Storages},
     system::Resource,
+    world::{DeferredWorld, World},
 };
 pub use bevy_ecs_macros::Component;
 use bevy_ptr::OwningPtr;
@@ -111,6 +113,9 @@ use std::{
 /// [newtype pattern]: https://doc.rust-lang.org/book/ch19-03-advanced-traits.html#using-the-newtype-pattern-to-implement-external-traits-on-external-types
 pub trait Component: Send + Sync + 'static {
     type Storage: ComponentStorage;
+
+    /// Called when registering this component, allowing it to set up its [`ComponentHooks`].
+    fn register_component_hooks(_hooks: &mut ComponentHooks) {}
 }
 
 pub struct TableStorage;
@@ -155,6 +160,128 @@ pub enum StorageType {
     SparseSet,
 }
 
+/// A hook run when a component changes on an entity, see [`ComponentHooks`].
+pub type ComponentHook = for<'w> fn(DeferredWorld<'w>, Entity, ComponentId);
+
+/// Functions run synchronously when a component is added to, inserted on or removed from an
+/// entity.
+///
+/// Hooks are registered once per component type, either by overriding
+/// [`Component::register_component_hooks`], through [`World::register_component_hooks`] or
+/// with [`ComponentDescriptor::with_hooks`]. Only one hook of each kind can be registered.
+///
+/// Hooks receive a [`DeferredWorld`]: they can read and mutate components and resources, but
+/// structural changes must go through [`DeferredWorld::commands`]. These commands are applied
+/// once the operation that triggered the hook has completed.
+///
+/// ```
+/// use bevy_ecs::{component::ComponentHooks, prelude::*, world::DeferredWorld};
+///
+/// #[derive(Resource, Default)]
+/// struct Count(usize);
+///
+/// #[derive(Component)]
+/// struct Tracked;
+///
+/// let mut world = World::new();
+/// world.init_resource::<Count>();
+/// world
+///     .register_component_hooks::<Tracked>()
+///     .on_add(|mut world, _, _| world.resource_mut::<Count>().0 += 1)
+///     .on_remove(|mut world, _, _| world.resource_mut::<Count>().0 -= 1);
+///
+/// let entity = world.spawn(Tracked).id();
+/// assert_eq!(world.resource::<Count>().0, 1);
+/// world.despawn(entity);
+/// assert_eq!(world.resource::<Count>().0, 0);
+/// ```
+///
+/// [`World::register_component_hooks`]: crate::world::World::register_component_hooks
+#[derive(Debug, Clone, Default)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Registers a hook run when the component is added to an entity that did not have it.
+    ///
+    /// It runs after the value has been written, before any `on_insert` hook.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an `on_add` hook is already registered.
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_add(hook)
+            .expect("Component already has an on_add hook")
+    }
+
+    /// Registers a hook run every time the component is inserted on an entity, including when
+    /// it replaces an existing value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an `on_insert` hook is already registered.
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_insert(hook)
+            .expect("Component already has an on_insert hook")
+    }
+
+    /// Registers a hook run when the component is removed from an entity, including when the
+    /// entity is despawned.
+    ///
+    /// It runs before the value is removed, so the hook can still read it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an `on_remove` hook is already registered.
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.try_on_remove(hook)
+            .expect("Component already has an on_remove hook")
+    }
+
+    /// Fallible version of [`ComponentHooks::on_add`].
+    ///
+    /// Returns `None` if an `on_add` hook is already registered.
+    pub fn try_on_add(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_add.is_some() {
+            return None;
+        }
+        self.on_add = Some(hook);
+        Some(self)
+    }
+
+    /// Fallible version of [`ComponentHooks::on_insert`].
+    ///
+    /// Returns `None` if an `on_insert` hook is already registered.
+    pub fn try_on_insert(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_insert.is_some() {
+            return None;
+        }
+        self.on_insert = Some(hook);
+        Some(self)
+    }
+
+    /// Fallible version of [`ComponentHooks::on_remove`].
+    ///
+    /// Returns `None` if an `on_remove` hook is already registered.
+    pub fn try_on_remove(&mut self, hook: ComponentHook) -> Option<&mut Self> {
+        if self.on_remove.is_some() {
+            return None;
+        }
+        self.on_remove = Some(hook);
+        Some(self)
+    }
+}
+
+/// Copies a component from a source entity to a target entity, see
+/// [`World::register_component_clone_with`].
+///
+/// The [`EntityMap`] maps every entity being cloned to its copy, which all exist but may not
+/// have their components yet.
+pub type ComponentCloneFn = fn(&mut World, Entity, Entity, &EntityMap);
+
 #[derive(Debug)]
 pub struct ComponentInfo {
     id: ComponentId,
@@ -203,6 +330,19 @@ impl ComponentInfo {
         self.descriptor.is_send_and_sync
     }
 
+    /// Returns the [`ComponentHooks`] registered for this component.
+    #[inline]
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.descriptor.hooks
+    }
+
+    /// Returns the function used to clone this component, if one was registered with
+    /// [`World::register_component_clone`] or [`World::register_component_clone_with`].
+    #[inline]
+    pub fn clone_fn(&self) -> Option<ComponentCloneFn> {
+        self.descriptor.clone
+    }
+
     fn new(id: ComponentId, descriptor: ComponentDescriptor) -> Self {
         ComponentInfo { id, descriptor }
     }
@@ -265,9 +405,11 @@ pub struct ComponentDescriptor {
     // this descriptor describes.
     // None if the underlying type doesn't need to be dropped
     drop: Option<for<'a> unsafe fn(OwningPtr<'a>)>,
+    hooks: ComponentHooks,
+    clone: Option<ComponentCloneFn>,
 }
 
-// We need to ignore the `drop` field in our `Debug` impl
+// We need to ignore the `drop` and `clone` fields in our `Debug` impl
 impl std::fmt::Debug for ComponentDescriptor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ComponentDescriptor")
@@ -276,6 +418,7 @@ impl std::fmt::Debug for ComponentDescriptor {
             .field("is_send_and_sync", &self.is_send_and_sync)
             .field("type_id", &self.type_id)
             .field("layout", &self.layout)
+            .field("hooks", &self.hooks)
             .finish()
     }
 }
@@ -288,6 +431,8 @@ impl ComponentDescriptor {
 
     /// Create a new `ComponentDescriptor` for the type `T`.
     pub fn new<T: Component>() -> Self {
+        let mut hooks = ComponentHooks::default();
+        T::register_component_hooks(&mut hooks);
         Self {
             name: Cow::Borrowed(std::any::type_name::<T>()),
             storage_type: T::Storage::STORAGE_TYPE,
@@ -295,6 +440,8 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            hooks,
+            clone: None,
         }
     }
 
@@ -316,9 +463,17 @@ impl ComponentDescriptor {
             type_id: None,
             layout,
             drop,
+            hooks: ComponentHooks::default(),
+            clone: None,
         }
     }
 
+    /// Sets the [`ComponentHooks`] of the described component.
+    pub fn with_hooks(mut self, hooks: ComponentHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Create a new `ComponentDescriptor` for a resource.
     ///
     /// The [`StorageType`] for resources is always [`TableStorage`].
@@ -332,6 +487,8 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            hooks: ComponentHooks::default(),
+            clone: None,
         }
     }
 
@@ -343,6 +500,8 @@ impl ComponentDescriptor {
             type_id: Some(TypeId::of::<T>()),
             layout: Layout::new::<T>(),
             drop: needs_drop::<T>().then_some(Self::drop_ptr::<T> as _),
+            hooks: ComponentHooks::default(),
+            clone: None,
         }
     }
 
@@ -424,6 +583,20 @@ impl Components {
         self.components.get(id.0)
     }
 
+    /// Returns the [`ComponentHooks`] of the component with the given `id`, allowing new hooks
+    /// to be registered.
+    #[inline]
+    pub fn get_hooks_mut(&mut self, id: ComponentId) -> Option<&mut ComponentHooks> {
+        self.components
+            .get_mut(id.0)
+            .map(|info| &mut info.descriptor.hooks)
+    }
+
+    #[inline]
+    pub(crate) fn set_clone_fn(&mut self, id: ComponentId, clone: ComponentCloneFn) {
+        self.components[id.0].descriptor.clone = Some(clone);
+    }
+
     /// # Safety
     ///
     /// `id` must be a valid [`ComponentId`]
@@ -0,0 +1,33 @@
+use crate::{self as bevy_ecs, component::Component};
+
+/// Marks an entity as disabled.
+///
+/// Disabled entities are skipped by [queries](crate::system::Query), as if every query had a
+/// [`Without<Disabled>`](crate::query::Without) filter. A query sees them again if it
+/// mentions this component anywhere, for example with [`With<Disabled>`](crate::query::With),
+/// `Option<&Disabled>` or one of the filters of an [`Or`](crate::query::Or), or with the
+/// [`IncludeDisabled`](crate::query::IncludeDisabled) filter.
+///
+/// Accessing the entity directly, for example with [`World::get`](crate::world::World::get),
+/// is not affected.
+///
+/// ```
+/// # use bevy_ecs::{prelude::*, entity::Disabled, query::IncludeDisabled};
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// let mut world = World::new();
+/// world.spawn(Enemy);
+/// world.spawn((Enemy, Disabled));
+///
+/// assert_eq!(world.query_filtered::<(), With<Enemy>>().iter(&world).count(), 1);
+/// assert_eq!(
+///     world
+///         .query_filtered::<(), (With<Enemy>, IncludeDisabled)>()
+///         .iter(&world)
+///         .count(),
+///     2
+/// );
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Disabled;
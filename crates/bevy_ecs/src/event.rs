@@ -2,8 +2,14 @@
 
 use crate as bevy_ecs;
 use crate::system::{Local, Res, ResMut, Resource, SystemParam};
+use crate::world::{FromWorld, World};
 use bevy_utils::tracing::{trace, warn};
+use std::collections::VecDeque;
 use std::ops::{Deref, DerefMut};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Weak,
+};
 use std::{fmt, hash::Hash, marker::PhantomData};
 
 /// A type that can be stored in an [`Events<E>`] resource
@@ -47,6 +53,24 @@ impl<E: Event> fmt::Debug for EventId<E> {
     }
 }
 
+/// How long an [`Events`] collection keeps its events, set with [`Events::with_retention`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventRetention {
+    /// Events are dropped by the second [`Events::update`] after they are sent.
+    #[default]
+    DoubleBuffered,
+    /// Events are dropped by the given number of [`Events::update`] calls after they are sent.
+    ///
+    /// `Updates(2)` behaves like [`EventRetention::DoubleBuffered`].
+    Updates(usize),
+    /// Events are dropped by the first [`Events::update`] after every registered reader has read
+    /// them, or after `max_updates` updates if set.
+    ///
+    /// Every [`EventReader`] is registered, and [`Events::get_registered_reader`] creates
+    /// registered [`ManualEventReader`]s. Other readers may miss events.
+    UntilRead { max_updates: Option<usize> },
+}
+
 #[derive(Debug)]
 struct EventInstance<E: Event> {
     pub event_id: EventId<E>,
@@ -115,6 +139,9 @@ struct EventInstance<E: Event> {
 ///
 /// The buffers in [`Events`] will grow indefinitely if [`update`](Events::update) is never called.
 ///
+/// Systems that don't run every frame, because of run criteria or fixed timesteps, can keep
+/// events longer by choosing another [`EventRetention`] with [`Events::with_retention`].
+///
 /// An alternative call pattern would be to call [`update`](Events::update)
 /// manually across frames to control when events are cleared.
 /// This complicates consumption and risks ever-expanding memory usage if not cleaned up,
@@ -132,6 +159,11 @@ pub struct Events<E: Event> {
     /// Holds the newer events.
     events_b: EventSequence<E>,
     event_count: usize,
+    retention: EventRetention,
+    /// The event count at each of the last updates, when the retention is not double buffered.
+    update_event_counts: VecDeque<usize>,
+    /// The position of each registered reader.
+    readers: Vec<Weak<AtomicUsize>>,
 }
 
 // Derived Default impl would incorrectly require E: Default
@@ -141,6 +173,9 @@ impl<E: Event> Default for Events<E> {
             events_a: Default::default(),
             events_b: Default::default(),
             event_count: Default::default(),
+            retention: Default::default(),
+            update_event_counts: Default::default(),
+            readers: Default::default(),
         }
     }
 }
@@ -186,10 +221,38 @@ impl<E: Event> DerefMut for EventSequence<E> {
 /// Reads events of type `T` in order and tracks which events have already been read.
 #[derive(SystemParam)]
 pub struct EventReader<'w, 's, E: Event> {
-    reader: Local<'s, ManualEventReader<E>>,
+    reader: Local<'s, RegisteredEventReader<E>>,
     events: Res<'w, Events<E>>,
 }
 
+/// The [`ManualEventReader`] of an [`EventReader`], registered with the [`Events<E>`] resource
+/// if it exists when the system is initialized.
+#[doc(hidden)]
+pub struct RegisteredEventReader<E: Event>(ManualEventReader<E>);
+
+impl<E: Event> FromWorld for RegisteredEventReader<E> {
+    fn from_world(world: &mut World) -> Self {
+        RegisteredEventReader(match world.get_resource_mut::<Events<E>>() {
+            Some(mut events) => events.get_registered_reader(),
+            None => ManualEventReader::default(),
+        })
+    }
+}
+
+impl<E: Event> Deref for RegisteredEventReader<E> {
+    type Target = ManualEventReader<E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<E: Event> DerefMut for RegisteredEventReader<E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 impl<'w, 's, E: Event> EventReader<'w, 's, E> {
     /// Iterates over the events this [`EventReader`] has not seen yet. This updates the
     /// [`EventReader`]'s event counter, which means subsequent event reads will not include events
@@ -324,6 +387,8 @@ impl<'w, 's, E: Event> EventWriter<'w, 's, E> {
 #[derive(Debug)]
 pub struct ManualEventReader<E: Event> {
     last_event_count: usize,
+    /// Shares `last_event_count` with the [`Events`] this reader is registered with.
+    registration: Option<Arc<AtomicUsize>>,
     _marker: PhantomData<E>,
 }
 
@@ -331,6 +396,7 @@ impl<E: Event> Default for ManualEventReader<E> {
     fn default() -> Self {
         ManualEventReader {
             last_event_count: 0,
+            registration: None,
             _marker: Default::default(),
         }
     }
@@ -369,13 +435,22 @@ impl<E: Event> ManualEventReader<E> {
         let unread_count = a.len() + b.len();
         // Ensure `len` is implemented correctly
         debug_assert_eq!(unread_count, self.len(events));
-        self.last_event_count = events.event_count - unread_count;
+        self.set_last_event_count(events.event_count - unread_count);
         // Iterate the oldest first, then the newer events
         let iterator = a.iter().chain(b.iter());
         iterator
             .map(|e| (&e.event, e.event_id))
             .with_exact_size(unread_count)
-            .inspect(move |(_, id)| self.last_event_count = (id.id + 1).max(self.last_event_count))
+            .inspect(move |(_, id)| {
+                self.set_last_event_count((id.id + 1).max(self.last_event_count));
+            })
+    }
+
+    fn set_last_event_count(&mut self, last_event_count: usize) {
+        self.last_event_count = last_event_count;
+        if let Some(registration) = &self.registration {
+            registration.store(last_event_count, Ordering::Relaxed);
+        }
     }
 
     /// See [`EventReader::len`]
@@ -495,11 +570,51 @@ impl<E: Event> Events<E> {
         }
     }
 
+    /// Gets a new [`ManualEventReader`] that keeps the events it has not read yet when the
+    /// retention is [`EventRetention::UntilRead`]. This will include all events already in the
+    /// event buffers.
+    pub fn get_registered_reader(&mut self) -> ManualEventReader<E> {
+        let registration = Arc::new(AtomicUsize::new(0));
+        self.readers.push(Arc::downgrade(&registration));
+        ManualEventReader {
+            registration: Some(registration),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an empty collection with the given [`EventRetention`].
+    pub fn with_retention(retention: EventRetention) -> Self {
+        Self {
+            retention,
+            ..Default::default()
+        }
+    }
+
+    /// Returns how long events are kept.
+    pub fn retention(&self) -> EventRetention {
+        self.retention
+    }
+
+    /// Changes how long events are kept, starting from the next [`Events::update`].
+    pub fn set_retention(&mut self, retention: EventRetention) {
+        self.retention = retention;
+    }
+
     /// Swaps the event buffers and clears the oldest event buffer. In general, this should be
     /// called once per frame/update.
+    ///
+    /// With a [`EventRetention`] other than the default, the events that must be kept are moved
+    /// to the oldest buffer instead, and the others are dropped.
     pub fn update(&mut self) {
-        std::mem::swap(&mut self.events_a, &mut self.events_b);
-        self.events_b.clear();
+        if self.retention == EventRetention::DoubleBuffered {
+            self.update_event_counts.clear();
+            std::mem::swap(&mut self.events_a, &mut self.events_b);
+            self.events_b.clear();
+        } else {
+            let newer = std::mem::take(&mut self.events_b.events);
+            self.events_a.extend(newer);
+            self.drop_expired_events();
+        }
         self.events_b.start_event_count = self.event_count;
         debug_assert_eq!(
             self.events_a.start_event_count + self.events_a.len(),
@@ -507,6 +622,57 @@ impl<E: Event> Events<E> {
         );
     }
 
+    /// Drops the events that are not kept by the retention.
+    fn drop_expired_events(&mut self) {
+        self.update_event_counts.push_back(self.event_count);
+        let (max_updates, until_read) = match self.retention {
+            EventRetention::DoubleBuffered => (Some(2), false),
+            EventRetention::Updates(updates) => (Some(updates), false),
+            EventRetention::UntilRead { max_updates } => (max_updates, true),
+        };
+
+        // Events sent before the count this many updates ago have expired.
+        let mut expired_count = 0;
+        if let Some(max_updates) = max_updates {
+            let max_updates = max_updates.max(1);
+            while self.update_event_counts.len() > max_updates {
+                self.update_event_counts.pop_front();
+            }
+            if self.update_event_counts.len() == max_updates {
+                expired_count = self.update_event_counts[0];
+            }
+        } else {
+            self.update_event_counts.clear();
+        }
+
+        let mut drop_count = expired_count;
+        if until_read {
+            self.readers.retain(|reader| reader.strong_count() > 0);
+            let read_count = self
+                .readers
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|reader| reader.load(Ordering::Relaxed))
+                .min()
+                .unwrap_or(self.event_count);
+            if expired_count > read_count {
+                let missed = expired_count - read_count.max(self.events_a.start_event_count);
+                if missed > 0 {
+                    let plural = if missed == 1 { "event" } else { "events" };
+                    let type_name = std::any::type_name::<E>();
+                    warn!("Dropped {missed} unread `{type_name}` {plural} after {} updates. Consider reading from the `EventReader` more often or raising the `max_updates` of its retention.", max_updates.unwrap_or_default());
+                }
+            }
+            drop_count = drop_count.max(read_count);
+        }
+
+        let drop_len = drop_count
+            .saturating_sub(self.events_a.start_event_count)
+            .min(self.events_a.len());
+        self.events_a.drain(..drop_len);
+        self.events_a.start_event_count += drop_len;
+    }
+
     /// A system that calls [`Events::update`] once per frame.
     pub fn update_system(mut events: ResMut<Self>) {
         events.update();
@@ -587,6 +753,106 @@ impl<E: Event> std::iter::Extend<E> for Events<E> {
     }
 }
 
+/// Sends events of type `E` into the [`Events<E>`] of a world from any thread or task.
+///
+/// Senders are created by an [`ExternalEvents<E>`] resource, usually added with
+/// `App::add_external_event`, and can be cloned freely. The events are moved into [`Events<E>`] when
+/// [`ExternalEvents::drain_system`] runs.
+#[derive(Debug)]
+pub struct EventSender<E: Event> {
+    sender: async_channel::Sender<E>,
+}
+
+// Derived Clone impl would incorrectly require E: Clone
+impl<E: Event> Clone for EventSender<E> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<E: Event> EventSender<E> {
+    /// Sends an `event`, which will be readable once the events are drained into [`Events<E>`].
+    ///
+    /// Fails, giving the event back, if the [`ExternalEvents<E>`] resource was dropped.
+    pub fn send(&self, event: E) -> Result<(), EventSendError<E>> {
+        self.sender
+            .try_send(event)
+            .map_err(|error| EventSendError(error.into_inner()))
+    }
+
+    /// Sends the default value of the event. Useful when the event is an empty struct.
+    pub fn send_default(&self) -> Result<(), EventSendError<E>>
+    where
+        E: Default,
+    {
+        self.send(Default::default())
+    }
+}
+
+/// An error returned by [`EventSender::send`] when the events can no longer be received.
+pub struct EventSendError<E>(pub E);
+
+impl<E> fmt::Debug for EventSendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("EventSendError").finish_non_exhaustive()
+    }
+}
+
+impl<E> std::error::Error for EventSendError<E> {}
+
+impl<E> fmt::Display for EventSendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot send the `{}` event because its receiver was dropped",
+            std::any::type_name::<E>()
+        )
+    }
+}
+
+/// Receives the events of type `E` sent by [`EventSender`]s from outside the schedule.
+///
+/// The events are moved into [`Events<E>`] by [`ExternalEvents::drain_system`], which
+/// `App::add_external_event` runs right after [`Events::update_system`].
+#[derive(Debug, Resource)]
+pub struct ExternalEvents<E: Event> {
+    sender: async_channel::Sender<E>,
+    receiver: async_channel::Receiver<E>,
+}
+
+impl<E: Event> Default for ExternalEvents<E> {
+    fn default() -> Self {
+        let (sender, receiver) = async_channel::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+impl<E: Event> ExternalEvents<E> {
+    /// Creates an [`EventSender`] for these events.
+    pub fn sender(&self) -> EventSender<E> {
+        EventSender {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Moves the events sent so far into `events`, in the order they were sent.
+    ///
+    /// Events sent while this runs are left for the next call, so senders on other threads
+    /// can't keep it from returning.
+    pub fn drain_into(&self, events: &mut Events<E>) {
+        events.extend((0..self.receiver.len()).map_while(|_| self.receiver.try_recv().ok()));
+    }
+
+    /// A system that calls [`ExternalEvents::drain_into`] once per frame.
+    pub fn drain_system(external_events: Res<Self>, mut events: ResMut<Events<E>>) {
+        if !external_events.receiver.is_empty() {
+            external_events.drain_into(&mut events);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{prelude::World, system::SystemState};
@@ -870,4 +1136,114 @@ mod tests {
         }
         read_for::<EmptyTestEvent>();
     }
+
+    #[test]
+    fn test_external_events() {
+        let external_events = ExternalEvents::<TestEvent>::default();
+        let mut events = Events::<TestEvent>::default();
+        let mut reader = events.get_reader();
+
+        let sender = external_events.sender();
+        std::thread::spawn(move || {
+            for i in 0..3 {
+                sender.send(TestEvent { i }).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+        external_events.sender().send(TestEvent { i: 3 }).unwrap();
+        assert!(events.is_empty());
+
+        external_events.drain_into(&mut events);
+        let received: Vec<_> = get_events(&events, &mut reader)
+            .into_iter()
+            .map(|event| event.i)
+            .collect();
+        assert_eq!(received, vec![0, 1, 2, 3]);
+
+        let sender = external_events.sender();
+        drop(external_events);
+        assert!(sender.send(TestEvent { i: 4 }).is_err());
+    }
+
+    #[test]
+    fn test_events_retention_updates() {
+        let mut events = Events::<TestEvent>::with_retention(EventRetention::Updates(3));
+        let mut reader = events.get_reader();
+        events.send(TestEvent { i: 0 });
+        events.update();
+        events.send(TestEvent { i: 1 });
+        events.update();
+        assert_eq!(events.len(), 2);
+        events.update();
+        assert_eq!(get_events(&events, &mut reader), vec![TestEvent { i: 1 }]);
+        events.update();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_events_retention_until_read() {
+        let mut events =
+            Events::<TestEvent>::with_retention(EventRetention::UntilRead { max_updates: None });
+        let mut reader_a = events.get_registered_reader();
+        let mut reader_b = events.get_registered_reader();
+        events.send(TestEvent { i: 0 });
+        for _ in 0..5 {
+            events.update();
+        }
+        assert_eq!(get_events(&events, &mut reader_a), vec![TestEvent { i: 0 }]);
+        events.update();
+        assert_eq!(events.len(), 1, "reader_b has not read the event yet");
+
+        events.send(TestEvent { i: 1 });
+        assert_eq!(
+            get_events(&events, &mut reader_b),
+            vec![TestEvent { i: 0 }, TestEvent { i: 1 }]
+        );
+        events.update();
+        assert_eq!(
+            events.len(),
+            1,
+            "reader_a has not read the second event yet"
+        );
+        drop(reader_a);
+        events.update();
+        assert!(events.is_empty(), "dropped readers are not waited for");
+    }
+
+    #[test]
+    fn test_events_retention_until_read_max_updates() {
+        let mut events = Events::<TestEvent>::with_retention(EventRetention::UntilRead {
+            max_updates: Some(3),
+        });
+        let mut reader = events.get_registered_reader();
+        events.send(TestEvent { i: 0 });
+        events.update();
+        events.update();
+        assert_eq!(events.len(), 1);
+        events.update();
+        assert!(events.is_empty());
+        assert_eq!(reader.missed_events(&events), 1);
+        assert!(get_events(&events, &mut reader).is_empty());
+    }
+
+    #[test]
+    fn test_event_reader_is_registered() {
+        let mut world = World::new();
+        world.insert_resource(Events::<TestEvent>::with_retention(
+            EventRetention::UntilRead { max_updates: None },
+        ));
+        let mut state = SystemState::<EventReader<TestEvent>>::new(&mut world);
+        world
+            .resource_mut::<Events<TestEvent>>()
+            .send(TestEvent { i: 0 });
+        for _ in 0..5 {
+            world.resource_mut::<Events<TestEvent>>().update();
+        }
+
+        let events: Vec<_> = state.get(&world).iter().copied().collect();
+        assert_eq!(events, vec![TestEvent { i: 0 }]);
+        world.resource_mut::<Events<TestEvent>>().update();
+        assert!(world.resource::<Events<TestEvent>>().is_empty());
+    }
 }
@@ -170,6 +170,9 @@ pub struct FilteredAccess<T: SparseSetIndex> {
     access: Access<T>,
     with: FixedBitSet,
     without: FixedBitSet,
+    /// The elements required or excluded by any filter, including the filters of `Or` and
+    /// `Option` that are not kept in `with` and `without`.
+    filtered: FixedBitSet,
 }
 
 impl<T: SparseSetIndex> Default for FilteredAccess<T> {
@@ -178,6 +181,7 @@ impl<T: SparseSetIndex> Default for FilteredAccess<T> {
             access: Access::default(),
             with: Default::default(),
             without: Default::default(),
+            filtered: Default::default(),
         }
     }
 }
@@ -219,21 +223,37 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
     pub fn add_with(&mut self, index: T) {
         self.with.grow(index.sparse_set_index() + 1);
         self.with.insert(index.sparse_set_index());
+        self.add_filtered(index);
     }
 
     /// Retains only combinations where the element given by `index` is not present.
     pub fn add_without(&mut self, index: T) {
         self.without.grow(index.sparse_set_index() + 1);
         self.without.insert(index.sparse_set_index());
+        self.add_filtered(index);
+    }
+
+    fn add_filtered(&mut self, index: T) {
+        self.filtered.grow(index.sparse_set_index() + 1);
+        self.filtered.insert(index.sparse_set_index());
+    }
+
+    /// Returns `true` if the element given by `index` is accessed, or required or excluded by
+    /// any filter, even one of the arms of an `Or`.
+    pub fn references(&self, index: T) -> bool {
+        let index = index.sparse_set_index();
+        self.access.reads_and_writes.contains(index) || self.filtered.contains(index)
     }
 
     pub fn extend_intersect_filter(&mut self, other: &FilteredAccess<T>) {
         self.without.intersect_with(&other.without);
         self.with.intersect_with(&other.with);
+        self.filtered.union_with(&other.filtered);
     }
 
     pub fn extend_access(&mut self, other: &FilteredAccess<T>) {
         self.access.extend(&other.access);
+        self.filtered.union_with(&other.filtered);
     }
 
     /// Returns `true` if this and `other` can be active at the same time.
@@ -260,6 +280,7 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
         self.access.extend(&access.access);
         self.with.union_with(&access.with);
         self.without.union_with(&access.without);
+        self.filtered.union_with(&access.filtered);
     }
 
     /// Sets the underlying unfiltered access as having access to all indexed elements.
@@ -386,8 +407,8 @@ mod tests {
         let mut access_d = Access::<usize>::default();
         access_d.add_read(0);
 
-        assert_eq!(access_d.get_conflicts(&access_a), vec![]);
-        assert_eq!(access_d.get_conflicts(&access_b), vec![]);
+        assert_eq!(access_d.get_conflicts(&access_a), Vec::<usize>::new());
+        assert_eq!(access_d.get_conflicts(&access_b), Vec::<usize>::new());
         assert_eq!(access_d.get_conflicts(&access_c), vec![0]);
     }
 
@@ -0,0 +1,946 @@
+use crate::{
+    archetype::{Archetype, ArchetypeComponentId, ArchetypeGeneration, ArchetypeId},
+    change_detection::{MutUntyped, Ticks},
+    component::{ComponentId, ComponentTicks, StorageType},
+    entity::{Disabled, Entity},
+    query::{Access, FilteredAccess},
+    storage::Table,
+    world::{World, WorldId},
+};
+use bevy_ptr::{Ptr, UnsafeCellDeref};
+use bevy_tasks::ComputeTaskPool;
+use fixedbitset::FixedBitSet;
+use std::{cell::UnsafeCell, fmt, ptr::NonNull};
+
+/// How a [`DynamicQueryState`] accesses a fetched component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicAccess {
+    /// The component is read through a [`Ptr`].
+    Read,
+    /// The component is written through a [`MutUntyped`].
+    Write,
+}
+
+/// Builds a [`DynamicQueryState`] from [`ComponentId`]s known only at runtime.
+///
+/// Components added with [`read`](Self::read) and [`write`](Self::write) are fetched in the
+/// order they were added. The other methods only filter the matched entities.
+///
+/// ```
+/// use bevy_ecs::{change_detection::DetectChanges, prelude::*, query::QueryBuilder};
+///
+/// #[derive(Component)]
+/// struct Position(f32);
+/// #[derive(Component)]
+/// struct Velocity(f32);
+/// #[derive(Component)]
+/// struct Frozen;
+///
+/// let mut world = World::new();
+/// world.spawn((Position(0.0), Velocity(1.0)));
+/// world.spawn((Position(0.0), Velocity(1.0), Frozen));
+///
+/// let position = world.init_component::<Position>();
+/// let velocity = world.init_component::<Velocity>();
+/// let frozen = world.init_component::<Frozen>();
+///
+/// let mut query = QueryBuilder::new(&mut world)
+///     .write(position)
+///     .read(velocity)
+///     .without(frozen)
+///     .build();
+///
+/// for mut item in query.iter_mut(&mut world) {
+///     // SAFETY: the components were fetched in the order `Position`, `Velocity`
+///     let velocity = unsafe { item.components()[1].as_ptr().deref::<Velocity>().0 };
+///     let position = item.components_mut()[0].as_mut().unwrap();
+///     position.set_changed();
+///     let position = position.bypass_change_detection().as_ptr().cast::<Position>();
+///     // SAFETY: the pointer was fetched for `Position`
+///     unsafe { (*position).0 += velocity };
+/// }
+/// ```
+pub struct QueryBuilder<'w> {
+    world: &'w mut World,
+    fetches: Vec<(ComponentId, DynamicAccess)>,
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+    changed: Vec<ComponentId>,
+    added: Vec<ComponentId>,
+}
+
+impl<'w> QueryBuilder<'w> {
+    /// Creates a builder for a query on `world`, which initially matches all entities.
+    pub fn new(world: &'w mut World) -> Self {
+        Self {
+            world,
+            fetches: Vec::new(),
+            with: Vec::new(),
+            without: Vec::new(),
+            changed: Vec::new(),
+            added: Vec::new(),
+        }
+    }
+
+    /// Fetches the component read-only, matching entities that have it.
+    pub fn read(mut self, component_id: ComponentId) -> Self {
+        self.fetches.push((component_id, DynamicAccess::Read));
+        self
+    }
+
+    /// Fetches the component mutably, matching entities that have it.
+    pub fn write(mut self, component_id: ComponentId) -> Self {
+        self.fetches.push((component_id, DynamicAccess::Write));
+        self
+    }
+
+    /// Only matches entities that have the component, like [`With`](crate::query::With).
+    pub fn with(mut self, component_id: ComponentId) -> Self {
+        self.with.push(component_id);
+        self
+    }
+
+    /// Only matches entities that don't have the component, like
+    /// [`Without`](crate::query::Without).
+    pub fn without(mut self, component_id: ComponentId) -> Self {
+        self.without.push(component_id);
+        self
+    }
+
+    /// Only matches entities whose component changed since the last change tick of the query,
+    /// like [`Changed`](crate::query::Changed).
+    ///
+    /// This is the last change tick of the world, unless the query is iterated with
+    /// [`DynamicQueryState::iter_with_ticks`] or another method taking the ticks.
+    pub fn changed(mut self, component_id: ComponentId) -> Self {
+        self.changed.push(component_id);
+        self
+    }
+
+    /// Only matches entities whose component was added since the last change tick of the
+    /// query, like [`Added`](crate::query::Added). See [`changed`](Self::changed).
+    pub fn added(mut self, component_id: ComponentId) -> Self {
+        self.added.push(component_id);
+        self
+    }
+
+    /// Builds the [`DynamicQueryState`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`ComponentId`] is not registered in the world, or if a component fetched
+    /// with [`write`](Self::write) is also fetched another time.
+    pub fn build(self) -> DynamicQueryState {
+        let Self {
+            world,
+            fetches,
+            with,
+            mut without,
+            changed,
+            added,
+        } = self;
+
+        let all_ids = fetches
+            .iter()
+            .map(|(id, _)| id)
+            .chain(with.iter().chain(&without).chain(&changed).chain(&added));
+        for id in all_ids {
+            assert!(
+                world.components().get_info(*id).is_some(),
+                "{id:?} is not a registered component in this world"
+            );
+        }
+        for (index, &(id, access)) in fetches.iter().enumerate() {
+            if access == DynamicAccess::Write {
+                assert!(
+                    fetches
+                        .iter()
+                        .enumerate()
+                        .all(|(other, &(other_id, _))| other == index || other_id != id),
+                    "{} is written by the query and cannot be fetched another time",
+                    world.components().get_info(id).unwrap().name()
+                );
+            }
+        }
+
+        let mut component_access = FilteredAccess::default();
+        for &(id, access) in &fetches {
+            match access {
+                DynamicAccess::Read => component_access.add_read(id),
+                DynamicAccess::Write => component_access.add_write(id),
+            }
+        }
+        for &id in &with {
+            component_access.add_with(id);
+        }
+        for &id in &without {
+            component_access.add_without(id);
+        }
+        for &id in changed.iter().chain(&added) {
+            component_access.add_read(id);
+        }
+
+        // Disabled entities are skipped unless the query mentions the marker, like `QueryState`.
+        let disabled_id = world.init_component::<Disabled>();
+        if !component_access.references(disabled_id) {
+            without.push(disabled_id);
+        }
+
+        let mut state = DynamicQueryState {
+            world_id: world.id(),
+            archetype_generation: ArchetypeGeneration::initial(),
+            matched_archetypes: FixedBitSet::default(),
+            matched_archetype_ids: Vec::new(),
+            component_access,
+            archetype_component_access: Access::default(),
+            fetches,
+            with,
+            without,
+            changed,
+            added,
+        };
+        state.update_archetypes(world);
+        state
+    }
+}
+
+/// A query whose components are chosen at runtime, built with a [`QueryBuilder`].
+///
+/// This is the dynamic equivalent of [`QueryState`](crate::query::QueryState), yielding a
+/// [`DynamicQueryItem`] per matched entity.
+///
+/// As its components are only known at runtime, it can't be a [`SystemParam`]. Code running it
+/// next to other queries, like an exclusive system or a custom executor, can check its
+/// [`component_access`](Self::component_access) for conflicts, and pass the change ticks of the
+/// system to [`iter_with_ticks`](Self::iter_with_ticks) or the unsafe `_manual` methods.
+///
+/// [`SystemParam`]: crate::system::SystemParam
+pub struct DynamicQueryState {
+    world_id: WorldId,
+    archetype_generation: ArchetypeGeneration,
+    matched_archetypes: FixedBitSet,
+    matched_archetype_ids: Vec<ArchetypeId>,
+    component_access: FilteredAccess<ComponentId>,
+    archetype_component_access: Access<ArchetypeComponentId>,
+    fetches: Vec<(ComponentId, DynamicAccess)>,
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+    changed: Vec<ComponentId>,
+    added: Vec<ComponentId>,
+}
+
+impl fmt::Debug for DynamicQueryState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicQueryState")
+            .field("fetches", &self.fetches)
+            .field("with", &self.with)
+            .field("without", &self.without)
+            .field("changed", &self.changed)
+            .field("added", &self.added)
+            .field("matched_archetypes", &self.matched_archetype_ids.len())
+            .finish()
+    }
+}
+
+impl DynamicQueryState {
+    /// Returns the fetched components and how they are accessed, in fetch order.
+    pub fn fetches(&self) -> &[(ComponentId, DynamicAccess)] {
+        &self.fetches
+    }
+
+    /// Returns the components read and written by the query, and its filters.
+    ///
+    /// Like the access of a [`QueryState`](crate::query::QueryState), it can be checked for
+    /// conflicts with [`FilteredAccess::is_compatible`].
+    pub fn component_access(&self) -> &FilteredAccess<ComponentId> {
+        &self.component_access
+    }
+
+    /// Returns the archetype components read and written by the query, in the archetypes
+    /// matched so far.
+    pub fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+        &self.archetype_component_access
+    }
+
+    /// Returns `true` if no component is fetched with [`DynamicAccess::Write`].
+    pub fn is_read_only(&self) -> bool {
+        self.fetches
+            .iter()
+            .all(|(_, access)| *access == DynamicAccess::Read)
+    }
+
+    /// Updates the matched archetypes with the ones created in `world` since the last update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `world` is not the world this query was built for.
+    pub fn update_archetypes(&mut self, world: &World) {
+        self.validate_world(world);
+        let archetypes = world.archetypes();
+        let new_generation = archetypes.generation();
+        let old_generation = std::mem::replace(&mut self.archetype_generation, new_generation);
+        for archetype_index in old_generation.value()..new_generation.value() {
+            self.new_archetype(&archetypes[ArchetypeId::new(archetype_index)]);
+        }
+    }
+
+    fn new_archetype(&mut self, archetype: &Archetype) {
+        let required = self
+            .fetches
+            .iter()
+            .map(|(id, _)| id)
+            .chain(&self.with)
+            .chain(&self.changed)
+            .chain(&self.added);
+        let matches = required.clone().all(|id| archetype.contains(*id))
+            && !self.without.iter().any(|id| archetype.contains(*id));
+        if !matches {
+            return;
+        }
+        for &(id, access) in &self.fetches {
+            if let Some(archetype_component_id) = archetype.get_archetype_component_id(id) {
+                match access {
+                    DynamicAccess::Read => self
+                        .archetype_component_access
+                        .add_read(archetype_component_id),
+                    DynamicAccess::Write => self
+                        .archetype_component_access
+                        .add_write(archetype_component_id),
+                }
+            }
+        }
+        for &id in self.changed.iter().chain(&self.added) {
+            if let Some(archetype_component_id) = archetype.get_archetype_component_id(id) {
+                self.archetype_component_access
+                    .add_read(archetype_component_id);
+            }
+        }
+        let archetype_index = archetype.id().index();
+        if !self.matched_archetypes.contains(archetype_index) {
+            self.matched_archetypes.grow(archetype_index + 1);
+            self.matched_archetypes.set(archetype_index, true);
+            self.matched_archetype_ids.push(archetype.id());
+        }
+    }
+
+    /// Returns an iterator over the matched entities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query writes a component, use [`iter_mut`](Self::iter_mut) instead.
+    pub fn iter<'w, 's>(&'s mut self, world: &'w World) -> DynamicQueryIter<'w, 's> {
+        self.iter_with_ticks(world, world.last_change_tick(), world.read_change_tick())
+    }
+
+    /// Returns an iterator over the matched entities, whose [`changed`](QueryBuilder::changed)
+    /// and [`added`](QueryBuilder::added) filters compare against `last_change_tick` instead of
+    /// the last change tick of the world.
+    ///
+    /// A system passes its own last change tick and the current change tick here, like a
+    /// [`Query`](crate::system::Query) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query writes a component.
+    pub fn iter_with_ticks<'w, 's>(
+        &'s mut self,
+        world: &'w World,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> DynamicQueryIter<'w, 's> {
+        self.update_archetypes(world);
+        self.iter_manual(world, last_change_tick, change_tick)
+    }
+
+    /// Returns an iterator over the matched entities without updating the matched archetypes,
+    /// which must be updated before with [`update_archetypes`](Self::update_archetypes).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query writes a component or if `world` is not the world this query was
+    /// built for.
+    pub fn iter_manual<'w, 's>(
+        &'s self,
+        world: &'w World,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> DynamicQueryIter<'w, 's> {
+        self.assert_read_only();
+        self.validate_world(world);
+        // SAFETY: the query is read-only and the world is validated
+        unsafe { self.iter_unchecked_manual(world, last_change_tick, change_tick) }
+    }
+
+    /// Returns an iterator over the matched entities.
+    pub fn iter_mut<'w, 's>(&'s mut self, world: &'w mut World) -> DynamicQueryIter<'w, 's> {
+        self.update_archetypes(world);
+        let (last_change_tick, change_tick) = (world.last_change_tick(), world.read_change_tick());
+        // SAFETY: the query has unique world access
+        unsafe { self.iter_unchecked_manual(world, last_change_tick, change_tick) }
+    }
+
+    /// Returns the item of `entity`, or `None` if it doesn't match the query.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query writes a component, use [`get_mut`](Self::get_mut) instead.
+    pub fn get<'w>(&mut self, world: &'w World, entity: Entity) -> Option<DynamicQueryItem<'w>> {
+        self.assert_read_only();
+        self.update_archetypes(world);
+        // SAFETY: the query is read-only
+        unsafe {
+            self.get_unchecked_manual(
+                world,
+                entity,
+                world.last_change_tick(),
+                world.read_change_tick(),
+            )
+        }
+    }
+
+    /// Returns the item of `entity`, or `None` if it doesn't match the query.
+    pub fn get_mut<'w>(
+        &mut self,
+        world: &'w mut World,
+        entity: Entity,
+    ) -> Option<DynamicQueryItem<'w>> {
+        self.update_archetypes(world);
+        let (last_change_tick, change_tick) = (world.last_change_tick(), world.read_change_tick());
+        // SAFETY: the query has unique world access
+        unsafe { self.get_unchecked_manual(world, entity, last_change_tick, change_tick) }
+    }
+
+    /// Runs `func` on each matched entity in parallel, in batches of `batch_size` entities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query writes a component, use [`par_for_each_mut`](Self::par_for_each_mut)
+    /// instead. Also panics if the [`ComputeTaskPool`] is not initialized.
+    pub fn par_for_each<'w>(
+        &mut self,
+        world: &'w World,
+        batch_size: usize,
+        func: impl Fn(DynamicQueryItem<'w>) + Send + Sync + Clone,
+    ) {
+        self.assert_read_only();
+        self.update_archetypes(world);
+        // SAFETY: the query is read-only
+        unsafe {
+            self.par_for_each_unchecked_manual(
+                world,
+                batch_size,
+                func,
+                world.last_change_tick(),
+                world.read_change_tick(),
+            )
+        }
+    }
+
+    /// Runs `func` on each matched entity in parallel, in batches of `batch_size` entities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`ComputeTaskPool`] is not initialized.
+    pub fn par_for_each_mut<'w>(
+        &mut self,
+        world: &'w mut World,
+        batch_size: usize,
+        func: impl Fn(DynamicQueryItem<'w>) + Send + Sync + Clone,
+    ) {
+        self.update_archetypes(world);
+        let (last_change_tick, change_tick) = (world.last_change_tick(), world.read_change_tick());
+        // SAFETY: the query has unique world access
+        unsafe {
+            self.par_for_each_unchecked_manual(
+                world,
+                batch_size,
+                func,
+                last_change_tick,
+                change_tick,
+            )
+        }
+    }
+
+    fn validate_world(&self, world: &World) {
+        assert!(
+            world.id() == self.world_id,
+            "Attempted to use a DynamicQueryState with a mismatched World."
+        );
+    }
+
+    fn assert_read_only(&self) {
+        assert!(
+            self.is_read_only(),
+            "This query writes components and requires mutable world access"
+        );
+    }
+
+    /// Returns an iterator over the matched entities, with the given change ticks.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `world` is the world this query was built for, that no
+    /// other access conflicts with the [`component_access`](Self::component_access) of this
+    /// query, and that the archetypes are up to date.
+    pub unsafe fn iter_unchecked_manual<'w, 's>(
+        &'s self,
+        world: &'w World,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> DynamicQueryIter<'w, 's> {
+        DynamicQueryIter {
+            world,
+            state: self,
+            archetype_ids: self.matched_archetype_ids.iter(),
+            archetype: None,
+            index: 0,
+            last_change_tick,
+            change_tick,
+        }
+    }
+
+    /// Returns the item of `entity` with the given change ticks, or `None` if it doesn't match
+    /// the query.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::iter_unchecked_manual`].
+    pub unsafe fn get_unchecked_manual<'w>(
+        &self,
+        world: &'w World,
+        entity: Entity,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<DynamicQueryItem<'w>> {
+        let location = world.entities().get(entity)?;
+        if !self
+            .matched_archetypes
+            .contains(location.archetype_id.index())
+        {
+            return None;
+        }
+        let archetype = &world.archetypes()[location.archetype_id];
+        self.fetch(
+            world,
+            archetype,
+            location.index,
+            last_change_tick,
+            change_tick,
+        )
+    }
+
+    /// Runs `func` on each matched entity in parallel with the given change ticks.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::iter_unchecked_manual`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`ComputeTaskPool`] is not initialized.
+    pub unsafe fn par_for_each_unchecked_manual<'w>(
+        &self,
+        world: &'w World,
+        batch_size: usize,
+        func: impl Fn(DynamicQueryItem<'w>) + Send + Sync + Clone,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) {
+        ComputeTaskPool::get().scope(|scope| {
+            for archetype_id in &self.matched_archetype_ids {
+                let archetype = &world.archetypes()[*archetype_id];
+                let mut offset = 0;
+                while offset < archetype.len() {
+                    let func = func.clone();
+                    let len = batch_size.min(archetype.len() - offset);
+                    scope.spawn(async move {
+                        for index in offset..offset + len {
+                            if let Some(item) =
+                                self.fetch(world, archetype, index, last_change_tick, change_tick)
+                            {
+                                func(item);
+                            }
+                        }
+                    });
+                    offset += batch_size;
+                }
+            }
+        });
+    }
+
+    /// Fetches the entity at `index` in `archetype`, or returns `None` if it is filtered out.
+    ///
+    /// # Safety
+    ///
+    /// `archetype` must be matched by this query and `index` must be in bounds. See also
+    /// [`Self::iter_unchecked_manual`].
+    unsafe fn fetch<'w>(
+        &self,
+        world: &'w World,
+        archetype: &'w Archetype,
+        index: usize,
+        last_change_tick: u32,
+        change_tick: u32,
+    ) -> Option<DynamicQueryItem<'w>> {
+        let archetype_entity = &archetype.entities()[index];
+        let entity = archetype_entity.entity();
+        let row = archetype_entity.table_row();
+        let table = &world.storages().tables[archetype.table_id()];
+
+        for &id in &self.changed {
+            let (_, ticks) = get_component_and_ticks(world, table, id, entity, row);
+            if !ticks.deref().is_changed(last_change_tick, change_tick) {
+                return None;
+            }
+        }
+        for &id in &self.added {
+            let (_, ticks) = get_component_and_ticks(world, table, id, entity, row);
+            if !ticks.deref().is_added(last_change_tick, change_tick) {
+                return None;
+            }
+        }
+
+        let components = self
+            .fetches
+            .iter()
+            .map(|&(id, access)| {
+                let (value, ticks) = get_component_and_ticks(world, table, id, entity, row);
+                match access {
+                    DynamicAccess::Read => DynamicComponent::Read(value),
+                    DynamicAccess::Write => DynamicComponent::Write(MutUntyped {
+                        value: value.assert_unique(),
+                        ticks: Ticks {
+                            component_ticks: ticks.deref_mut(),
+                            last_change_tick,
+                            change_tick,
+                        },
+                    }),
+                }
+            })
+            .collect();
+        Some(DynamicQueryItem { entity, components })
+    }
+}
+
+/// # Safety
+///
+/// `id` must be a component of the archetype stored in `table` at `row`.
+unsafe fn get_component_and_ticks<'w>(
+    world: &'w World,
+    table: &'w Table,
+    id: ComponentId,
+    entity: Entity,
+    row: usize,
+) -> (Ptr<'w>, &'w UnsafeCell<ComponentTicks>) {
+    match world.components().get_info_unchecked(id).storage_type() {
+        StorageType::Table => {
+            let column = table.get_column(id).unwrap();
+            (
+                column.get_data_unchecked(row),
+                column.get_ticks_unchecked(row),
+            )
+        }
+        StorageType::SparseSet => world
+            .storages()
+            .sparse_sets
+            .get(id)
+            .and_then(|sparse_set| sparse_set.get_with_ticks(entity))
+            .unwrap(),
+    }
+}
+
+/// A component fetched by a [`DynamicQueryState`].
+pub enum DynamicComponent<'w> {
+    /// A component fetched with [`DynamicAccess::Read`].
+    Read(Ptr<'w>),
+    /// A component fetched with [`DynamicAccess::Write`].
+    Write(MutUntyped<'w>),
+}
+
+impl<'w> DynamicComponent<'w> {
+    /// Returns a read-only pointer to the component.
+    pub fn as_ptr(&self) -> Ptr<'_> {
+        match self {
+            DynamicComponent::Read(ptr) => {
+                // SAFETY: the returned pointer borrows `self`
+                unsafe { Ptr::new(NonNull::new_unchecked(ptr.as_ptr())) }
+            }
+            DynamicComponent::Write(mut_untyped) => {
+                // SAFETY: the returned pointer borrows `self`, preventing mutable access
+                unsafe { Ptr::new(NonNull::new_unchecked(mut_untyped.value.as_ptr())) }
+            }
+        }
+    }
+
+    /// Returns the mutable access to the component, or `None` if it was fetched read-only.
+    pub fn as_mut(&mut self) -> Option<&mut MutUntyped<'w>> {
+        match self {
+            DynamicComponent::Read(_) => None,
+            DynamicComponent::Write(mut_untyped) => Some(mut_untyped),
+        }
+    }
+}
+
+/// The components of an entity matched by a [`DynamicQueryState`].
+pub struct DynamicQueryItem<'w> {
+    entity: Entity,
+    components: Vec<DynamicComponent<'w>>,
+}
+
+impl<'w> DynamicQueryItem<'w> {
+    /// Returns the matched entity.
+    #[inline]
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns the fetched components, in the order they were added to the [`QueryBuilder`].
+    #[inline]
+    pub fn components(&self) -> &[DynamicComponent<'w>] {
+        &self.components
+    }
+
+    /// Returns the fetched components, in the order they were added to the [`QueryBuilder`].
+    #[inline]
+    pub fn components_mut(&mut self) -> &mut [DynamicComponent<'w>] {
+        &mut self.components
+    }
+
+    /// Consumes the item, returning the fetched components.
+    #[inline]
+    pub fn into_components(self) -> Vec<DynamicComponent<'w>> {
+        self.components
+    }
+}
+
+/// An iterator over the entities matched by a [`DynamicQueryState`].
+pub struct DynamicQueryIter<'w, 's> {
+    world: &'w World,
+    state: &'s DynamicQueryState,
+    archetype_ids: std::slice::Iter<'s, ArchetypeId>,
+    archetype: Option<&'w Archetype>,
+    index: usize,
+    last_change_tick: u32,
+    change_tick: u32,
+}
+
+impl<'w, 's> Iterator for DynamicQueryIter<'w, 's> {
+    type Item = DynamicQueryItem<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(archetype) = self.archetype {
+                while self.index < archetype.len() {
+                    let index = self.index;
+                    self.index += 1;
+                    // SAFETY: the archetype is matched and `index` is in bounds. The state
+                    // ensured access is valid when creating this iterator, and each entity is
+                    // only fetched once.
+                    let item = unsafe {
+                        self.state.fetch(
+                            self.world,
+                            archetype,
+                            index,
+                            self.last_change_tick,
+                            self.change_tick,
+                        )
+                    };
+                    if item.is_some() {
+                        return item;
+                    }
+                }
+            }
+            let archetype_id = self.archetype_ids.next()?;
+            self.archetype = Some(&self.world.archetypes()[*archetype_id]);
+            self.index = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryBuilder;
+    use crate::{self as bevy_ecs, change_detection::DetectChanges, prelude::*, query::QueryState};
+    use bevy_tasks::{ComputeTaskPool, TaskPool};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Component, Debug, PartialEq)]
+    struct A(usize);
+    #[derive(Component, Debug, PartialEq)]
+    #[component(storage = "SparseSet")]
+    struct B(usize);
+    #[derive(Component)]
+    struct C;
+
+    #[test]
+    fn dynamic_query_fetches_and_filters() {
+        let mut world = World::new();
+        let e1 = world.spawn((A(1), B(10))).id();
+        world.spawn((A(2), B(20), C));
+        world.spawn(A(3));
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+
+        let mut query = QueryBuilder::new(&mut world)
+            .read(b)
+            .read(a)
+            .without(c)
+            .build();
+        let results: Vec<_> = query
+            .iter(&world)
+            .map(|item| {
+                // SAFETY: the components were fetched in the order `B`, `A`
+                unsafe {
+                    (
+                        item.entity(),
+                        item.components()[0].as_ptr().deref::<B>().0,
+                        item.components()[1].as_ptr().deref::<A>().0,
+                    )
+                }
+            })
+            .collect();
+        assert_eq!(results, [(e1, 10, 1)]);
+
+        // Archetypes created after building the query are matched too.
+        let e4 = world.spawn((A(4), B(40))).id();
+        assert_eq!(query.iter(&world).count(), 2);
+        assert!(query.get(&world, e4).is_some());
+    }
+
+    #[test]
+    fn dynamic_query_writes_and_change_filters() {
+        let mut world = World::new();
+        let e1 = world.spawn(A(1)).id();
+        let e2 = world.spawn(A(2)).id();
+        let a = world.init_component::<A>();
+        world.clear_trackers();
+
+        let mut changed = QueryBuilder::new(&mut world).changed(a).build();
+        let mut added = QueryBuilder::new(&mut world).added(a).build();
+        assert_eq!(changed.iter(&world).count(), 0);
+
+        let mut query = QueryBuilder::new(&mut world).write(a).build();
+        for mut item in query.iter_mut(&mut world) {
+            if item.entity() == e1 {
+                let component = item.components_mut()[0].as_mut().unwrap();
+                component.set_changed();
+                // SAFETY: the component is an `A`
+                unsafe { (*component.bypass_change_detection().as_ptr().cast::<A>()).0 += 10 };
+            }
+        }
+        assert_eq!(world.get::<A>(e1), Some(&A(11)));
+        assert_eq!(world.get::<A>(e2), Some(&A(2)));
+
+        let changed_entities: Vec<_> = changed.iter(&world).map(|item| item.entity()).collect();
+        assert_eq!(changed_entities, [e1]);
+        assert_eq!(added.iter(&world).count(), 0);
+
+        let e3 = world.spawn(A(3)).id();
+        let added_entities: Vec<_> = added.iter(&world).map(|item| item.entity()).collect();
+        assert_eq!(added_entities, [e3]);
+    }
+
+    #[test]
+    fn dynamic_query_change_ticks() {
+        let mut world = World::new();
+        let e1 = world.spawn(A(1)).id();
+        let e2 = world.spawn(A(2)).id();
+        let a = world.init_component::<A>();
+        world.clear_trackers();
+        let system_last_change_tick = world.read_change_tick();
+        world.increment_change_tick();
+        world.get_mut::<A>(e1).unwrap().0 += 1;
+        world.clear_trackers();
+        world.increment_change_tick();
+        world.get_mut::<A>(e2).unwrap().0 += 1;
+
+        let mut changed = QueryBuilder::new(&mut world).changed(a).build();
+        let entities: Vec<_> = changed.iter(&world).map(|item| item.entity()).collect();
+        assert_eq!(entities, [e2]);
+
+        let change_tick = world.read_change_tick();
+        let entities: Vec<_> = changed
+            .iter_with_ticks(&world, system_last_change_tick, change_tick)
+            .map(|item| item.entity())
+            .collect();
+        assert_eq!(entities, [e1, e2]);
+        assert_eq!(
+            changed
+                .iter_manual(&world, change_tick, change_tick)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn dynamic_query_access() {
+        let mut world = World::new();
+        world.spawn((A(1), B(1)));
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        let query = QueryBuilder::new(&mut world)
+            .write(a)
+            .read(b)
+            .without(c)
+            .build();
+        let access = query.component_access();
+        assert!(access.access().has_write(a));
+        assert!(access.access().has_read(b));
+        assert!(!access.access().has_write(b));
+
+        let write_b = QueryState::<&mut B>::new(&mut world);
+        assert!(!access.is_compatible(&write_b.component_access));
+        let read_b = QueryState::<&B>::new(&mut world);
+        assert!(access.is_compatible(&read_b.component_access));
+        let write_a_with_c = QueryState::<&mut A, With<C>>::new(&mut world);
+        assert!(access.is_compatible(&write_a_with_c.component_access));
+
+        let archetype = world
+            .archetypes()
+            .iter()
+            .find(|archetype| archetype.contains(a) && archetype.contains(b))
+            .unwrap();
+        let archetype_access = query.archetype_component_access();
+        assert!(archetype_access.has_write(archetype.get_archetype_component_id(a).unwrap()));
+        assert!(archetype_access.has_read(archetype.get_archetype_component_id(b).unwrap()));
+    }
+
+    #[test]
+    fn dynamic_query_par_for_each() {
+        ComputeTaskPool::init(TaskPool::default);
+        let mut world = World::new();
+        world.spawn_batch((0..100).map(A));
+        world.spawn_batch((0..50).map(|i| (A(i), B(i))));
+        let a = world.init_component::<A>();
+
+        let count = AtomicUsize::new(0);
+        let mut query = QueryBuilder::new(&mut world).read(a).build();
+        query.par_for_each(&world, 16, |_| {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(count.load(Ordering::Relaxed), 150);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dynamic_query_conflicting_writes() {
+        let mut world = World::new();
+        let a = world.init_component::<A>();
+        QueryBuilder::new(&mut world).write(a).read(a).build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn dynamic_query_iter_requires_read_only() {
+        let mut world = World::new();
+        let a = world.init_component::<A>();
+        let mut query = QueryBuilder::new(&mut world).write(a).build();
+        query.iter(&world);
+    }
+}
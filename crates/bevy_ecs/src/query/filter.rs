@@ -1,7 +1,7 @@
 use crate::{
     archetype::{Archetype, ArchetypeComponentId},
     component::{Component, ComponentId, ComponentStorage, ComponentTicks, StorageType},
-    entity::Entity,
+    entity::{Disabled, Entity},
     query::{Access, DebugCheckedUnwrap, FilteredAccess, WorldQuery},
     storage::{ComponentSparseSet, Table},
     world::World,
@@ -219,6 +219,104 @@ unsafe impl<T: Component> WorldQuery for Without<T> {
 // SAFETY: no component access or archetype component access
 unsafe impl<T: Component> ReadOnlyWorldQuery for Without<T> {}
 
+/// Filter that includes [`Disabled`] entities, which queries skip otherwise.
+///
+/// The query matches the same entities whether they are disabled or not. It is registered as
+/// reading [`Disabled`], so it conflicts with queries writing that component.
+///
+/// # Examples
+///
+/// ```
+/// # use bevy_ecs::component::Component;
+/// # use bevy_ecs::query::IncludeDisabled;
+/// # use bevy_ecs::system::IntoSystem;
+/// # use bevy_ecs::system::Query;
+/// #
+/// # #[derive(Component)]
+/// # struct Pooled;
+/// #
+/// fn count_pool_system(query: Query<&Pooled, IncludeDisabled>) {
+///     println!("{} pooled entities, active or not", query.iter().count());
+/// }
+/// # bevy_ecs::system::assert_is_system(count_pool_system);
+/// ```
+pub struct IncludeDisabled;
+
+// SAFETY: `Self::ReadOnly` is the same as `Self`
+unsafe impl WorldQuery for IncludeDisabled {
+    type Fetch<'w> = ();
+    type Item<'w> = ();
+    type ReadOnly = Self;
+    type State = ComponentId;
+
+    fn shrink<'wlong: 'wshort, 'wshort>(_: Self::Item<'wlong>) -> Self::Item<'wshort> {}
+
+    unsafe fn init_fetch(
+        _world: &World,
+        _state: &ComponentId,
+        _last_change_tick: u32,
+        _change_tick: u32,
+    ) {
+    }
+
+    unsafe fn clone_fetch<'w>(_fetch: &Self::Fetch<'w>) -> Self::Fetch<'w> {}
+
+    const IS_DENSE: bool = true;
+
+    const IS_ARCHETYPAL: bool = true;
+
+    #[inline]
+    unsafe fn set_table(_fetch: &mut (), _state: &ComponentId, _table: &Table) {}
+
+    #[inline]
+    unsafe fn set_archetype(
+        _fetch: &mut (),
+        _state: &ComponentId,
+        _archetype: &Archetype,
+        _table: &Table,
+    ) {
+    }
+
+    #[inline(always)]
+    unsafe fn fetch<'w>(
+        _fetch: &mut Self::Fetch<'w>,
+        _entity: Entity,
+        _table_row: usize,
+    ) -> Self::Item<'w> {
+    }
+
+    #[inline]
+    fn update_component_access(&id: &ComponentId, access: &mut FilteredAccess<ComponentId>) {
+        // Unlike `FilteredAccess::add_read`, this doesn't require the component.
+        access.access_mut().add_read(id);
+    }
+
+    #[inline]
+    fn update_archetype_component_access(
+        &id: &ComponentId,
+        archetype: &Archetype,
+        access: &mut Access<ArchetypeComponentId>,
+    ) {
+        if let Some(archetype_component_id) = archetype.get_archetype_component_id(id) {
+            access.add_read(archetype_component_id);
+        }
+    }
+
+    fn init_state(world: &mut World) -> ComponentId {
+        world.init_component::<Disabled>()
+    }
+
+    fn matches_component_set(
+        _state: &ComponentId,
+        _set_contains_id: &impl Fn(ComponentId) -> bool,
+    ) -> bool {
+        true
+    }
+}
+
+// SAFETY: only reads `Disabled`, which is never fetched
+unsafe impl ReadOnlyWorldQuery for IncludeDisabled {}
+
 /// A filter that tests if any of the given filters apply.
 ///
 /// This is useful for example if a system with multiple components in a query only wants to run
@@ -5,7 +5,9 @@ use crate::{
     query::{ArchetypeFilter, DebugCheckedUnwrap, QueryState, WorldQuery},
     storage::{TableId, Tables},
 };
-use std::{borrow::Borrow, iter::FusedIterator, marker::PhantomData, mem::MaybeUninit};
+use std::{
+    borrow::Borrow, cmp::Ordering, iter::FusedIterator, marker::PhantomData, mem::MaybeUninit,
+};
 
 use super::ReadOnlyWorldQuery;
 
@@ -14,6 +16,7 @@ use super::ReadOnlyWorldQuery;
 /// This struct is created by the [`Query::iter`](crate::system::Query::iter) and
 /// [`Query::iter_mut`](crate::system::Query::iter_mut) methods.
 pub struct QueryIter<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> {
+    entities: &'w Entities,
     tables: &'w Tables,
     archetypes: &'w Archetypes,
     query_state: &'s QueryState<Q, F>,
@@ -34,11 +37,87 @@ impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> QueryIter<'w, 's, Q, F> {
     ) -> Self {
         QueryIter {
             query_state,
+            entities: &world.entities,
             tables: &world.storages().tables,
             archetypes: &world.archetypes,
             cursor: QueryIterationCursor::init(world, query_state, last_change_tick, change_tick),
         }
     }
+
+    /// Sorts the remaining items of the query, and returns an iterator over them in that order.
+    ///
+    /// The items are only kept while sorting: the returned [`QuerySortedIter`] fetches each of
+    /// them again when it returns it, so this also works for queries with mutable access.
+    ///
+    /// The sort is stable, see [`slice::sort`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component, PartialEq, Eq, PartialOrd, Ord)]
+    /// struct Depth(u32);
+    ///
+    /// fn draw(query: Query<(Entity, &Depth)>) {
+    ///     for (entity, depth) in query.iter().sort_by_key(|(_, depth)| depth.0) {
+    ///         // Draw from back to front
+    ///     }
+    /// }
+    /// # bevy_ecs::system::assert_is_system(draw);
+    /// ```
+    pub fn sort(self) -> QuerySortedIter<'w, 's, Q, F>
+    where
+        Q::Item<'w>: Ord,
+    {
+        self.sort_by(Ord::cmp)
+    }
+
+    /// Sorts the remaining items of the query with a comparator function, see
+    /// [`QueryIter::sort`].
+    pub fn sort_by(
+        self,
+        mut compare: impl FnMut(&Q::Item<'w>, &Q::Item<'w>) -> Ordering,
+    ) -> QuerySortedIter<'w, 's, Q, F> {
+        self.sort_entities(|items| items.sort_by(|(_, a), (_, b)| compare(a, b)))
+    }
+
+    /// Sorts the remaining items of the query with a key extraction function, see
+    /// [`QueryIter::sort`].
+    ///
+    /// The key of each item is only computed once.
+    pub fn sort_by_key<K: Ord>(
+        self,
+        mut f: impl FnMut(&Q::Item<'w>) -> K,
+    ) -> QuerySortedIter<'w, 's, Q, F> {
+        self.sort_entities(|items| items.sort_by_cached_key(|(_, item)| f(item)))
+    }
+
+    fn sort_entities(
+        mut self,
+        sort: impl FnOnce(&mut Vec<(Entity, Q::Item<'w>)>),
+    ) -> QuerySortedIter<'w, 's, Q, F> {
+        let mut items = Vec::new();
+        // SAFETY:
+        // `tables` and `archetypes` belong to the same world that the cursor was initialized for.
+        // `query_state` is the state that was passed to `QueryIterationCursor::init`.
+        while let Some(item) = unsafe {
+            self.cursor
+                .next(self.tables, self.archetypes, self.query_state)
+        } {
+            items.push((self.cursor.last_entity().unwrap(), item));
+        }
+        sort(&mut items);
+        // The items are dropped before the returned iterator fetches them again.
+        let entities: Vec<Entity> = items.into_iter().map(|(entity, _)| entity).collect();
+        QuerySortedIter {
+            entity_iter: entities.into_iter(),
+            entities: self.entities,
+            tables: self.tables,
+            archetypes: self.archetypes,
+            fetch: self.cursor.fetch,
+            query_state: self.query_state,
+        }
+    }
 }
 
 impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> Iterator for QueryIter<'w, 's, Q, F> {
@@ -72,6 +151,88 @@ impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> Iterator for QueryIter<'w, 's
 // This is correct as [`QueryIter`] always returns `None` once exhausted.
 impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> FusedIterator for QueryIter<'w, 's, Q, F> {}
 
+/// An [`Iterator`] over query results of a [`Query`](crate::system::Query), in the order they
+/// were sorted in.
+///
+/// This struct is created by the [`QueryIter::sort`], [`QueryIter::sort_by`] and
+/// [`QueryIter::sort_by_key`] methods.
+pub struct QuerySortedIter<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> {
+    /// The sorted entities that matched the query, each appearing once.
+    entity_iter: std::vec::IntoIter<Entity>,
+    entities: &'w Entities,
+    tables: &'w Tables,
+    archetypes: &'w Archetypes,
+    fetch: Q::Fetch<'w>,
+    query_state: &'s QueryState<Q, F>,
+}
+
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> QuerySortedIter<'w, 's, Q, F> {
+    /// # Safety
+    /// `entity` must have matched the query while it was sorted, and no other item must be
+    /// alive for it.
+    #[inline(always)]
+    unsafe fn fetch(&mut self, entity: Entity) -> Q::Item<'w> {
+        // The entities can't have moved since they were sorted, as the world is borrowed.
+        let location = self.entities.get(entity).debug_checked_unwrap();
+        let archetype = self
+            .archetypes
+            .get(location.archetype_id)
+            .debug_checked_unwrap();
+        let table = self.tables.get(archetype.table_id()).debug_checked_unwrap();
+
+        // SAFETY: `archetype` is from the world that `fetch` was created for,
+        // `fetch_state` is the state that `fetch` was initialized with
+        Q::set_archetype(
+            &mut self.fetch,
+            &self.query_state.fetch_state,
+            archetype,
+            table,
+        );
+        // SAFETY: set_archetype was called prior, `location.index` is an archetype index in range of the current archetype
+        Q::fetch(
+            &mut self.fetch,
+            entity,
+            archetype.entity_table_row(location.index),
+        )
+    }
+}
+
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> Iterator for QuerySortedIter<'w, 's, Q, F> {
+    type Item = Q::Item<'w>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entity = self.entity_iter.next()?;
+        // SAFETY: The entities were sorted from a single iteration of the query, so each of them
+        // matched it and is returned only once.
+        unsafe { Some(self.fetch(entity)) }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entity_iter.size_hint()
+    }
+}
+
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> DoubleEndedIterator
+    for QuerySortedIter<'w, 's, Q, F>
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let entity = self.entity_iter.next_back()?;
+        // SAFETY: The entities were sorted from a single iteration of the query, so each of them
+        // matched it and is returned only once.
+        unsafe { Some(self.fetch(entity)) }
+    }
+}
+
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> ExactSizeIterator
+    for QuerySortedIter<'w, 's, Q, F>
+{
+}
+
+// This is correct as [`QuerySortedIter`] always returns `None` once exhausted.
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> FusedIterator for QuerySortedIter<'w, 's, Q, F> {}
+
 /// An [`Iterator`] over the query items generated from an iterator of [`Entity`]s.
 ///
 /// Items are returned in the order of the provided iterator.
@@ -550,6 +711,19 @@ impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> QueryIterationCursor<'w, 's,
         }
     }
 
+    /// Returns the entity of the item returned from the most recent `next` call.
+    #[inline]
+    fn last_entity(&self) -> Option<Entity> {
+        let index = self.current_index.checked_sub(1)?;
+        if Self::IS_DENSE {
+            self.table_entities.get(index).copied()
+        } else {
+            self.archetype_entities
+                .get(index)
+                .map(|archetype_entity| archetype_entity.entity)
+        }
+    }
+
     /// retrieve item returned from most recent `next` call again.
     #[inline]
     unsafe fn peek_last(&mut self) -> Option<Q::Item<'w>> {
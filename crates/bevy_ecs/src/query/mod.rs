@@ -1,10 +1,12 @@
 mod access;
+mod dynamic;
 mod fetch;
 mod filter;
 mod iter;
 mod state;
 
 pub use access::*;
+pub use dynamic::*;
 pub use fetch::*;
 pub use filter::*;
 pub use iter::*;
@@ -59,8 +61,9 @@ impl<T> DebugCheckedUnwrap for Option<T> {
 #[cfg(test)]
 mod tests {
     use super::{ReadOnlyWorldQuery, WorldQuery};
+    use crate::entity::Disabled;
     use crate::prelude::{AnyOf, Entity, Or, QueryState, With, Without};
-    use crate::query::{ArchetypeFilter, QueryCombinationIter};
+    use crate::query::{ArchetypeFilter, IncludeDisabled, QueryCombinationIter};
     use crate::system::{IntoSystem, Query, System, SystemState};
     use crate::{self as bevy_ecs, component::Component, world::World};
     use std::any::type_name;
@@ -506,6 +509,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn query_iter_sorted() {
+        let mut world = World::new();
+        world.spawn((A(3), B(0)));
+        world.spawn((A(1), Sparse(0)));
+        world.spawn((A(2), B(0)));
+        world.spawn(A(0));
+
+        let mut query = world.query::<&A>();
+        let values: Vec<_> = query
+            .iter(&world)
+            .sort_by_key(|a| a.0)
+            .map(|a| a.0)
+            .collect();
+        assert_eq!(values, [0, 1, 2, 3]);
+        let values: Vec<_> = query
+            .iter(&world)
+            .sort_by(|a, b| b.0.cmp(&a.0))
+            .map(|a| a.0)
+            .collect();
+        assert_eq!(values, [3, 2, 1, 0]);
+        let mut sorted = query.iter(&world).sort_by_key(|a| a.0);
+        assert_eq!(sorted.len(), 4);
+        assert_eq!(sorted.next_back(), Some(&A(3)));
+
+        let mut query = world.query::<(&A, &mut Sparse)>();
+        for (a, mut sparse) in query.iter_mut(&mut world).sort_by_key(|(a, _)| a.0) {
+            sparse.0 = a.0 * 10;
+        }
+        let mut query = world.query::<(&mut A, Option<&B>)>();
+        let mut previous = None;
+        for (mut a, _) in query.iter_mut(&mut world).sort_by_key(|(a, _)| a.0) {
+            if let Some(previous) = previous {
+                assert!(previous < a.0);
+            }
+            previous = Some(a.0);
+            a.0 += 10;
+        }
+        let mut query = world.query::<&Sparse>();
+        assert_eq!(query.single(&world), &Sparse(10));
+        let mut query = world.query::<&A>();
+        let values: Vec<_> = query
+            .iter(&world)
+            .sort_by_key(|a| a.0)
+            .map(|a| a.0)
+            .collect();
+        assert_eq!(values, [10, 11, 12, 13]);
+    }
+
     #[test]
     fn multi_storage_query() {
         let mut world = World::new();
@@ -820,4 +872,54 @@ mod tests {
         let _: [&Foo; 1] = q.many([e]);
         let _: &Foo = q.single();
     }
+
+    #[test]
+    fn query_skips_disabled_entities() {
+        let mut world = World::new();
+        let enabled = world.spawn(A(1)).id();
+        let disabled = world.spawn((A(2), Disabled)).id();
+
+        let mut query = world.query::<Entity>();
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), [enabled]);
+        assert!(query.get(&world, disabled).is_err());
+
+        let mut query = world.query_filtered::<Entity, With<Disabled>>();
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), [disabled]);
+
+        let mut query = world.query::<(&A, Option<&Disabled>)>();
+        assert_eq!(query.iter(&world).count(), 2);
+
+        let mut query = world.query_filtered::<&A, IncludeDisabled>();
+        assert_eq!(query.iter(&world).count(), 2);
+
+        world.entity_mut(disabled).remove::<Disabled>();
+        let mut query = world.query::<&A>();
+        assert_eq!(query.iter(&world).count(), 2);
+    }
+
+    #[test]
+    fn query_with_disabled_in_or_includes_disabled_entities() {
+        let mut world = World::new();
+        let enabled = world.spawn((A(1), B(1))).id();
+        let disabled = world.spawn((A(2), Disabled)).id();
+        world.spawn(A(3));
+
+        let mut query = world.query_filtered::<Entity, Or<(With<B>, With<Disabled>)>>();
+        let mut entities: Vec<_> = query.iter(&world).collect();
+        entities.sort();
+        assert_eq!(entities, [enabled, disabled]);
+
+        let mut query = world.query_filtered::<&A, Or<(Without<Disabled>, Without<B>)>>();
+        assert_eq!(query.iter(&world).count(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn include_disabled_does_not_require_disabled() {
+        // `IncludeDisabled` must not be treated as `With<Disabled>`, or these queries would be
+        // considered disjoint.
+        fn system(_: Query<&mut A, IncludeDisabled>, _: Query<&mut A, Without<Disabled>>) {}
+        let mut world = World::new();
+        IntoSystem::into_system(system).initialize(&mut world);
+    }
 }
@@ -1,64 +1,56 @@
 use bevy_utils::tracing::info;
 use fixedbitset::FixedBitSet;
+use std::{borrow::Cow, fmt, path::Path};
 
 use crate::component::ComponentId;
-use crate::schedule::{AmbiguityDetection, GraphNode, SystemContainer, SystemStage};
+use crate::schedule::{
+    AmbiguityDetection, GraphNode, Schedule, StageLabel, SystemContainer, SystemLabel,
+    SystemSegment, SystemStage,
+};
 use crate::world::World;
 
 use super::SystemLabelId;
 
+/// An execution order ambiguity between two systems, found by
+/// [`Schedule::ambiguity_report`].
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct SystemOrderAmbiguity {
-    segment: SystemStageSegment,
+pub struct SystemOrderAmbiguity {
+    /// The label of the stage the systems are in.
+    ///
+    /// The stages of nested schedules are prefixed by the label of the stage containing them,
+    /// separated by a `/`.
+    pub stage: String,
+    /// The part of the stage the systems are in.
+    pub segment: SystemSegment,
     // Note: In order for comparisons to work correctly,
     // `system_names` and `conflicts` must be sorted at all times.
-    system_names: [String; 2],
-    conflicts: Vec<String>,
-}
-
-/// Which part of a [`SystemStage`] was a [`SystemOrderAmbiguity`] detected in?
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
-enum SystemStageSegment {
-    Parallel,
-    ExclusiveAtStart,
-    ExclusiveBeforeCommands,
-    ExclusiveAtEnd,
+    /// The names of the systems, sorted.
+    pub system_names: [String; 2],
+    /// The sorted names of the components and resources the systems conflict on.
+    ///
+    /// Empty if one of the systems is exclusive.
+    pub conflicts: Vec<String>,
 }
 
-impl SystemStageSegment {
-    pub fn desc(&self) -> &'static str {
-        match self {
-            SystemStageSegment::Parallel => "Parallel systems",
-            SystemStageSegment::ExclusiveAtStart => "Exclusive systems at start of stage",
-            SystemStageSegment::ExclusiveBeforeCommands => {
-                "Exclusive systems before commands of stage"
-            }
-            SystemStageSegment::ExclusiveAtEnd => "Exclusive systems at end of stage",
-        }
+fn segment_desc(segment: SystemSegment) -> &'static str {
+    match segment {
+        SystemSegment::Parallel => "Parallel systems",
+        SystemSegment::ExclusiveAtStart => "Exclusive systems at start of stage",
+        SystemSegment::ExclusiveBeforeCommands => "Exclusive systems before commands of stage",
+        SystemSegment::ExclusiveAtEnd => "Exclusive systems at end of stage",
     }
 }
 
 impl SystemOrderAmbiguity {
     fn from_raw(
-        system_a_index: usize,
-        system_b_index: usize,
+        system_a: &SystemContainer,
+        system_b: &SystemContainer,
         component_ids: Vec<ComponentId>,
-        segment: SystemStageSegment,
-        stage: &SystemStage,
+        segment: SystemSegment,
+        stage: &str,
         world: &World,
     ) -> Self {
-        use SystemStageSegment::*;
-
-        let systems = match segment {
-            Parallel => stage.parallel_systems(),
-            ExclusiveAtStart => stage.exclusive_at_start_systems(),
-            ExclusiveBeforeCommands => stage.exclusive_before_commands_systems(),
-            ExclusiveAtEnd => stage.exclusive_at_end_systems(),
-        };
-        let mut system_names = [
-            systems[system_a_index].name().to_string(),
-            systems[system_b_index].name().to_string(),
-        ];
+        let mut system_names = [system_a.name().to_string(), system_b.name().to_string()];
         system_names.sort();
 
         let mut conflicts: Vec<_> = component_ids
@@ -68,11 +60,275 @@ impl SystemOrderAmbiguity {
         conflicts.sort();
 
         Self {
+            stage: stage.to_owned(),
             system_names,
             conflicts,
             segment,
         }
     }
+
+    /// Returns `true` if `other` is the same ambiguity, and conflicts on the same or on more
+    /// components and resources.
+    fn is_covered_by(&self, other: &SystemOrderAmbiguity) -> bool {
+        self.stage == other.stage
+            && self.segment == other.segment
+            && self.system_names == other.system_names
+            && self
+                .conflicts
+                .iter()
+                .all(|conflict| other.conflicts.contains(conflict))
+    }
+}
+
+/// Ambiguities to leave out of an [`AmbiguityReport`].
+///
+/// Unlike [`ambiguous_with`](crate::schedule::ParallelSystemDescriptorCoercion::ambiguous_with),
+/// this doesn't change how systems are declared, which makes it suitable for systems of other
+/// crates.
+#[derive(Debug, Clone, Default)]
+pub struct AmbiguityAllowList {
+    labels: Vec<SystemLabelId>,
+    crates: Vec<Cow<'static, str>>,
+}
+
+impl AmbiguityAllowList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows any ambiguity involving a system with the label.
+    #[must_use]
+    pub fn allow_label(mut self, label: impl SystemLabel) -> Self {
+        self.labels.push(label.as_label());
+        self
+    }
+
+    /// Allows ambiguities between two systems of the given crates, for example `"bevy_render"`.
+    ///
+    /// Ambiguities between a system of an allowed crate and a system of another crate are still
+    /// reported.
+    #[must_use]
+    pub fn allow_crate(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.crates.push(name.into());
+        self
+    }
+
+    fn allows(&self, system_a: &SystemContainer, system_b: &SystemContainer) -> bool {
+        let has_label = |system: &SystemContainer| {
+            system
+                .labels()
+                .iter()
+                .any(|label| self.labels.contains(label))
+        };
+        let in_crate = |system: &SystemContainer| {
+            let name = system.name();
+            self.crates.iter().any(|crate_name| {
+                matches!(name.strip_prefix(&**crate_name), Some(path) if path.starts_with("::"))
+            })
+        };
+        has_label(system_a) || has_label(system_b) || (in_crate(system_a) && in_crate(system_b))
+    }
+}
+
+/// The execution order ambiguities of a [`Schedule`], created with
+/// [`Schedule::ambiguity_report`].
+///
+/// To catch new ambiguities in a test, store a baseline with [`to_baseline`](Self::to_baseline)
+/// and compare against it with
+/// [`assert_no_new_ambiguities`](Self::assert_no_new_ambiguities).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmbiguityReport {
+    ambiguities: Vec<SystemOrderAmbiguity>,
+}
+
+/// An error returned when parsing an [`AmbiguityReport`] baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidAmbiguityBaseline {
+    /// The line that couldn't be parsed, starting at 1.
+    pub line: usize,
+}
+
+impl std::error::Error for InvalidAmbiguityBaseline {}
+
+impl fmt::Display for InvalidAmbiguityBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid ambiguity baseline on line {}", self.line)
+    }
+}
+
+impl AmbiguityReport {
+    /// Returns the ambiguities, sorted by stage, segment and system names.
+    pub fn ambiguities(&self) -> &[SystemOrderAmbiguity] {
+        &self.ambiguities
+    }
+
+    /// Returns `true` if no ambiguity was found.
+    pub fn is_empty(&self) -> bool {
+        self.ambiguities.is_empty()
+    }
+
+    /// Returns the ambiguities that aren't in `baseline`.
+    ///
+    /// An ambiguity of the baseline covers an ambiguity between the same systems of the same
+    /// stage if it conflicts on at least the same components and resources.
+    pub fn new_ambiguities(&self, baseline: &AmbiguityReport) -> Vec<&SystemOrderAmbiguity> {
+        self.ambiguities
+            .iter()
+            .filter(|ambiguity| {
+                !baseline
+                    .ambiguities
+                    .iter()
+                    .any(|known| ambiguity.is_covered_by(known))
+            })
+            .collect()
+    }
+
+    /// Writes the report in the baseline format read by [`from_baseline`](Self::from_baseline).
+    ///
+    /// Each ambiguity is a line of tab-separated fields: the stage, the segment, the two system
+    /// names and the conflicts.
+    pub fn to_baseline(&self) -> String {
+        let mut baseline = String::new();
+        for ambiguity in &self.ambiguities {
+            write_baseline_line(&mut baseline, ambiguity);
+        }
+        baseline
+    }
+
+    /// Reads a report written by [`to_baseline`](Self::to_baseline).
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_baseline(baseline: &str) -> Result<Self, InvalidAmbiguityBaseline> {
+        let mut ambiguities = Vec::new();
+        for (index, line) in baseline.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = InvalidAmbiguityBaseline { line: index + 1 };
+            let mut fields = line.split('\t');
+            let mut next = || fields.next().map(str::to_owned).ok_or(error);
+            let stage = next()?;
+            let segment = SystemSegment::from_name(&next()?).ok_or(error)?;
+            let mut system_names = [next()?, next()?];
+            system_names.sort();
+            let mut conflicts: Vec<_> = fields.map(str::to_owned).collect();
+            conflicts.sort();
+            ambiguities.push(SystemOrderAmbiguity {
+                stage,
+                segment,
+                system_names,
+                conflicts,
+            });
+        }
+        ambiguities.sort();
+        Ok(Self { ambiguities })
+    }
+
+    /// Panics if the report has ambiguities that aren't in the baseline file at `path`.
+    ///
+    /// The panic message lists the new ambiguities in the baseline format, so that they can be
+    /// added to the file once reviewed. A missing file is an empty baseline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are new ambiguities, or if the file can't be read or parsed.
+    pub fn assert_no_new_ambiguities(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let baseline = match std::fs::read_to_string(path) {
+            Ok(baseline) => AmbiguityReport::from_baseline(&baseline)
+                .unwrap_or_else(|error| panic!("{}: {error}", path.display())),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(error) => panic!("failed to read {}: {error}", path.display()),
+        };
+        let new_ambiguities = self.new_ambiguities(&baseline);
+        if !new_ambiguities.is_empty() {
+            let mut lines = String::new();
+            for ambiguity in new_ambiguities {
+                write_baseline_line(&mut lines, ambiguity);
+            }
+            panic!(
+                "new execution order ambiguities, not in {}:\n{lines}",
+                path.display()
+            );
+        }
+    }
+}
+
+fn write_baseline_line(baseline: &mut String, ambiguity: &SystemOrderAmbiguity) {
+    let SystemOrderAmbiguity {
+        stage,
+        segment,
+        system_names: [system_a, system_b],
+        conflicts,
+    } = ambiguity;
+    baseline.push_str(&[stage, segment.name(), system_a, system_b].join("\t"));
+    for conflict in conflicts {
+        baseline.push('\t');
+        baseline.push_str(conflict);
+    }
+    baseline.push('\n');
+}
+
+impl Schedule {
+    /// Returns the execution order ambiguities between systems of this schedule, including
+    /// nested schedules, except the ones allowed by `allow_list`.
+    ///
+    /// The report may be incorrect if this schedule has not been run on `world`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::{prelude::*, schedule::AmbiguityAllowList};
+    /// #
+    /// # #[derive(Resource)]
+    /// # struct Score(u32);
+    /// # #[derive(StageLabel)]
+    /// # struct MyStage;
+    /// fn bonus(mut score: ResMut<Score>) { score.0 += 10; }
+    /// fn penalty(mut score: ResMut<Score>) { score.0 -= 1; }
+    ///
+    /// let mut world = World::new();
+    /// world.insert_resource(Score(1));
+    /// let mut schedule = Schedule::default().with_stage(
+    ///     MyStage,
+    ///     SystemStage::parallel().with_system(bonus).with_system(penalty),
+    /// );
+    /// # bevy_tasks::ComputeTaskPool::init(bevy_tasks::TaskPool::default);
+    /// schedule.run(&mut world);
+    ///
+    /// let report = schedule.ambiguity_report(&world, &AmbiguityAllowList::new());
+    /// assert_eq!(report.ambiguities().len(), 1);
+    /// assert_eq!(report.ambiguities()[0].stage, "MyStage");
+    /// // In a test, with a reviewed baseline file:
+    /// // report.assert_no_new_ambiguities("tests/ambiguities.txt");
+    /// ```
+    pub fn ambiguity_report(
+        &self,
+        world: &World,
+        allow_list: &AmbiguityAllowList,
+    ) -> AmbiguityReport {
+        let mut ambiguities = Vec::new();
+        self.collect_ambiguities(world, allow_list, "", &mut ambiguities);
+        ambiguities.sort();
+        AmbiguityReport { ambiguities }
+    }
+
+    fn collect_ambiguities(
+        &self,
+        world: &World,
+        allow_list: &AmbiguityAllowList,
+        prefix: &str,
+        ambiguities: &mut Vec<SystemOrderAmbiguity>,
+    ) {
+        for (label, stage) in self.iter_stages() {
+            let label = format!("{prefix}{}", label.as_str());
+            if let Some(stage) = stage.downcast_ref::<SystemStage>() {
+                ambiguities.extend(stage.ambiguities(world, &label, allow_list));
+            } else if let Some(schedule) = stage.downcast_ref::<Schedule>() {
+                schedule.collect_ambiguities(world, allow_list, &format!("{label}/"), ambiguities);
+            }
+        }
+    }
 }
 
 impl SystemStage {
@@ -82,7 +338,7 @@ impl SystemStage {
     pub fn report_ambiguities(&self, world: &World) {
         debug_assert!(!self.systems_modified);
         use std::fmt::Write;
-        let ambiguities = self.ambiguities(world);
+        let ambiguities = self.ambiguities(world, "", &AmbiguityAllowList::default());
         if !ambiguities.is_empty() {
             let mut string = "Execution order ambiguities detected, you might want to \
 						add an explicit dependency relation between some of these systems:\n"
@@ -93,11 +349,12 @@ impl SystemStage {
                 system_names: [system_a, system_b],
                 conflicts,
                 segment,
+                ..
             } in &ambiguities
             {
                 // If the ambiguity occurred in a different segment than the previous one, write a header for the segment.
                 if last_segment_kind != Some(segment) {
-                    writeln!(string, " * {}:", segment.desc()).unwrap();
+                    writeln!(string, " * {}:", segment_desc(*segment)).unwrap();
                     last_segment_kind = Some(segment);
                 }
 
@@ -112,73 +369,32 @@ impl SystemStage {
         }
     }
 
-    /// Returns all execution order ambiguities between systems.
-    ///
-    /// Returns 4 vectors of ambiguities for each stage, in the following order:
-    /// - parallel
-    /// - exclusive at start,
-    /// - exclusive before commands
-    /// - exclusive at end
+    /// Returns all execution order ambiguities between systems, except the ones allowed by
+    /// `allow_list`, sorted by segment.
     ///
     /// The result may be incorrect if this stage has not been initialized with `world`.
-    fn ambiguities(&self, world: &World) -> Vec<SystemOrderAmbiguity> {
-        let parallel = find_ambiguities(&self.parallel).into_iter().map(
-            |(system_a_index, system_b_index, component_ids)| {
-                SystemOrderAmbiguity::from_raw(
-                    system_a_index,
-                    system_b_index,
-                    component_ids.to_vec(),
-                    SystemStageSegment::Parallel,
-                    self,
-                    world,
-                )
-            },
-        );
-
-        let at_start = find_ambiguities(&self.exclusive_at_start).into_iter().map(
-            |(system_a_index, system_b_index, component_ids)| {
-                SystemOrderAmbiguity::from_raw(
-                    system_a_index,
-                    system_b_index,
-                    component_ids,
-                    SystemStageSegment::ExclusiveAtStart,
-                    self,
-                    world,
-                )
-            },
-        );
-
-        let before_commands = find_ambiguities(&self.exclusive_before_commands)
-            .into_iter()
-            .map(|(system_a_index, system_b_index, component_ids)| {
-                SystemOrderAmbiguity::from_raw(
-                    system_a_index,
-                    system_b_index,
-                    component_ids,
-                    SystemStageSegment::ExclusiveBeforeCommands,
-                    self,
-                    world,
-                )
-            });
-
-        let at_end = find_ambiguities(&self.exclusive_at_end).into_iter().map(
-            |(system_a_index, system_b_index, component_ids)| {
-                SystemOrderAmbiguity::from_raw(
-                    system_a_index,
-                    system_b_index,
-                    component_ids,
-                    SystemStageSegment::ExclusiveAtEnd,
-                    self,
-                    world,
-                )
-            },
-        );
-
-        let mut ambiguities: Vec<_> = at_start
-            .chain(parallel)
-            .chain(before_commands)
-            .chain(at_end)
-            .collect();
+    fn ambiguities(
+        &self,
+        world: &World,
+        stage: &str,
+        allow_list: &AmbiguityAllowList,
+    ) -> Vec<SystemOrderAmbiguity> {
+        let mut ambiguities = Vec::new();
+        for (segment, systems) in self.segments() {
+            for (system_a, system_b, component_ids) in find_ambiguities(systems) {
+                let (system_a, system_b) = (&systems[system_a], &systems[system_b]);
+                if !allow_list.allows(system_a, system_b) {
+                    ambiguities.push(SystemOrderAmbiguity::from_raw(
+                        system_a,
+                        system_b,
+                        component_ids,
+                        segment,
+                        stage,
+                        world,
+                    ));
+                }
+            }
+        }
         ambiguities.sort();
         ambiguities
     }
@@ -188,14 +404,17 @@ impl SystemStage {
     /// The result may be incorrect if this stage has not been initialized with `world`.
     #[cfg(test)]
     fn ambiguity_count(&self, world: &World) -> usize {
-        self.ambiguities(world).len()
+        self.ambiguities(world, "", &AmbiguityAllowList::default())
+            .len()
     }
 }
 
 /// Returns vector containing all pairs of indices of systems with ambiguous execution order,
 /// along with specific components that have triggered the warning.
 /// Systems must be topologically sorted beforehand.
-fn find_ambiguities(systems: &[SystemContainer]) -> Vec<(usize, usize, Vec<ComponentId>)> {
+pub(super) fn find_ambiguities(
+    systems: &[SystemContainer],
+) -> Vec<(usize, usize, Vec<ComponentId>)> {
     // Check if we should ignore ambiguities between `system_a` and `system_b`.
     fn should_ignore(system_a: &SystemContainer, system_b: &SystemContainer) -> bool {
         fn should_ignore_inner(
@@ -566,43 +785,150 @@ mod tests {
 
         test_stage.run(&mut world);
 
-        let ambiguities = test_stage.ambiguities(&world);
+        let ambiguities = test_stage.ambiguities(&world, "", &AmbiguityAllowList::default());
         assert_eq!(
             ambiguities,
             vec![
                 SystemOrderAmbiguity {
+                    stage: String::new(),
                     system_names: [
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_a".to_string(),
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_b".to_string()
                     ],
                     conflicts: vec!["bevy_ecs::schedule::ambiguity_detection::tests::R".to_string()],
-                    segment: SystemStageSegment::Parallel,
+                    segment: SystemSegment::Parallel,
                 },
                 SystemOrderAmbiguity {
+                    stage: String::new(),
                     system_names: [
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_a".to_string(),
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_d".to_string()
                     ],
                     conflicts: vec!["bevy_ecs::schedule::ambiguity_detection::tests::R".to_string()],
-                    segment: SystemStageSegment::Parallel,
+                    segment: SystemSegment::Parallel,
                 },
                 SystemOrderAmbiguity {
+                    stage: String::new(),
                     system_names: [
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_b".to_string(),
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_e".to_string()
                     ],
                     conflicts: vec!["bevy_ecs::schedule::ambiguity_detection::tests::R".to_string()],
-                    segment: SystemStageSegment::Parallel,
+                    segment: SystemSegment::Parallel,
                 },
                 SystemOrderAmbiguity {
+                    stage: String::new(),
                     system_names: [
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_d".to_string(),
                         "bevy_ecs::schedule::ambiguity_detection::tests::system_e".to_string()
                     ],
                     conflicts: vec!["bevy_ecs::schedule::ambiguity_detection::tests::R".to_string()],
-                    segment: SystemStageSegment::Parallel,
+                    segment: SystemSegment::Parallel,
                 },
             ]
         );
     }
+
+    #[derive(StageLabel)]
+    struct Outer;
+
+    #[derive(StageLabel)]
+    struct Inner;
+
+    #[derive(SystemLabel)]
+    struct Allowed;
+
+    fn report_schedule() -> (World, Schedule) {
+        let mut world = World::new();
+        world.insert_resource(R);
+        let mut schedule = Schedule::default()
+            .with_stage(
+                Outer,
+                SystemStage::single_threaded()
+                    .with_system(system_a)
+                    .with_system(system_b.label(Allowed)),
+            )
+            .with_stage(
+                Inner,
+                Schedule::default().with_stage(
+                    Inner,
+                    SystemStage::single_threaded()
+                        .with_system(system_c)
+                        .with_system(system_d),
+                ),
+            );
+        schedule.run(&mut world);
+        (world, schedule)
+    }
+
+    #[test]
+    fn ambiguity_report() {
+        use super::AmbiguityAllowList;
+
+        let (world, schedule) = report_schedule();
+
+        let report = schedule.ambiguity_report(&world, &AmbiguityAllowList::new());
+        let stages: Vec<_> = report
+            .ambiguities()
+            .iter()
+            .map(|ambiguity| ambiguity.stage.as_str())
+            .collect();
+        assert_eq!(stages, ["Inner/Inner", "Outer"]);
+
+        let allow_list = AmbiguityAllowList::new().allow_label(Allowed);
+        let report = schedule.ambiguity_report(&world, &allow_list);
+        assert_eq!(report.ambiguities().len(), 1);
+        assert_eq!(report.ambiguities()[0].stage, "Inner/Inner");
+
+        let allow_list = AmbiguityAllowList::new().allow_crate("bevy");
+        assert_eq!(
+            schedule
+                .ambiguity_report(&world, &allow_list)
+                .ambiguities()
+                .len(),
+            2
+        );
+        let allow_list = AmbiguityAllowList::new().allow_crate("bevy_ecs");
+        assert!(schedule.ambiguity_report(&world, &allow_list).is_empty());
+    }
+
+    #[test]
+    fn ambiguity_baseline() {
+        use super::{AmbiguityAllowList, AmbiguityReport};
+
+        let (world, schedule) = report_schedule();
+        let report = schedule.ambiguity_report(&world, &AmbiguityAllowList::new());
+
+        let baseline = report.to_baseline();
+        assert_eq!(AmbiguityReport::from_baseline(&baseline).unwrap(), report);
+        assert!(report
+            .new_ambiguities(&AmbiguityReport::from_baseline(&baseline).unwrap())
+            .is_empty());
+
+        // A baseline that only knows the outer ambiguity.
+        let outer = baseline
+            .lines()
+            .find(|line| line.starts_with("Outer\t"))
+            .unwrap();
+        let partial = AmbiguityReport::from_baseline(&format!("# reviewed\n{outer}\n")).unwrap();
+        let new = report.new_ambiguities(&partial);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].stage, "Inner/Inner");
+
+        assert_eq!(
+            AmbiguityReport::from_baseline("Outer\tnowhere\ta\tb").map_err(|error| error.line),
+            Err(1)
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "bevy_ecs_ambiguity_baseline_{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(&path, &baseline).unwrap();
+        report.assert_no_new_ambiguities(&path);
+        std::fs::write(&path, outer).unwrap();
+        let result = std::panic::catch_unwind(|| report.assert_no_new_ambiguities(&path));
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,182 @@
+//! Run criteria for common conditions.
+//!
+//! They can be combined with each other and with other run criteria through
+//! [`RunCriteriaDescriptorCoercion::and_then`](super::RunCriteriaDescriptorCoercion::and_then),
+//! [`or`](super::RunCriteriaDescriptorCoercion::or) and
+//! [`not`](super::RunCriteriaDescriptorCoercion::not).
+//!
+//! ```
+//! use bevy_ecs::{prelude::*, schedule::common_conditions::*};
+//!
+//! #[derive(Resource)]
+//! struct Score(u32);
+//!
+//! #[derive(Component)]
+//! struct Player;
+//!
+//! fn update_scoreboard(score: Res<Score>) {}
+//!
+//! let mut stage = SystemStage::parallel();
+//! stage.add_system(
+//!     update_scoreboard
+//!         .with_run_criteria(resource_changed::<Score>().and_then(any_with_component::<Player>())),
+//! );
+//! ```
+
+use crate::{
+    component::Component,
+    event::{Event, EventReader},
+    query::With,
+    schedule::{ShouldRun, State, StateData},
+    system::{Query, Res, Resource},
+};
+
+/// Runs while the resource `R` exists.
+pub fn resource_exists<R: Resource>() -> impl FnMut(Option<Res<R>>) -> ShouldRun {
+    |resource: Option<Res<R>>| resource.is_some().into()
+}
+
+/// Runs when the resource `R` was added or changed since the criteria last ran.
+///
+/// Does not run while the resource does not exist.
+pub fn resource_changed<R: Resource>() -> impl FnMut(Option<Res<R>>) -> ShouldRun {
+    |resource: Option<Res<R>>| {
+        resource
+            .map_or(false, |resource| resource.is_changed())
+            .into()
+    }
+}
+
+/// Runs when events of type `E` were sent since the criteria last ran.
+///
+/// The events are read by the criteria, they can still be read by the systems it controls.
+pub fn on_event<E: Event>() -> impl FnMut(EventReader<E>) -> ShouldRun {
+    |mut reader: EventReader<E>| (reader.iter().count() > 0).into()
+}
+
+/// Runs while the current value of [`State<S>`] is `state`.
+///
+/// Unlike [`State::on_update`], it also runs while a transition is queued or applied.
+pub fn state_equals<S: StateData>(state: S) -> impl FnMut(Option<Res<State<S>>>) -> ShouldRun {
+    move |current: Option<Res<State<S>>>| {
+        current
+            .map_or(false, |current| current.current() == &state)
+            .into()
+    }
+}
+
+/// Runs while there is any entity with the component `C`.
+pub fn any_with_component<C: Component>() -> impl FnMut(Query<(), With<C>>) -> ShouldRun {
+    |query: Query<(), With<C>>| (!query.is_empty()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as bevy_ecs,
+        event::Events,
+        schedule::{IntoSystemDescriptor, RunCriteriaDescriptorCoercion, Stage, SystemStage},
+        system::ResMut,
+        world::World,
+    };
+
+    #[derive(Resource, Default)]
+    struct Runs(usize);
+
+    #[derive(Resource)]
+    struct Flag;
+
+    #[derive(Component)]
+    struct Marker;
+
+    struct TestEvent;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum AppState {
+        Menu,
+        Game,
+    }
+
+    fn count(mut runs: ResMut<Runs>) {
+        runs.0 += 1;
+    }
+
+    #[test]
+    fn resource_conditions() {
+        let mut world = World::new();
+        world.init_resource::<Runs>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(count.with_run_criteria(resource_exists::<Flag>()));
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 0);
+        world.insert_resource(Flag);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 2);
+
+        world.resource_mut::<Runs>().0 = 0;
+        world.remove_resource::<Flag>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(count.with_run_criteria(resource_changed::<Flag>()));
+        stage.run(&mut world);
+        world.insert_resource(Flag);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 1);
+        world.insert_resource(Flag);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 2);
+    }
+
+    #[test]
+    fn event_condition() {
+        let mut world = World::new();
+        world.init_resource::<Runs>();
+        world.init_resource::<Events<TestEvent>>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(count.with_run_criteria(on_event::<TestEvent>()));
+        stage.run(&mut world);
+        world.send_event(TestEvent);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 1);
+    }
+
+    #[test]
+    fn state_and_component_conditions() {
+        let mut world = World::new();
+        world.init_resource::<Runs>();
+        let mut stage = SystemStage::single_threaded().with_system(count.with_run_criteria(
+            state_equals(AppState::Game).and_then(any_with_component::<Marker>()),
+        ));
+        world.insert_resource(State::new(AppState::Menu));
+        world.spawn(Marker);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 0);
+
+        world.insert_resource(State::new(AppState::Game));
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 1);
+
+        world.clear_entities();
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 1);
+
+        let mut stage = SystemStage::single_threaded().with_system(
+            count.with_run_criteria(
+                any_with_component::<Marker>()
+                    .not()
+                    .or(resource_exists::<Flag>()),
+            ),
+        );
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 2);
+        world.spawn(Marker);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 2);
+        world.insert_resource(Flag);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<Runs>().0, 3);
+    }
+}
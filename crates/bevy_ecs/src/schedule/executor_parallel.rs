@@ -1,13 +1,14 @@
 use crate::{
     archetype::ArchetypeComponentId,
     query::Access,
-    schedule::{ParallelSystemExecutor, SystemContainer},
+    schedule::{timing::add_run_time, ParallelSystemExecutor, SystemContainer, SystemTimings},
     world::World,
 };
 use async_channel::{Receiver, Sender};
 use bevy_tasks::{ComputeTaskPool, Scope, TaskPool};
 #[cfg(feature = "trace")]
 use bevy_utils::tracing::Instrument;
+use bevy_utils::Instant;
 use event_listener::Event;
 use fixedbitset::FixedBitSet;
 
@@ -176,6 +177,7 @@ impl ParallelExecutor {
         #[cfg(feature = "trace")]
         let _span = bevy_utils::tracing::info_span!("prepare_systems").entered();
         self.should_run.clear();
+        let timed = world.contains_resource::<SystemTimings>();
         for (index, (system_data, system)) in
             self.system_metadata.iter_mut().zip(systems).enumerate()
         {
@@ -204,7 +206,7 @@ impl ParallelExecutor {
             // Spawn the system task.
             self.should_run.insert(index);
             let finish_sender = self.finish_sender.clone();
-            let system = system.system_mut();
+            let (system, run_time) = system.system_and_run_time_mut();
             #[cfg(feature = "trace")] // NB: outside the task to get the TLS current span
             let system_span = bevy_utils::tracing::info_span!("system", name = &*system.name());
             #[cfg(feature = "trace")]
@@ -214,8 +216,10 @@ impl ParallelExecutor {
             let mut run = move || {
                 #[cfg(feature = "trace")]
                 let _system_guard = system_span.enter();
+                let start = timed.then(Instant::now);
                 // SAFETY: the executor prevents two systems with conflicting access from running simultaneously.
                 unsafe { system.run_unsafe((), world) };
+                add_run_time(run_time, start);
             };
 
             if can_start {
@@ -0,0 +1,615 @@
+use crate::{
+    schedule::{
+        ambiguity_detection::find_ambiguities, GraphNode, RunCriteriaInner, RunCriteriaLabel,
+        Schedule, Stage, StageLabel, SystemContainer, SystemLabel, SystemStage,
+    },
+    world::World,
+};
+use bevy_utils::get_short_name;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A description of the stages, systems and run criteria of a [`Schedule`], created with
+/// [`Schedule::export_graph`].
+///
+/// Render it with [`to_dot`](Self::to_dot) to get a picture of the frame from Graphviz. The
+/// graph also implements [`Serialize`], so it can be exported to JSON or any other `serde`
+/// format to feed other tools. The contents of a stage are tagged with a `kind` field set to
+/// `"systems"`, `"schedule"` or `"other"`.
+///
+/// Systems are only ordered, and their ambiguities only accurate, once their stage has been run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleGraph {
+    /// The name of the run criteria of the schedule, if any.
+    pub run_criteria: Option<String>,
+    /// The stages of the schedule, in execution order.
+    pub stages: Vec<StageGraph>,
+}
+
+/// A stage of a [`ScheduleGraph`].
+#[derive(Debug, Clone, Serialize)]
+pub struct StageGraph {
+    pub label: String,
+    pub contents: StageContents,
+}
+
+/// What a [`StageGraph`] is made of.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StageContents {
+    /// A [`SystemStage`].
+    Systems(SystemStageGraph),
+    /// A nested [`Schedule`].
+    Schedule(ScheduleGraph),
+    /// Any other [`Stage`], which can't be looked into.
+    Other,
+}
+
+/// A description of the systems of a [`SystemStage`], created with
+/// [`SystemStage::export_graph`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemStageGraph {
+    /// The name of the run criteria of the stage, if any.
+    pub run_criteria: Option<String>,
+    /// The run criteria of the systems, in evaluation order.
+    pub system_run_criteria: Vec<RunCriteriaNode>,
+    /// The systems, in execution order within each [`SystemSegment`].
+    pub systems: Vec<SystemNode>,
+    /// The pairs of systems with an ambiguous execution order, as reported by
+    /// [`SystemStage::report_ambiguities`].
+    pub ambiguities: Vec<Ambiguity>,
+}
+
+/// A system run criteria of a [`SystemStageGraph`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RunCriteriaNode {
+    pub name: String,
+    pub label: Option<String>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    /// The index of the run criteria whose result is piped into this one.
+    pub piped_from: Option<usize>,
+    /// The type name of the [`State`](super::State) driven by this run criteria, if it is the
+    /// run criteria of a [`State::get_driver`](super::State::get_driver) set.
+    pub state_driver: Option<String>,
+}
+
+/// A system of a [`SystemStageGraph`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemNode {
+    pub name: String,
+    pub segment: SystemSegment,
+    pub labels: Vec<String>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    /// The index of the run criteria of the system.
+    pub run_criteria: Option<usize>,
+    /// The indices of the systems this system runs after.
+    pub dependencies: Vec<usize>,
+}
+
+/// The part of a [`SystemStage`] a system runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemSegment {
+    /// Exclusive systems at the start of the stage.
+    ExclusiveAtStart,
+    /// Parallel systems.
+    Parallel,
+    /// Exclusive systems after parallel systems, before the application of their commands.
+    ExclusiveBeforeCommands,
+    /// Exclusive systems at the end of the stage.
+    ExclusiveAtEnd,
+}
+
+impl SystemSegment {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            SystemSegment::ExclusiveAtStart => "exclusive_at_start",
+            SystemSegment::Parallel => "parallel",
+            SystemSegment::ExclusiveBeforeCommands => "exclusive_before_commands",
+            SystemSegment::ExclusiveAtEnd => "exclusive_at_end",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [
+            SystemSegment::ExclusiveAtStart,
+            SystemSegment::Parallel,
+            SystemSegment::ExclusiveBeforeCommands,
+            SystemSegment::ExclusiveAtEnd,
+        ]
+        .into_iter()
+        .find(|segment| segment.name() == name)
+    }
+
+    fn desc(&self) -> &'static str {
+        match self {
+            SystemSegment::ExclusiveAtStart => "exclusive at start",
+            SystemSegment::Parallel => "parallel",
+            SystemSegment::ExclusiveBeforeCommands => "exclusive before commands",
+            SystemSegment::ExclusiveAtEnd => "exclusive at end",
+        }
+    }
+}
+
+/// Two systems of a [`SystemStageGraph`] with an ambiguous execution order.
+#[derive(Debug, Clone, Serialize)]
+pub struct Ambiguity {
+    /// The indices of the systems.
+    pub systems: [usize; 2],
+    /// The names of the components and resources both systems access, one of them mutably.
+    ///
+    /// Empty if one of the systems is exclusive.
+    pub conflicts: Vec<String>,
+}
+
+impl Schedule {
+    /// Describes the stages, systems, run criteria and execution order ambiguities of this
+    /// schedule, including nested schedules.
+    ///
+    /// `world` is used to name the conflicting components of ambiguities. The result may be
+    /// incomplete if the schedule has not been run on `world`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #
+    /// # fn my_system() {}
+    /// # #[derive(StageLabel)]
+    /// # struct MyStage;
+    /// let mut world = World::new();
+    /// let mut schedule = Schedule::default()
+    ///     .with_stage(MyStage, SystemStage::single_threaded().with_system(my_system));
+    /// schedule.run(&mut world);
+    ///
+    /// let graph = schedule.export_graph(&world);
+    /// // Render with `dot -Tsvg schedule.dot -o schedule.svg`.
+    /// let dot = graph.to_dot();
+    /// # assert!(dot.contains("my_system"));
+    /// ```
+    pub fn export_graph(&self, world: &World) -> ScheduleGraph {
+        ScheduleGraph {
+            run_criteria: self.run_criteria.name().map(Into::into),
+            stages: self
+                .iter_stages()
+                .map(|(label, stage)| StageGraph {
+                    label: label.as_str().to_owned(),
+                    contents: StageContents::of(stage, world),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl StageContents {
+    fn of(stage: &dyn Stage, world: &World) -> Self {
+        if let Some(stage) = stage.downcast_ref::<SystemStage>() {
+            StageContents::Systems(stage.export_graph(world))
+        } else if let Some(schedule) = stage.downcast_ref::<Schedule>() {
+            StageContents::Schedule(schedule.export_graph(world))
+        } else {
+            StageContents::Other
+        }
+    }
+}
+
+impl SystemStage {
+    /// Describes the systems, run criteria and execution order ambiguities of this stage.
+    ///
+    /// `world` is used to name the conflicting components of ambiguities. The result may be
+    /// incomplete if the stage has not been run on `world`.
+    pub fn export_graph(&self, world: &World) -> SystemStageGraph {
+        let system_run_criteria = self
+            .run_criteria
+            .iter()
+            .map(|criteria| {
+                let name = criteria.name();
+                RunCriteriaNode {
+                    state_driver: criteria.state_driver.map(Into::into),
+                    name: name.into_owned(),
+                    label: criteria.label.map(|label| label.as_str().to_owned()),
+                    before: criteria.before.iter().map(|l| l.as_str().into()).collect(),
+                    after: criteria.after.iter().map(|l| l.as_str().into()).collect(),
+                    piped_from: match criteria.inner {
+                        RunCriteriaInner::Single(_) => None,
+                        RunCriteriaInner::Piped { input, .. } => Some(input),
+                    },
+                }
+            })
+            .collect();
+
+        let mut systems = Vec::new();
+        let mut ambiguities = Vec::new();
+        for (segment, containers) in self.segments() {
+            let offset = systems.len();
+            systems.extend(
+                containers
+                    .iter()
+                    .map(|container| SystemNode::new(container, segment, offset)),
+            );
+            ambiguities.extend(find_ambiguities(containers).into_iter().map(
+                |(system_a, system_b, conflicts)| {
+                    Ambiguity {
+                        systems: [offset + system_a, offset + system_b],
+                        conflicts: conflicts
+                            .into_iter()
+                            .map(|id| match world.components().get_info(id) {
+                                Some(info) => info.name().to_owned(),
+                                None => format!("{id:?}"),
+                            })
+                            .collect(),
+                    }
+                },
+            ));
+        }
+
+        SystemStageGraph {
+            run_criteria: self.stage_run_criteria.name().map(Into::into),
+            system_run_criteria,
+            systems,
+            ambiguities,
+        }
+    }
+}
+
+impl SystemNode {
+    fn new(container: &SystemContainer, segment: SystemSegment, offset: usize) -> Self {
+        SystemNode {
+            name: container.name().into_owned(),
+            segment,
+            labels: container
+                .labels()
+                .iter()
+                .map(|l| l.as_str().into())
+                .collect(),
+            before: container
+                .before()
+                .iter()
+                .map(|l| l.as_str().into())
+                .collect(),
+            after: container
+                .after()
+                .iter()
+                .map(|l| l.as_str().into())
+                .collect(),
+            run_criteria: container.run_criteria(),
+            dependencies: container
+                .dependencies()
+                .iter()
+                .map(|index| offset + index)
+                .collect(),
+        }
+    }
+}
+
+impl ScheduleGraph {
+    /// Renders the graph in the DOT language of Graphviz.
+    ///
+    /// Each stage is a cluster, and stages are linked in execution order. Within a stage, solid
+    /// edges go from a system to the systems that run after it, dotted edges from a run criteria
+    /// to the systems or piped run criteria it controls, and dashed red edges link ambiguous
+    /// systems.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph schedule {{").unwrap();
+        writeln!(dot, "    compound=true;").unwrap();
+        writeln!(dot, "    node [shape=box];").unwrap();
+        self.write_dot(&mut dot, "");
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String, prefix: &str) {
+        for (index, stage) in self.stages.iter().enumerate() {
+            let id = format!("{prefix}{index}");
+            writeln!(dot, "    subgraph \"cluster_{id}\" {{").unwrap();
+            let mut label = stage.label.clone();
+            if let Some(run_criteria) = self.stage_run_criteria(stage) {
+                write!(label, "\nrun criteria: {}", get_short_name(run_criteria)).unwrap();
+            }
+            writeln!(dot, "    label=\"{}\";", escape(&label)).unwrap();
+            writeln!(dot, "    \"{id}\" [shape=point, style=invis];").unwrap();
+            match &stage.contents {
+                StageContents::Systems(graph) => graph.write_dot(dot, &id),
+                StageContents::Schedule(schedule) => schedule.write_dot(dot, &format!("{id}_")),
+                StageContents::Other => {}
+            }
+            writeln!(dot, "    }}").unwrap();
+        }
+        for index in 1..self.stages.len() {
+            let (from, to) = (format!("{prefix}{}", index - 1), format!("{prefix}{index}"));
+            writeln!(
+                dot,
+                "    \"{from}\" -> \"{to}\" [ltail=\"cluster_{from}\", lhead=\"cluster_{to}\", style=bold];"
+            )
+            .unwrap();
+        }
+    }
+
+    fn stage_run_criteria<'a>(&self, stage: &'a StageGraph) -> Option<&'a str> {
+        match &stage.contents {
+            StageContents::Systems(graph) => graph.run_criteria.as_deref(),
+            StageContents::Schedule(schedule) => schedule.run_criteria.as_deref(),
+            StageContents::Other => None,
+        }
+    }
+}
+
+impl SystemStageGraph {
+    /// Returns the indices of the run criteria with the label.
+    fn labelled<'a>(&'a self, label: &'a String) -> impl Iterator<Item = usize> + 'a {
+        self.system_run_criteria
+            .iter()
+            .enumerate()
+            .filter(move |(_, criteria)| criteria.label.as_ref() == Some(label))
+            .map(|(index, _)| index)
+    }
+
+    fn write_dot(&self, dot: &mut String, stage: &str) {
+        for (index, criteria) in self.system_run_criteria.iter().enumerate() {
+            let label = match &criteria.state_driver {
+                Some(state) => format!("state driver: {}", get_short_name(state)),
+                None => get_short_name(&criteria.name),
+            };
+            writeln!(
+                dot,
+                "    \"{stage}_rc{index}\" [label=\"{}\", tooltip=\"{}\", shape=diamond];",
+                escape(&label),
+                escape(&criteria.name)
+            )
+            .unwrap();
+            // Edges follow the `before` and `after` labels of run criteria, such as the ones of
+            // the sets of a `State`, which run after its driver.
+            for other in criteria.after.iter().flat_map(|label| self.labelled(label)) {
+                writeln!(dot, "    \"{stage}_rc{other}\" -> \"{stage}_rc{index}\";").unwrap();
+            }
+            for other in criteria
+                .before
+                .iter()
+                .flat_map(|label| self.labelled(label))
+            {
+                writeln!(dot, "    \"{stage}_rc{index}\" -> \"{stage}_rc{other}\";").unwrap();
+            }
+            if let Some(input) = criteria.piped_from {
+                writeln!(
+                    dot,
+                    "    \"{stage}_rc{input}\" -> \"{stage}_rc{index}\" [style=dotted];"
+                )
+                .unwrap();
+            }
+        }
+
+        let mut segment = None;
+        for (index, system) in self.systems.iter().enumerate() {
+            if segment != Some(system.segment) {
+                if segment.is_some() {
+                    writeln!(dot, "    }}").unwrap();
+                }
+                segment = Some(system.segment);
+                writeln!(
+                    dot,
+                    "    subgraph \"cluster_{stage}_{:?}\" {{",
+                    system.segment
+                )
+                .unwrap();
+                writeln!(dot, "    label=\"{}\";", system.segment.desc()).unwrap();
+            }
+            writeln!(
+                dot,
+                "    \"{stage}_s{index}\" [label=\"{}\", tooltip=\"{}\"];",
+                escape(&get_short_name(&system.name)),
+                escape(&system.name)
+            )
+            .unwrap();
+        }
+        if segment.is_some() {
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        for (index, system) in self.systems.iter().enumerate() {
+            for dependency in &system.dependencies {
+                writeln!(
+                    dot,
+                    "    \"{stage}_s{dependency}\" -> \"{stage}_s{index}\";"
+                )
+                .unwrap();
+            }
+            if let Some(criteria) = system.run_criteria {
+                writeln!(
+                    dot,
+                    "    \"{stage}_rc{criteria}\" -> \"{stage}_s{index}\" [style=dotted];"
+                )
+                .unwrap();
+            }
+        }
+        for Ambiguity {
+            systems: [a, b],
+            conflicts,
+        } in &self.ambiguities
+        {
+            let conflicts: Vec<_> = conflicts.iter().map(|name| get_short_name(name)).collect();
+            writeln!(
+                dot,
+                "    \"{stage}_s{a}\" -> \"{stage}_s{b}\" [dir=none, style=dashed, color=red, constraint=false, label=\"{}\"];",
+                escape(&conflicts.join(", "))
+            )
+            .unwrap();
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as bevy_ecs,
+        prelude::*,
+        schedule::{Schedule, StageContents, SystemSegment},
+    };
+
+    #[derive(StageLabel)]
+    struct First;
+
+    #[derive(StageLabel)]
+    struct Nested;
+
+    #[derive(StageLabel)]
+    struct Inner;
+
+    #[derive(SystemLabel)]
+    struct Writes;
+
+    #[derive(Resource)]
+    struct R;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum AppState {
+        Menu,
+    }
+
+    fn write_a(_res: ResMut<R>) {}
+    fn write_b(_res: ResMut<R>) {}
+    fn after_writes() {}
+    fn on_menu() {}
+    fn at_start(_world: &mut World) {}
+
+    fn schedule() -> (World, Schedule) {
+        let mut world = World::new();
+        world.insert_resource(R);
+        world.insert_resource(State::new(AppState::Menu));
+        let mut schedule = Schedule::default()
+            .with_stage(
+                First,
+                SystemStage::single_threaded()
+                    .with_system(write_a.label(Writes))
+                    .with_system(write_b.label(Writes))
+                    .with_system(after_writes.after(Writes))
+                    .with_system(at_start.at_start())
+                    .with_system_set(State::<AppState>::get_driver())
+                    .with_system_set(State::on_update_set(AppState::Menu).with_system(on_menu)),
+            )
+            .with_stage(
+                Nested,
+                Schedule::default().with_stage(Inner, SystemStage::single_threaded()),
+            );
+        schedule.run(&mut world);
+        (world, schedule)
+    }
+
+    #[test]
+    fn export_stages_systems_and_ambiguities() {
+        let (world, schedule) = schedule();
+        let graph = schedule.export_graph(&world);
+
+        assert_eq!(graph.stages.len(), 2);
+        let stage = match &graph.stages[0].contents {
+            StageContents::Systems(stage) => stage,
+            _ => panic!("expected a system stage"),
+        };
+        let index_of = |name: &str| {
+            stage
+                .systems
+                .iter()
+                .position(|system| system.name.ends_with(name))
+                .unwrap()
+        };
+
+        assert_eq!(stage.systems[0].segment, SystemSegment::ExclusiveAtStart);
+        let after_writes = &stage.systems[index_of("::after_writes")];
+        assert_eq!(after_writes.after, ["Writes"]);
+        let mut dependencies = after_writes.dependencies.clone();
+        dependencies.sort_unstable();
+        let mut writers = vec![index_of("::write_a"), index_of("::write_b")];
+        writers.sort_unstable();
+        assert_eq!(dependencies, writers);
+
+        assert_eq!(stage.ambiguities.len(), 1);
+        let mut ambiguous = stage.ambiguities[0].systems;
+        ambiguous.sort_unstable();
+        assert_eq!(ambiguous.to_vec(), writers);
+        assert!(stage.ambiguities[0].conflicts[0].ends_with("::R"));
+
+        let driver = stage
+            .system_run_criteria
+            .iter()
+            .position(|criteria| criteria.state_driver.is_some())
+            .unwrap();
+        assert!(stage.system_run_criteria[driver]
+            .state_driver
+            .as_ref()
+            .unwrap()
+            .ends_with("::AppState"));
+        let on_menu = &stage.systems[index_of("::on_menu")];
+        let criteria = &stage.system_run_criteria[on_menu.run_criteria.unwrap()];
+        assert!(criteria.state_driver.is_none());
+
+        match &graph.stages[1].contents {
+            StageContents::Schedule(nested) => assert_eq!(nested.stages[0].label, "Inner"),
+            _ => panic!("expected a nested schedule"),
+        }
+    }
+
+    #[test]
+    fn render_dot() {
+        let (world, schedule) = schedule();
+        let graph = schedule.export_graph(&world);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph schedule {"));
+        assert!(dot.contains("label=\"First\";"));
+        assert!(dot.contains("[label=\"after_writes\""));
+        assert!(dot.contains("state driver: AppState"));
+        assert!(dot.contains("\"0_rc0\" -> \"0_rc1\";"));
+        assert!(dot.contains("color=red"));
+        assert!(dot.contains("\"0\" -> \"1\""));
+        assert!(dot.contains("subgraph \"cluster_1_0\""));
+    }
+
+    #[test]
+    fn export_json() {
+        let (world, schedule) = schedule();
+        let graph = schedule.export_graph(&world);
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert_eq!(json["run_criteria"], serde_json::Value::Null);
+        let first = &json["stages"][0];
+        assert_eq!(first["label"], "First");
+        assert_eq!(first["contents"]["kind"], "systems");
+        let systems = first["contents"]["systems"].as_array().unwrap();
+        assert_eq!(systems[0]["segment"], "exclusive_at_start");
+        let after_writes = systems
+            .iter()
+            .find(|system| system["name"].as_str().unwrap().ends_with("::after_writes"))
+            .unwrap();
+        assert_eq!(after_writes["after"], serde_json::json!(["Writes"]));
+        assert!(first["contents"]["system_run_criteria"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|criteria| criteria["state_driver"]
+                == "bevy_ecs::schedule::graph_export::tests::AppState"));
+
+        let nested = &json["stages"][1];
+        assert_eq!(nested["label"], "Nested");
+        assert_eq!(nested["contents"]["kind"], "schedule");
+        assert_eq!(nested["contents"]["stages"][0]["label"], "Inner");
+        assert_eq!(
+            nested["contents"]["stages"][0]["contents"]["kind"],
+            "systems"
+        );
+
+        // The JSON text can be parsed back by other tools.
+        let text = serde_json::to_string(&graph).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+            json
+        );
+    }
+}
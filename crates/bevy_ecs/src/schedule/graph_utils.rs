@@ -1,6 +1,6 @@
 use bevy_utils::{tracing::warn, HashMap, HashSet};
 use fixedbitset::FixedBitSet;
-use std::{borrow::Cow, fmt::Debug, hash::Hash};
+use std::{borrow::Cow, collections::BTreeSet, fmt::Debug, hash::Hash};
 
 pub enum DependencyGraphError<Labels> {
     GraphCycles(Vec<(usize, Labels)>),
@@ -12,6 +12,12 @@ pub trait GraphNode {
     fn labels(&self) -> &[Self::Label];
     fn before(&self) -> &[Self::Label];
     fn after(&self) -> &[Self::Label];
+
+    /// The labels that sort this node among the nodes it isn't ordered with, see
+    /// [`deterministic_topological_order`].
+    fn sorting_labels(&self) -> Vec<&Self::Label> {
+        self.labels().iter().collect()
+    }
 }
 
 /// Constructs a dependency graph of given nodes.
@@ -124,3 +130,75 @@ pub fn topological_order<Labels: Clone>(
     }
     Ok(sorted)
 }
+
+/// Generates a topological order for the given graph of `nodes` that does not depend on the
+/// order of the nodes or on hashing.
+///
+/// Whenever several nodes can come next, the one with the smallest sorted list of
+/// [sorting label](GraphNode::sorting_labels) names is picked. Names of nodes are not used, as
+/// they come from [`std::any::type_name`], which isn't stable across compilers and platforms.
+///
+/// Nodes that can come next at the same time with the same label names, like unlabeled
+/// systems, or labels of different types with the same name, keep the order in which they were
+/// given, and a warning is logged.
+pub fn deterministic_topological_order<Node, Labels: Clone>(
+    nodes: &[Node],
+    graph: &HashMap<usize, HashMap<usize, Labels>>,
+) -> Result<Vec<usize>, DependencyGraphError<Labels>>
+where
+    Node: GraphNode,
+    Node::Label: Debug,
+{
+    let keys: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            let mut labels: Vec<String> = node
+                .sorting_labels()
+                .into_iter()
+                .map(|label| format!("{label:?}"))
+                .collect();
+            labels.sort_unstable();
+            labels
+        })
+        .collect();
+    let mut dependency_counts = vec![0; nodes.len()];
+    let mut dependants = vec![Vec::new(); nodes.len()];
+    for (&node, dependencies) in graph {
+        dependency_counts[node] = dependencies.len();
+        for &dependency in dependencies.keys() {
+            dependants[dependency].push(node);
+        }
+    }
+    let mut ready: BTreeSet<_> = (0..nodes.len())
+        .filter(|&node| dependency_counts[node] == 0)
+        .map(|node| (&keys[node], node))
+        .collect();
+    let mut sorted = Vec::with_capacity(nodes.len());
+    while let Some(&(key, node)) = ready.iter().next() {
+        ready.remove(&(key, node));
+        if let Some(&(next_key, next)) = ready.iter().next() {
+            if next_key == key {
+                warn!(
+                    "{} and {} are not ordered and have the same labels {:?}, so their order \
+                    depends on the order they were added in. Add labels or ordering constraints \
+                    to make it deterministic.",
+                    nodes[node].name(),
+                    nodes[next].name(),
+                    key
+                );
+            }
+        }
+        sorted.push(node);
+        for &dependant in &dependants[node] {
+            dependency_counts[dependant] -= 1;
+            if dependency_counts[dependant] == 0 {
+                ready.insert((&keys[dependant], dependant));
+            }
+        }
+    }
+    if sorted.len() < nodes.len() {
+        // Some nodes are part of a cycle, which the regular sort reports.
+        topological_order(graph)?;
+    }
+    Ok(sorted)
+}
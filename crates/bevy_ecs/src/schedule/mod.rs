@@ -4,32 +4,42 @@
 //!  [`Stage`], which then lives within a [`Schedule`].
 
 mod ambiguity_detection;
+pub mod common_conditions;
 mod executor;
 mod executor_parallel;
+mod graph_export;
 pub mod graph_utils;
 mod label;
 mod run_criteria;
 mod stage;
 mod state;
+mod stepping;
 mod system_container;
 mod system_descriptor;
 mod system_set;
+mod timing;
 
+pub use ambiguity_detection::{
+    AmbiguityAllowList, AmbiguityReport, InvalidAmbiguityBaseline, SystemOrderAmbiguity,
+};
 pub use executor::*;
 pub use executor_parallel::*;
+pub use graph_export::*;
 pub use graph_utils::GraphNode;
 pub use label::*;
 pub use run_criteria::*;
 pub use stage::*;
 pub use state::*;
+pub use stepping::*;
 pub use system_container::*;
 pub use system_descriptor::*;
 pub use system_set::*;
+pub use timing::{SystemTiming, SystemTimings};
 
 use std::fmt::Debug;
 
-use crate::{system::IntoSystem, world::World};
-use bevy_utils::HashMap;
+use crate::{change_detection::DetectChanges, system::IntoSystem, world::World};
+use bevy_utils::{HashMap, Instant};
 
 /// A container of [`Stage`]s set to be run in a linear order.
 ///
@@ -366,8 +376,20 @@ impl Schedule {
         for label in &self.stage_order {
             #[cfg(feature = "trace")]
             let _stage_span = bevy_utils::tracing::info_span!("stage", name = ?label).entered();
+            if let Some(mut stepping) = world.get_resource_mut::<Stepping>() {
+                stepping.bypass_change_detection().begin_stage(*label);
+            }
+            let timed = world
+                .get_resource_mut::<SystemTimings>()
+                .map(|mut timings| (timings.set_current_stage(Some(*label)), Instant::now()));
             let stage = self.stages.get_mut(label).unwrap();
             stage.run(world);
+            if let Some((outer_stage, start)) = timed {
+                if let Some(mut timings) = world.get_resource_mut::<SystemTimings>() {
+                    timings.record_stage(*label, start.elapsed());
+                    timings.set_current_stage(outer_stage);
+                }
+            }
         }
     }
 
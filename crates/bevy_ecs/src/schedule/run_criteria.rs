@@ -1,5 +1,8 @@
 use crate::{
+    archetype::ArchetypeComponentId,
+    component::ComponentId,
     prelude::System,
+    query::Access,
     schedule::{GraphNode, RunCriteriaLabel, RunCriteriaLabelId},
     system::{BoxedSystem, IntoSystem, Local},
     world::World,
@@ -56,6 +59,26 @@ impl ShouldRun {
             ShouldRun::Yes
         }
     }
+
+    fn runs(self) -> bool {
+        matches!(self, ShouldRun::Yes | ShouldRun::YesAndCheckAgain)
+    }
+
+    fn checks_again(self) -> bool {
+        matches!(
+            self,
+            ShouldRun::YesAndCheckAgain | ShouldRun::NoAndCheckAgain
+        )
+    }
+
+    fn from_parts(runs: bool, check_again: bool) -> Self {
+        match (runs, check_again) {
+            (true, false) => ShouldRun::Yes,
+            (false, false) => ShouldRun::No,
+            (true, true) => ShouldRun::YesAndCheckAgain,
+            (false, true) => ShouldRun::NoAndCheckAgain,
+        }
+    }
 }
 
 impl From<bool> for ShouldRun {
@@ -80,6 +103,11 @@ impl BoxedRunCriteria {
         self.initialized = false;
     }
 
+    /// Returns the name of the criteria system, if one is set.
+    pub(crate) fn name(&self) -> Option<Cow<'static, str>> {
+        self.criteria_system.as_ref().map(|system| system.name())
+    }
+
     pub(crate) fn should_run(&mut self, world: &mut World) -> ShouldRun {
         if let Some(ref mut run_criteria) = self.criteria_system {
             if !self.initialized {
@@ -104,6 +132,18 @@ pub(crate) enum RunCriteriaInner {
     },
 }
 
+impl RunCriteriaInner {
+    /// Applies the commands of the criteria, so that later criteria can see them.
+    ///
+    /// Stages only do this for state drivers, the commands of other run criteria are not applied.
+    pub(crate) fn apply_buffers(&mut self, world: &mut World) {
+        match self {
+            RunCriteriaInner::Single(system) => system.apply_buffers(world),
+            RunCriteriaInner::Piped { system, .. } => system.apply_buffers(world),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RunCriteriaContainer {
     pub(crate) should_run: ShouldRun,
@@ -111,6 +151,8 @@ pub(crate) struct RunCriteriaContainer {
     pub(crate) label: Option<RunCriteriaLabelId>,
     pub(crate) before: Vec<RunCriteriaLabelId>,
     pub(crate) after: Vec<RunCriteriaLabelId>,
+    /// The type name of the [`State`](super::State) driven by this run criteria, if any.
+    pub(crate) state_driver: Option<&'static str>,
 }
 
 impl RunCriteriaContainer {
@@ -124,6 +166,7 @@ impl RunCriteriaContainer {
             label: descriptor.label,
             before: descriptor.before,
             after: descriptor.after,
+            state_driver: descriptor.state_driver,
         }
     }
 
@@ -188,6 +231,15 @@ pub struct RunCriteriaDescriptor {
     pub(crate) duplicate_label_strategy: DuplicateLabelStrategy,
     pub(crate) before: Vec<RunCriteriaLabelId>,
     pub(crate) after: Vec<RunCriteriaLabelId>,
+    pub(crate) state_driver: Option<&'static str>,
+}
+
+impl RunCriteriaDescriptor {
+    /// Marks this run criteria as the driver of the [`State<T>`](super::State).
+    pub(crate) fn drives_state<T: 'static>(mut self) -> Self {
+        self.state_driver = Some(std::any::type_name::<T>());
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -257,6 +309,40 @@ pub trait RunCriteriaDescriptorCoercion<Param> {
 
     /// Specifies that this criteria must be evaluated after a criteria with the given label.
     fn after(self, label: impl RunCriteriaLabel) -> RunCriteriaDescriptor;
+
+    /// Combines this criteria with `other`, so that systems run only when both say so.
+    ///
+    /// `other` is only evaluated when this criteria says yes. The combined criteria is checked
+    /// again while either of them asks for it, so a [`ShouldRun::YesAndCheckAgain`] loop, such
+    /// as the one of a fixed timestep, keeps going even when `other` says no for some steps.
+    /// Both criteria are evaluated again when the combined criteria is checked again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either criteria has a label or is piped from another criteria, or if `other`
+    /// is a label. Label the combined criteria instead.
+    fn and_then<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor;
+
+    /// Combines this criteria with `other`, so that systems run when either of them says so.
+    ///
+    /// `other` is only evaluated when this criteria says no. The combined criteria is checked
+    /// again while either of the evaluated criteria asks for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either criteria has a label or is piped from another criteria, or if `other`
+    /// is a label. Label the combined criteria instead.
+    fn or<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor;
+
+    /// Inverts this criteria, so that systems run when it says no and the other way around.
+    ///
+    /// Whether the criteria is checked again is kept as is: [`ShouldRun::YesAndCheckAgain`]
+    /// becomes [`ShouldRun::NoAndCheckAgain`] and the other way around.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the criteria has a label or is piped from another criteria.
+    fn not(self) -> RunCriteriaDescriptor;
 }
 
 impl RunCriteriaDescriptorCoercion<()> for RunCriteriaDescriptor {
@@ -281,6 +367,22 @@ impl RunCriteriaDescriptorCoercion<()> for RunCriteriaDescriptor {
         self.after.push(label.as_label());
         self
     }
+
+    fn and_then<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        CombinedRunCriteria::descriptor(
+            self,
+            Some(IntoRunCriteria::into(other)),
+            Combinator::AndThen,
+        )
+    }
+
+    fn or<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        CombinedRunCriteria::descriptor(self, Some(IntoRunCriteria::into(other)), Combinator::Or)
+    }
+
+    fn not(self) -> RunCriteriaDescriptor {
+        CombinedRunCriteria::descriptor(self, None, Combinator::Not)
+    }
 }
 
 fn new_run_criteria_descriptor(system: BoxedSystem<(), ShouldRun>) -> RunCriteriaDescriptor {
@@ -290,6 +392,7 @@ fn new_run_criteria_descriptor(system: BoxedSystem<(), ShouldRun>) -> RunCriteri
         duplicate_label_strategy: DuplicateLabelStrategy::Panic,
         before: vec![],
         after: vec![],
+        state_driver: None,
     }
 }
 
@@ -309,6 +412,18 @@ impl RunCriteriaDescriptorCoercion<()> for BoxedSystem<(), ShouldRun> {
     fn after(self, label: impl RunCriteriaLabel) -> RunCriteriaDescriptor {
         new_run_criteria_descriptor(self).after(label)
     }
+
+    fn and_then<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(self).and_then(other)
+    }
+
+    fn or<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(self).or(other)
+    }
+
+    fn not(self) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(self).not()
+    }
 }
 
 impl<S, Param> RunCriteriaDescriptorCoercion<Param> for S
@@ -331,6 +446,187 @@ where
     fn after(self, label: impl RunCriteriaLabel) -> RunCriteriaDescriptor {
         new_run_criteria_descriptor(Box::new(IntoSystem::into_system(self))).after(label)
     }
+
+    fn and_then<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(Box::new(IntoSystem::into_system(self))).and_then(other)
+    }
+
+    fn or<Marker>(self, other: impl IntoRunCriteria<Marker>) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(Box::new(IntoSystem::into_system(self))).or(other)
+    }
+
+    fn not(self) -> RunCriteriaDescriptor {
+        new_run_criteria_descriptor(Box::new(IntoSystem::into_system(self))).not()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Combinator {
+    AndThen,
+    Or,
+    Not,
+}
+
+/// A run criteria built by [`RunCriteriaDescriptorCoercion::and_then`],
+/// [`RunCriteriaDescriptorCoercion::or`] or [`RunCriteriaDescriptorCoercion::not`].
+struct CombinedRunCriteria {
+    first: BoxedSystem<(), ShouldRun>,
+    second: Option<BoxedSystem<(), ShouldRun>>,
+    combinator: Combinator,
+    name: Cow<'static, str>,
+    component_access: Access<ComponentId>,
+    archetype_component_access: Access<ArchetypeComponentId>,
+}
+
+impl CombinedRunCriteria {
+    fn descriptor(
+        first: RunCriteriaDescriptor,
+        second: Option<RunCriteriaDescriptorOrLabel>,
+        combinator: Combinator,
+    ) -> RunCriteriaDescriptor {
+        let (first, mut before, mut after) = Self::into_system(first);
+        let second = second.map(|second| match second {
+            RunCriteriaDescriptorOrLabel::Descriptor(descriptor) => {
+                let (system, other_before, other_after) = Self::into_system(descriptor);
+                before.extend(other_before);
+                after.extend(other_after);
+                system
+            }
+            RunCriteriaDescriptorOrLabel::Label(label) => {
+                panic!("Run criteria {label:?} is a label and cannot be combined.")
+            }
+        });
+        let name = match (combinator, &second) {
+            (Combinator::AndThen, Some(second)) => {
+                format!("{}.and_then({})", first.name(), second.name())
+            }
+            (Combinator::Or, Some(second)) => format!("{}.or({})", first.name(), second.name()),
+            _ => format!("{}.not()", first.name()),
+        };
+        RunCriteriaDescriptor {
+            system: RunCriteriaSystem::Single(Box::new(CombinedRunCriteria {
+                first,
+                second,
+                combinator,
+                name: name.into(),
+                component_access: Default::default(),
+                archetype_component_access: Default::default(),
+            })),
+            label: None,
+            duplicate_label_strategy: DuplicateLabelStrategy::Panic,
+            before,
+            after,
+            state_driver: None,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn into_system(
+        descriptor: RunCriteriaDescriptor,
+    ) -> (
+        BoxedSystem<(), ShouldRun>,
+        Vec<RunCriteriaLabelId>,
+        Vec<RunCriteriaLabelId>,
+    ) {
+        if let Some(label) = descriptor.label {
+            panic!("Run criteria labeled {label:?} cannot be combined, label the combined criteria instead.");
+        }
+        match descriptor.system {
+            RunCriteriaSystem::Single(system) => (system, descriptor.before, descriptor.after),
+            RunCriteriaSystem::Piped(system) => panic!(
+                "Run criteria {} is piped from another criteria and cannot be combined.",
+                system.name()
+            ),
+        }
+    }
+
+    fn systems_mut(&mut self) -> impl Iterator<Item = &mut BoxedSystem<(), ShouldRun>> {
+        std::iter::once(&mut self.first).chain(self.second.as_mut())
+    }
+}
+
+impl System for CombinedRunCriteria {
+    type In = ();
+    type Out = ShouldRun;
+
+    fn name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+
+    fn component_access(&self) -> &Access<ComponentId> {
+        &self.component_access
+    }
+
+    fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+        &self.archetype_component_access
+    }
+
+    fn is_send(&self) -> bool {
+        self.first.is_send() && self.second.as_ref().map_or(true, |second| second.is_send())
+    }
+
+    fn is_exclusive(&self) -> bool {
+        self.first.is_exclusive()
+            || self
+                .second
+                .as_ref()
+                .map_or(false, |second| second.is_exclusive())
+    }
+
+    unsafe fn run_unsafe(&mut self, _input: (), world: &World) -> ShouldRun {
+        let first = self.first.run_unsafe((), world);
+        let second = match (self.combinator, &mut self.second) {
+            (Combinator::AndThen, Some(second)) if first.runs() => second.run_unsafe((), world),
+            (Combinator::Or, Some(second)) if !first.runs() => second.run_unsafe((), world),
+            (Combinator::Not, _) => {
+                return ShouldRun::from_parts(!first.runs(), first.checks_again())
+            }
+            _ => return first,
+        };
+        ShouldRun::from_parts(second.runs(), first.checks_again() || second.checks_again())
+    }
+
+    fn apply_buffers(&mut self, world: &mut World) {
+        for system in self.systems_mut() {
+            system.apply_buffers(world);
+        }
+    }
+
+    fn initialize(&mut self, world: &mut World) {
+        self.first.initialize(world);
+        self.component_access.extend(self.first.component_access());
+        if let Some(second) = &mut self.second {
+            second.initialize(world);
+            self.component_access.extend(second.component_access());
+        }
+    }
+
+    fn update_archetype_component_access(&mut self, world: &World) {
+        self.first.update_archetype_component_access(world);
+        self.archetype_component_access
+            .extend(self.first.archetype_component_access());
+        if let Some(second) = &mut self.second {
+            second.update_archetype_component_access(world);
+            self.archetype_component_access
+                .extend(second.archetype_component_access());
+        }
+    }
+
+    fn check_change_tick(&mut self, change_tick: u32) {
+        for system in self.systems_mut() {
+            system.check_change_tick(change_tick);
+        }
+    }
+
+    fn get_last_change_tick(&self) -> u32 {
+        self.first.get_last_change_tick()
+    }
+
+    fn set_last_change_tick(&mut self, last_change_tick: u32) {
+        for system in self.systems_mut() {
+            system.set_last_change_tick(last_change_tick);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -351,6 +647,7 @@ impl RunCriteria {
             duplicate_label_strategy: DuplicateLabelStrategy::Panic,
             before: vec![],
             after: vec![label.as_label()],
+            state_driver: None,
         }
     }
 }
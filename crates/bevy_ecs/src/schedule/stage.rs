@@ -1,6 +1,6 @@
 use crate::{
     self as bevy_ecs,
-    change_detection::CHECK_TICK_THRESHOLD,
+    change_detection::{DetectChanges, CHECK_TICK_THRESHOLD},
     component::ComponentId,
     prelude::IntoSystem,
     schedule::{
@@ -8,7 +8,8 @@ use crate::{
         BoxedRunCriteria, DuplicateLabelStrategy, ExclusiveInsertionPoint, GraphNode,
         ParallelExecutor, ParallelSystemExecutor, RunCriteriaContainer, RunCriteriaDescriptor,
         RunCriteriaDescriptorOrLabel, RunCriteriaInner, RunCriteriaLabelId, ShouldRun,
-        SingleThreadedExecutor, SystemContainer, SystemDescriptor, SystemLabelId, SystemSet,
+        SingleThreadedExecutor, Stepping, SystemContainer, SystemDescriptor, SystemLabelId,
+        SystemSegment, SystemSet, SystemTimings,
     },
     world::{World, WorldId},
 };
@@ -70,9 +71,9 @@ pub struct SystemStage {
     /// Instance of a scheduling algorithm for running the systems.
     executor: Box<dyn ParallelSystemExecutor>,
     /// Determines whether the stage should run.
-    stage_run_criteria: BoxedRunCriteria,
+    pub(super) stage_run_criteria: BoxedRunCriteria,
     /// Topologically sorted run criteria of systems.
-    run_criteria: Vec<RunCriteriaContainer>,
+    pub(super) run_criteria: Vec<RunCriteriaContainer>,
     /// Topologically sorted exclusive systems that want to be run at the start of the stage.
     pub(super) exclusive_at_start: Vec<SystemContainer>,
     /// Topologically sorted exclusive systems that want to be run after parallel systems but
@@ -100,6 +101,8 @@ pub struct SystemStage {
     last_tick_check: u32,
     /// If true, buffers will be automatically applied at the end of the stage. If false, buffers must be manually applied.
     apply_buffers: bool,
+    /// If true, systems and run criteria are sorted by their labels instead of by insertion order.
+    deterministic: bool,
     must_read_resource: Option<ComponentId>,
 }
 
@@ -123,6 +126,7 @@ impl SystemStage {
             uninitialized_at_end: vec![],
             last_tick_check: Default::default(),
             apply_buffers: true,
+            deterministic: false,
             must_read_resource: None,
         }
     }
@@ -139,6 +143,22 @@ impl SystemStage {
         Self::new(Box::<ParallelExecutor>::default())
     }
 
+    /// Creates a stage that runs its systems one at a time in a reproducible order.
+    ///
+    /// Systems that are not ordered relative to each other are sorted by the names of the labels
+    /// added to them, so the order is the same on every run and platform and does not depend on
+    /// the order in which the systems were added. This is meant for lockstep networking and
+    /// replays. See [`SystemStage::set_deterministic`].
+    ///
+    /// Systems without labels, or with labels of the same names, can't be told apart: they run
+    /// in the order they were added in when nothing else orders them, and a warning is logged.
+    /// Give every system that can run next to another one a label or an ordering constraint.
+    pub fn deterministic() -> Self {
+        let mut stage = Self::single_threaded();
+        stage.deterministic = true;
+        stage
+    }
+
     pub fn get_executor<T: ParallelSystemExecutor>(&self) -> Option<&T> {
         self.executor.downcast_ref()
     }
@@ -242,6 +262,21 @@ impl SystemStage {
         self.apply_buffers = apply_buffers;
     }
 
+    /// Sets whether systems and run criteria that are not ordered relative to each other are
+    /// sorted by their label names instead of by insertion order, see
+    /// [`SystemStage::deterministic`].
+    ///
+    /// The order is only followed exactly by an executor that runs one system at a time, such
+    /// as the [`SingleThreadedExecutor`] used by [`SystemStage::deterministic`].
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.systems_modified |= self.deterministic != deterministic;
+        self.deterministic = deterministic;
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
     /// Topologically sorted parallel systems.
     ///
     /// Note that systems won't be fully-formed until the stage has been run at least once.
@@ -271,6 +306,19 @@ impl SystemStage {
         &self.exclusive_before_commands
     }
 
+    /// The systems of each segment of the stage, in execution order.
+    pub(crate) fn segments(&self) -> [(SystemSegment, &[SystemContainer]); 4] {
+        [
+            (SystemSegment::ExclusiveAtStart, &self.exclusive_at_start),
+            (SystemSegment::Parallel, &self.parallel),
+            (
+                SystemSegment::ExclusiveBeforeCommands,
+                &self.exclusive_before_commands,
+            ),
+            (SystemSegment::ExclusiveAtEnd, &self.exclusive_at_end),
+        ]
+    }
+
     #[must_use]
     pub fn with_system_set(mut self, system_set: SystemSet) -> Self {
         self.add_system_set(system_set);
@@ -477,22 +525,34 @@ impl SystemStage {
             "run criteria",
         );
         unwrap_dependency_cycle_error(
-            process_systems(&mut self.parallel, &run_criteria_labels),
+            process_systems(&mut self.parallel, &run_criteria_labels, self.deterministic),
             &self.parallel,
             "parallel systems",
         );
         unwrap_dependency_cycle_error(
-            process_systems(&mut self.exclusive_at_start, &run_criteria_labels),
+            process_systems(
+                &mut self.exclusive_at_start,
+                &run_criteria_labels,
+                self.deterministic,
+            ),
             &self.exclusive_at_start,
             "exclusive systems at start of stage",
         );
         unwrap_dependency_cycle_error(
-            process_systems(&mut self.exclusive_before_commands, &run_criteria_labels),
+            process_systems(
+                &mut self.exclusive_before_commands,
+                &run_criteria_labels,
+                self.deterministic,
+            ),
             &self.exclusive_before_commands,
             "exclusive systems before commands of stage",
         );
         unwrap_dependency_cycle_error(
-            process_systems(&mut self.exclusive_at_end, &run_criteria_labels),
+            process_systems(
+                &mut self.exclusive_at_end,
+                &run_criteria_labels,
+                self.deterministic,
+            ),
             &self.exclusive_at_end,
             "exclusive systems at end of stage",
         );
@@ -511,6 +571,41 @@ impl SystemStage {
         }
     }
 
+    /// Returns which systems may run this update according to the [`Stepping`] resource, or
+    /// `None` if the stage isn't being stepped through.
+    fn plan_stepping(&self, world: &mut World) -> Option<Vec<bool>> {
+        let mut stepping = world.get_resource_mut::<Stepping>()?;
+        if !stepping.is_stepping_stage() {
+            return None;
+        }
+        let systems: Vec<_> = self
+            .exclusive_at_start
+            .iter()
+            .chain(&self.parallel)
+            .chain(&self.exclusive_before_commands)
+            .chain(&self.exclusive_at_end)
+            .collect();
+        stepping.bypass_change_detection().plan_stage(&systems)
+    }
+
+    /// Moves the run durations of the systems into the [`SystemTimings`] resource.
+    ///
+    /// The durations are dropped if a system removed the resource during the stage.
+    fn collect_system_timings(&mut self, world: &mut World) {
+        let mut timings = world.get_resource_mut::<SystemTimings>();
+        let systems = self
+            .exclusive_at_start
+            .iter_mut()
+            .chain(&mut self.parallel)
+            .chain(&mut self.exclusive_before_commands)
+            .chain(&mut self.exclusive_at_end);
+        for container in systems {
+            if let (Some(run_time), Some(timings)) = (container.run_time.take(), &mut timings) {
+                timings.record_system(container.name(), run_time);
+            }
+        }
+    }
+
     /// All system and component change ticks are scanned once the world counter has incremented
     /// at least [`CHECK_TICK_THRESHOLD`](crate::change_detection::CHECK_TICK_THRESHOLD)
     /// times since the previous `check_tick` scan.
@@ -549,7 +644,11 @@ impl SystemStage {
     ) -> Result<HashMap<RunCriteriaLabelId, usize>, DependencyGraphError<HashSet<RunCriteriaLabelId>>>
     {
         let graph = graph_utils::build_dependency_graph(&self.run_criteria);
-        let order = graph_utils::topological_order(&graph)?;
+        let order = if self.deterministic {
+            graph_utils::deterministic_topological_order(&self.run_criteria, &graph)?
+        } else {
+            graph_utils::topological_order(&graph)?
+        };
         let mut order_inverted = order.iter().enumerate().collect::<Vec<_>>();
         order_inverted.sort_unstable_by_key(|(_, &key)| key);
         let labels: HashMap<_, _> = self
@@ -650,8 +749,8 @@ impl std::fmt::Debug for SystemStage {
         )?;
         write!(
             f,
-            "last_tick_check: {:?}, apply_buffers: {:?}, ",
-            self.last_tick_check, self.apply_buffers
+            "last_tick_check: {:?}, apply_buffers: {:?}, deterministic: {:?}, ",
+            self.last_tick_check, self.apply_buffers, self.deterministic
         )?;
         write!(f, "must_read_resource: {:?}}}", self.must_read_resource)
     }
@@ -662,9 +761,14 @@ impl std::fmt::Debug for SystemStage {
 fn process_systems(
     systems: &mut Vec<SystemContainer>,
     run_criteria_labels: &HashMap<RunCriteriaLabelId, usize>,
+    deterministic: bool,
 ) -> Result<(), DependencyGraphError<HashSet<SystemLabelId>>> {
     let mut graph = graph_utils::build_dependency_graph(systems);
-    let order = graph_utils::topological_order(&graph)?;
+    let order = if deterministic {
+        graph_utils::deterministic_topological_order(systems, &graph)?
+    } else {
+        graph_utils::topological_order(&graph)?
+    };
     let mut order_inverted = order.iter().enumerate().collect::<Vec<_>>();
     order_inverted.sort_unstable_by_key(|(_, &key)| key);
     for (index, container) in systems.iter_mut().enumerate() {
@@ -718,11 +822,24 @@ impl Stage for SystemStage {
             self.executor_modified = false;
         }
 
+        // With stepping, only the systems allowed by the `Stepping` resource may run. They are
+        // indexed in the order of `exclusive_at_start`, `parallel`, `exclusive_before_commands`
+        // then `exclusive_at_end`.
+        let stepping = self.plan_stepping(world);
+        let allowed = |index: usize| match &stepping {
+            Some(allowed) => allowed[index],
+            None => true,
+        };
+        let parallel_offset = self.exclusive_at_start.len();
+        let before_commands_offset = parallel_offset + self.parallel.len();
+        let at_end_offset = before_commands_offset + self.exclusive_before_commands.len();
+        let timed = world.contains_resource::<SystemTimings>();
+
         let mut run_stage_loop = true;
         while run_stage_loop {
             let should_run = self.stage_run_criteria.should_run(world);
             match should_run {
-                ShouldRun::No => return,
+                ShouldRun::No => break,
                 ShouldRun::NoAndCheckAgain => continue,
                 ShouldRun::YesAndCheckAgain => (),
                 ShouldRun::Yes => {
@@ -748,6 +865,11 @@ impl Stage for SystemStage {
                         ..
                     } => criteria.should_run = system.run(run_criteria[*parent].should_run, world),
                 }
+                // Sub-state drivers insert the resources of their state with commands, which the
+                // run criteria that follow them need to see.
+                if criteria.state_driver.is_some() {
+                    criteria.inner.apply_buffers(world);
+                }
             }
 
             let mut run_system_loop = true;
@@ -770,8 +892,10 @@ impl Stage for SystemStage {
                 }
 
                 // Run systems that want to be at the start of stage.
-                for container in &mut self.exclusive_at_start {
-                    if should_run(container, &self.run_criteria, default_should_run) {
+                for (index, container) in self.exclusive_at_start.iter_mut().enumerate() {
+                    if allowed(index)
+                        && should_run(container, &self.run_criteria, default_should_run)
+                    {
                         {
                             #[cfg(feature = "trace")]
                             let _system_span = bevy_utils::tracing::info_span!(
@@ -779,7 +903,7 @@ impl Stage for SystemStage {
                                 name = &*container.name()
                             )
                             .entered();
-                            container.system_mut().run((), world);
+                            container.run(world, timed);
                         }
                         {
                             #[cfg(feature = "trace")]
@@ -795,15 +919,17 @@ impl Stage for SystemStage {
 
                 // Run parallel systems using the executor.
                 // TODO: hard dependencies, nested sets, whatever... should be evaluated here.
-                for container in &mut self.parallel {
-                    container.should_run =
-                        should_run(container, &self.run_criteria, default_should_run);
+                for (index, container) in self.parallel.iter_mut().enumerate() {
+                    container.should_run = allowed(parallel_offset + index)
+                        && should_run(container, &self.run_criteria, default_should_run);
                 }
                 self.executor.run_systems(&mut self.parallel, world);
 
                 // Run systems that want to be between parallel systems and their command buffers.
-                for container in &mut self.exclusive_before_commands {
-                    if should_run(container, &self.run_criteria, default_should_run) {
+                for (index, container) in self.exclusive_before_commands.iter_mut().enumerate() {
+                    if allowed(before_commands_offset + index)
+                        && should_run(container, &self.run_criteria, default_should_run)
+                    {
                         {
                             #[cfg(feature = "trace")]
                             let _system_span = bevy_utils::tracing::info_span!(
@@ -811,7 +937,7 @@ impl Stage for SystemStage {
                                 name = &*container.name()
                             )
                             .entered();
-                            container.system_mut().run((), world);
+                            container.run(world, timed);
                         }
                         {
                             #[cfg(feature = "trace")]
@@ -841,8 +967,10 @@ impl Stage for SystemStage {
                 }
 
                 // Run systems that want to be at the end of stage.
-                for container in &mut self.exclusive_at_end {
-                    if should_run(container, &self.run_criteria, default_should_run) {
+                for (index, container) in self.exclusive_at_end.iter_mut().enumerate() {
+                    if allowed(at_end_offset + index)
+                        && should_run(container, &self.run_criteria, default_should_run)
+                    {
                         {
                             #[cfg(feature = "trace")]
                             let _system_span = bevy_utils::tracing::info_span!(
@@ -850,7 +978,7 @@ impl Stage for SystemStage {
                                 name = &*container.name()
                             )
                             .entered();
-                            container.system_mut().run((), world);
+                            container.run(world, timed);
                         }
                         {
                             #[cfg(feature = "trace")]
@@ -889,6 +1017,9 @@ impl Stage for SystemStage {
                                         system.run(run_criteria[*parent].should_run, world);
                                 }
                             }
+                            if criteria.state_driver.is_some() {
+                                criteria.inner.apply_buffers(world);
+                            }
                             match criteria.should_run {
                                 ShouldRun::Yes
                                 | ShouldRun::YesAndCheckAgain
@@ -905,6 +1036,10 @@ impl Stage for SystemStage {
                 default_should_run = ShouldRun::No;
             }
         }
+
+        if timed {
+            self.collect_system_timings(world);
+        }
     }
 }
 
@@ -1199,6 +1334,49 @@ mod tests {
         stage.run(&mut world);
     }
 
+    #[test]
+    fn deterministic_order() {
+        fn stage(reversed: bool) -> SystemStage {
+            let mut systems = vec![
+                make_parallel(3).label(L3),
+                make_parallel(1).label(L1),
+                make_parallel(2).label(L2).after(L3),
+                make_parallel(0).label(L0),
+                make_parallel(4).label(L4).before(L1),
+                make_exclusive(6).label(L1).at_start(),
+                make_exclusive(5).label(L0).at_start(),
+            ];
+            if reversed {
+                systems.reverse();
+            }
+            let mut stage = SystemStage::deterministic();
+            for system in systems {
+                stage.add_system(system);
+            }
+            stage
+        }
+
+        let mut orders = Vec::new();
+        for reversed in [false, true, false, true] {
+            let mut world = World::new();
+            world.init_resource::<EntityCount>();
+            stage(reversed).run(&mut world);
+            orders.push(world.remove_resource::<EntityCount>().unwrap().0);
+        }
+        // Unordered systems run by label name, whatever order they were added in.
+        for order in &orders {
+            assert_eq!(order, &vec![5, 6, 0, 3, 2, 4, 1]);
+        }
+
+        let mut world = World::new();
+        world.init_resource::<EntityCount>();
+        let mut stage = stage(false);
+        stage.set_deterministic(false);
+        assert!(!stage.is_deterministic());
+        stage.run(&mut world);
+        assert_eq!(world.resource::<EntityCount>().0.len(), 7);
+    }
+
     #[test]
     fn parallel_after() {
         let mut world = World::new();
@@ -1475,6 +1653,46 @@ mod tests {
         assert_eq!(stage.run_criteria.len(), 1);
     }
 
+    #[test]
+    fn combined_run_criteria() {
+        fn three_steps(mut steps: Local<usize>) -> ShouldRun {
+            *steps += 1;
+            if *steps <= 3 {
+                ShouldRun::YesAndCheckAgain
+            } else {
+                *steps = 0;
+                ShouldRun::No
+            }
+        }
+
+        let mut world = World::new();
+        world.init_resource::<EntityCount>();
+
+        // The loop of the first criteria goes on while the second one says no.
+        let mut stage = SystemStage::parallel().with_system(
+            make_parallel(0).with_run_criteria(three_steps.and_then(every_other_time)),
+        );
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<EntityCount>().0, vec![0, 0, 0]);
+
+        // Inverting a loop runs once it is done.
+        world.resource_mut::<EntityCount>().0.clear();
+        let mut stage = SystemStage::parallel()
+            .with_system(make_parallel(1).with_run_criteria(three_steps.not()));
+        stage.run(&mut world);
+        stage.run(&mut world);
+        assert_eq!(world.resource::<EntityCount>().0, vec![1, 1]);
+
+        world.resource_mut::<EntityCount>().0.clear();
+        let mut stage = SystemStage::parallel()
+            .with_system(make_parallel(2).with_run_criteria(every_other_time.or(ShouldRun::once)));
+        for _ in 0..4 {
+            stage.run(&mut world);
+        }
+        assert_eq!(world.resource::<EntityCount>().0, vec![2, 2, 2]);
+    }
+
     #[test]
     #[should_panic]
     fn duplicate_run_criteria_label_panic() {
@@ -1,14 +1,16 @@
 use crate::{
+    change_detection::DetectChanges,
     schedule::{
         RunCriteriaDescriptor, RunCriteriaDescriptorCoercion, RunCriteriaLabel, ShouldRun,
         SystemSet,
     },
-    system::{In, IntoPipeSystem, Local, Res, ResMut, Resource},
+    system::{Commands, In, IntoPipeSystem, Local, Res, ResMut, Resource},
 };
 use std::{
     any::TypeId,
     fmt::{self, Debug},
     hash::Hash,
+    marker::PhantomData,
 };
 // Required for derive macros
 use crate as bevy_ecs;
@@ -32,6 +34,10 @@ pub struct State<T: StateData> {
     stack: Vec<T>,
     scheduled: Option<ScheduledOperation<T>>,
     end_next_loop: bool,
+    /// Whether the driver returned [`ShouldRun::No`] the last time it ran.
+    finished: bool,
+    /// Whether a [`SubState`] driver follows this state.
+    has_sub_states: bool,
 }
 
 #[derive(Debug)]
@@ -44,6 +50,10 @@ enum StateTransition<T: StateData> {
     Entering(T, T),
     Resuming(T, T),
     Pausing(T, T),
+    /// An operation is scheduled, but the sub-states exit before it starts.
+    PreparingExit,
+    /// The state exits and its resource is removed, see [`SubState`].
+    Removing(T),
 }
 
 #[derive(Debug)]
@@ -76,54 +86,84 @@ where
     T: StateData,
 {
     pub fn on_update(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>| {
+        (move |state: Option<Res<State<T>>>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
             state.stack.last().unwrap() == &pred && state.transition.is_none()
         })
         .pipe(should_run_adapter::<T>)
         .after(DriverLabel::of::<T>())
     }
 
+    /// Runs on every update while the state exists, whatever its value.
+    ///
+    /// For a [`SubState`], this matches all the values of its parent state in which it exists.
+    pub fn on_any_update() -> RunCriteriaDescriptor {
+        (|state: Option<Res<State<T>>>| matches!(state, Some(state) if state.transition.is_none()))
+            .pipe(should_run_adapter::<T>)
+            .after(DriverLabel::of::<T>())
+    }
+
     pub fn on_inactive_update(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>, mut is_inactive: Local<bool>| match &state.transition {
-            Some(StateTransition::Pausing(ref relevant, _))
-            | Some(StateTransition::Resuming(_, ref relevant)) => {
-                if relevant == &pred {
-                    *is_inactive = !*is_inactive;
+        (move |state: Option<Res<State<T>>>, mut is_inactive: Local<bool>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
+            match &state.transition {
+                Some(StateTransition::Pausing(ref relevant, _))
+                | Some(StateTransition::Resuming(_, ref relevant)) => {
+                    if relevant == &pred {
+                        *is_inactive = !*is_inactive;
+                    }
+                    false
                 }
-                false
+                Some(_) => false,
+                None => *is_inactive,
             }
-            Some(_) => false,
-            None => *is_inactive,
         })
         .pipe(should_run_adapter::<T>)
         .after(DriverLabel::of::<T>())
     }
 
     pub fn on_in_stack_update(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>, mut is_in_stack: Local<bool>| match &state.transition {
-            Some(StateTransition::Entering(ref relevant, _))
-            | Some(StateTransition::ExitingToResume(_, ref relevant))
-            | Some(StateTransition::ExitingFull(_, ref relevant)) => {
-                if relevant == &pred {
-                    *is_in_stack = !*is_in_stack;
+        (move |state: Option<Res<State<T>>>, mut is_in_stack: Local<bool>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
+            match &state.transition {
+                Some(StateTransition::Entering(ref relevant, _))
+                | Some(StateTransition::ExitingToResume(_, ref relevant))
+                | Some(StateTransition::ExitingFull(_, ref relevant))
+                | Some(StateTransition::Removing(ref relevant)) => {
+                    if relevant == &pred {
+                        *is_in_stack = !*is_in_stack;
+                    }
+                    false
                 }
-                false
-            }
-            Some(StateTransition::Startup) => {
-                if state.stack.last().unwrap() == &pred {
-                    *is_in_stack = !*is_in_stack;
+                Some(StateTransition::Startup) => {
+                    if state.stack.last().unwrap() == &pred {
+                        *is_in_stack = !*is_in_stack;
+                    }
+                    false
                 }
-                false
+                Some(_) => false,
+                None => *is_in_stack,
             }
-            Some(_) => false,
-            None => *is_in_stack,
         })
         .pipe(should_run_adapter::<T>)
         .after(DriverLabel::of::<T>())
     }
 
     pub fn on_enter(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>| {
+        (move |state: Option<Res<State<T>>>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
             state
                 .transition
                 .as_ref()
@@ -138,13 +178,18 @@ where
     }
 
     pub fn on_exit(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>| {
+        (move |state: Option<Res<State<T>>>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
             state
                 .transition
                 .as_ref()
                 .map_or(false, |transition| match transition {
                     StateTransition::ExitingToResume(exiting, _)
-                    | StateTransition::ExitingFull(exiting, _) => exiting == &pred,
+                    | StateTransition::ExitingFull(exiting, _)
+                    | StateTransition::Removing(exiting) => exiting == &pred,
                     _ => false,
                 })
         })
@@ -153,7 +198,11 @@ where
     }
 
     pub fn on_pause(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>| {
+        (move |state: Option<Res<State<T>>>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
             state
                 .transition
                 .as_ref()
@@ -167,7 +216,11 @@ where
     }
 
     pub fn on_resume(pred: T) -> RunCriteriaDescriptor {
-        (move |state: Res<State<T>>| {
+        (move |state: Option<Res<State<T>>>| {
+            let state = match state {
+                Some(state) => state,
+                None => return false,
+            };
             state
                 .transition
                 .as_ref()
@@ -184,6 +237,10 @@ where
         SystemSet::new().with_run_criteria(Self::on_update(s))
     }
 
+    pub fn on_any_update_set() -> SystemSet {
+        SystemSet::new().with_run_criteria(Self::on_any_update())
+    }
+
     pub fn on_inactive_update_set(s: T) -> SystemSet {
         SystemSet::new().with_run_criteria(Self::on_inactive_update(s))
     }
@@ -209,7 +266,11 @@ where
     /// Important note: this set must be inserted **before** all other state-dependant sets to work
     /// properly!
     pub fn get_driver() -> SystemSet {
-        SystemSet::default().with_run_criteria(state_cleaner::<T>.label(DriverLabel::of::<T>()))
+        SystemSet::default().with_run_criteria(
+            state_cleaner::<T>
+                .label(DriverLabel::of::<T>())
+                .drives_state::<T>(),
+        )
     }
 
     pub fn new(initial: T) -> Self {
@@ -218,6 +279,8 @@ where
             transition: Some(StateTransition::PreStartup),
             scheduled: None,
             end_next_loop: false,
+            finished: false,
+            has_sub_states: false,
         }
     }
 
@@ -355,6 +418,105 @@ where
     pub fn clear_schedule(&mut self) {
         self.scheduled = None;
     }
+
+    /// Returns the state that will be current once the scheduled operation is done.
+    fn next(&self) -> Option<&T> {
+        match &self.scheduled {
+            Some(ScheduledOperation::Set(next))
+            | Some(ScheduledOperation::Replace(next))
+            | Some(ScheduledOperation::Push(next)) => Some(next),
+            Some(ScheduledOperation::Pop) => self.stack.iter().rev().nth(1),
+            None => None,
+        }
+    }
+}
+
+/// A state that only exists while its [`Parent`](SubState::Parent) state has certain values.
+///
+/// The `State<Self>` resource is inserted when the parent state enters a value for which
+/// [`from_parent`](SubState::from_parent) returns `Some`, and removed when it leaves it. The
+/// [`State::on_exit`] systems of the sub-state run before those of the parent, and its
+/// [`State::on_enter`] systems run after those of the parent. Paused states of a removed
+/// sub-state are dropped without running their systems.
+///
+/// While it exists, a sub-state can be changed like any other state. Run criteria of a sub-state
+/// don't match while it doesn't exist, and [`State::on_any_update`] matches as long as it does.
+///
+/// Sub-states are driven by [`State::get_sub_state_driver`], which must be added to the same
+/// stage as the driver of the parent state.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::schedule::SubState;
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum AppState {
+///     MainMenu,
+///     InGame,
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum PauseMenu {
+///     Hidden,
+///     Shown,
+/// }
+///
+/// impl SubState for PauseMenu {
+///     type Parent = AppState;
+///
+///     fn from_parent(parent: &AppState) -> Option<Self> {
+///         match parent {
+///             AppState::InGame => Some(PauseMenu::Hidden),
+///             AppState::MainMenu => None,
+///         }
+///     }
+/// }
+///
+/// # fn open_pause_menu() {}
+/// let mut world = World::new();
+/// world.insert_resource(State::new(AppState::InGame));
+/// let mut stage = SystemStage::parallel()
+///     .with_system_set(State::<AppState>::get_driver())
+///     .with_system_set(State::<PauseMenu>::get_sub_state_driver())
+///     .with_system_set(State::on_enter_set(PauseMenu::Shown).with_system(open_pause_menu));
+/// stage.run(&mut world);
+/// assert_eq!(world.resource::<State<PauseMenu>>().current(), &PauseMenu::Hidden);
+/// ```
+pub trait SubState: StateData {
+    type Parent: StateData;
+
+    /// If `true`, the state is reset to the value returned by
+    /// [`from_parent`](SubState::from_parent) on every transition of the parent, exiting and
+    /// entering again if it changes. Otherwise, it keeps its value as long as it exists.
+    const COMPUTED: bool = false;
+
+    /// Returns the value of this state when the parent state is `parent`, or `None` if it
+    /// doesn't exist then.
+    fn from_parent(parent: &Self::Parent) -> Option<Self>;
+}
+
+impl<S: SubState> State<S> {
+    /// Creates a driver set for the [`SubState`], which creates and removes it following its
+    /// parent state.
+    ///
+    /// Like [`State::get_driver`], this set must be inserted **before** all other
+    /// state-dependant sets of the sub-state, in the stage of the parent driver.
+    pub fn get_sub_state_driver() -> SystemSet {
+        SystemSet::default().with_run_criteria(
+            sub_state_driver::<S>
+                .label(DriverLabel::of::<S>())
+                .after(DriverLabel::of::<S::Parent>())
+                .drives_state::<S>(),
+        )
+    }
+}
+
+/// Tells the run criteria of a [`SubState`] whether to check again while it doesn't exist.
+#[derive(Resource)]
+struct SubStateStatus<T: StateData> {
+    awaiting_parent: bool,
+    marker: PhantomData<fn() -> T>,
 }
 
 #[derive(Debug)]
@@ -383,7 +545,18 @@ impl fmt::Display for StateError {
     }
 }
 
-fn should_run_adapter<T: StateData>(In(cmp_result): In<bool>, state: Res<State<T>>) -> ShouldRun {
+fn should_run_adapter<T: StateData>(
+    In(cmp_result): In<bool>,
+    state: Option<Res<State<T>>>,
+    status: Option<Res<SubStateStatus<T>>>,
+) -> ShouldRun {
+    let state = match state {
+        Some(state) => state,
+        None if matches!(status, Some(status) if status.awaiting_parent) => {
+            return ShouldRun::NoAndCheckAgain
+        }
+        None => return ShouldRun::No,
+    };
     if state.end_next_loop {
         return ShouldRun::No;
     }
@@ -398,6 +571,90 @@ fn state_cleaner<T: StateData>(
     mut state: ResMut<State<T>>,
     mut prep_exit: Local<bool>,
 ) -> ShouldRun {
+    let should_run = drive_state(&mut state, &mut prep_exit);
+    state.finished = should_run == ShouldRun::No;
+    should_run
+}
+
+fn sub_state_driver<S: SubState>(
+    mut commands: Commands,
+    mut parent: ResMut<State<S::Parent>>,
+    state: Option<ResMut<State<S>>>,
+    status: Option<ResMut<SubStateStatus<S>>>,
+    mut prep_exit: Local<bool>,
+) -> ShouldRun {
+    // The parent driver runs first, so the parent can't transition anymore once it's finished.
+    let awaiting_parent = !parent.finished;
+    match status {
+        Some(mut status) => status.bypass_change_detection().awaiting_parent = awaiting_parent,
+        None => {
+            parent.bypass_change_detection().has_sub_states = true;
+            commands.insert_resource(SubStateStatus::<S> {
+                awaiting_parent,
+                marker: PhantomData,
+            });
+        }
+    }
+    let idle = if awaiting_parent {
+        ShouldRun::NoAndCheckAgain
+    } else {
+        ShouldRun::No
+    };
+
+    // The parent value to follow: the next one before the parent exits, or the current one
+    // once it's settled.
+    let parent_value = match parent.transition {
+        Some(StateTransition::PreparingExit) => parent.next(),
+        None if parent.scheduled.is_none() => Some(parent.current()),
+        _ => None,
+    };
+
+    let mut state = match state {
+        Some(state) => state,
+        None => {
+            // Sub-states are entered once the parent has settled.
+            let initial = parent_value
+                .filter(|_| parent.transition.is_none())
+                .and_then(S::from_parent);
+            if let Some(initial) = initial {
+                commands.insert_resource(State::new(initial));
+                *prep_exit = false;
+                return ShouldRun::YesAndCheckAgain;
+            }
+            return idle;
+        }
+    };
+
+    if matches!(state.transition, Some(StateTransition::Removing(_))) {
+        commands.remove_resource::<State<S>>();
+        return idle;
+    }
+    if let Some(parent_value) = parent_value {
+        let keep = matches!(
+            S::from_parent(parent_value),
+            Some(value) if !S::COMPUTED || &value == state.current()
+        );
+        if !keep {
+            let current = state.current().clone();
+            state.scheduled = None;
+            state.end_next_loop = false;
+            state.transition = Some(StateTransition::Removing(current));
+            *prep_exit = false;
+            return ShouldRun::YesAndCheckAgain;
+        }
+    }
+
+    match drive_state(&mut state, &mut prep_exit) {
+        // Stay idle while the parent may still transition.
+        ShouldRun::No if awaiting_parent => {
+            state.end_next_loop = true;
+            ShouldRun::NoAndCheckAgain
+        }
+        should_run => should_run,
+    }
+}
+
+fn drive_state<T: StateData>(state: &mut State<T>, prep_exit: &mut bool) -> ShouldRun {
     if *prep_exit {
         *prep_exit = false;
         if state.scheduled.is_none() {
@@ -408,6 +665,11 @@ fn state_cleaner<T: StateData>(
         state.end_next_loop = false;
         return ShouldRun::No;
     }
+    if state.has_sub_states && state.transition.is_none() && state.scheduled.is_some() {
+        // Give the sub-states a loop to exit before this state does.
+        state.transition = Some(StateTransition::PreparingExit);
+        return ShouldRun::YesAndCheckAgain;
+    }
     match state.scheduled.take() {
         Some(ScheduledOperation::Set(next)) => {
             state.transition = Some(StateTransition::ExitingFull(
@@ -754,4 +1016,191 @@ mod test {
             &LoadState::Finish
         );
     }
+
+    #[test]
+    fn sub_state_follows_parent() {
+        #[derive(Clone, PartialEq, Eq, Debug, Hash)]
+        enum AppState {
+            Menu,
+            InGame,
+        }
+
+        #[derive(Clone, PartialEq, Eq, Debug, Hash)]
+        enum GamePhase {
+            Running,
+            Paused,
+        }
+
+        impl SubState for GamePhase {
+            type Parent = AppState;
+
+            fn from_parent(parent: &AppState) -> Option<Self> {
+                match parent {
+                    AppState::InGame => Some(GamePhase::Running),
+                    AppState::Menu => None,
+                }
+            }
+        }
+
+        #[derive(Resource, Default)]
+        struct NameList(Vec<&'static str>);
+
+        #[derive(Resource, Default)]
+        struct PhaseUpdates(usize);
+
+        fn push(name: &'static str) -> impl FnMut(ResMut<NameList>) {
+            move |mut r: ResMut<NameList>| r.0.push(name)
+        }
+
+        let mut world = World::new();
+        world.init_resource::<NameList>();
+        world.init_resource::<PhaseUpdates>();
+        world.insert_resource(State::new(AppState::Menu));
+
+        let mut stage = SystemStage::parallel()
+            .with_system_set(State::<AppState>::get_driver())
+            .with_system_set(State::<GamePhase>::get_sub_state_driver())
+            .with_system_set(State::on_enter_set(AppState::Menu).with_system(push("enter Menu")))
+            .with_system_set(State::on_exit_set(AppState::Menu).with_system(push("exit Menu")))
+            .with_system_set(
+                State::on_enter_set(AppState::InGame).with_system(push("enter InGame")),
+            )
+            .with_system_set(State::on_exit_set(AppState::InGame).with_system(push("exit InGame")))
+            .with_system_set(
+                State::on_enter_set(GamePhase::Running).with_system(push("enter Running")),
+            )
+            .with_system_set(
+                State::on_exit_set(GamePhase::Running).with_system(push("exit Running")),
+            )
+            .with_system_set(
+                State::on_enter_set(GamePhase::Paused).with_system(push("enter Paused")),
+            )
+            .with_system_set(State::on_exit_set(GamePhase::Paused).with_system(push("exit Paused")))
+            .with_system_set(
+                State::<GamePhase>::on_any_update_set()
+                    .with_system(|mut r: ResMut<PhaseUpdates>| r.0 += 1),
+            );
+
+        let mut run = |world: &mut World| {
+            stage.run(world);
+            std::mem::take(&mut world.resource_mut::<NameList>().0)
+        };
+
+        assert_eq!(run(&mut world), ["enter Menu"]);
+        assert!(!world.contains_resource::<State<GamePhase>>());
+
+        world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::InGame)
+            .unwrap();
+        assert_eq!(
+            run(&mut world),
+            ["exit Menu", "enter InGame", "enter Running"]
+        );
+
+        world
+            .resource_mut::<State<GamePhase>>()
+            .set(GamePhase::Paused)
+            .unwrap();
+        assert_eq!(run(&mut world), ["exit Running", "enter Paused"]);
+        assert_eq!(world.resource::<PhaseUpdates>().0, 2);
+
+        world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::Menu)
+            .unwrap();
+        assert_eq!(
+            run(&mut world),
+            ["exit Paused", "exit InGame", "enter Menu"]
+        );
+        assert!(!world.contains_resource::<State<GamePhase>>());
+        assert_eq!(world.resource::<PhaseUpdates>().0, 2);
+    }
+
+    #[test]
+    fn computed_sub_state_follows_parent_value() {
+        #[derive(Clone, PartialEq, Eq, Debug, Hash)]
+        enum AppState {
+            Menu,
+            Forest,
+            Cave,
+            Castle,
+        }
+
+        #[derive(Clone, PartialEq, Eq, Debug, Hash)]
+        enum Difficulty {
+            Easy,
+            Hard,
+        }
+
+        impl SubState for Difficulty {
+            type Parent = AppState;
+            const COMPUTED: bool = true;
+
+            fn from_parent(parent: &AppState) -> Option<Self> {
+                match parent {
+                    AppState::Forest => Some(Difficulty::Easy),
+                    AppState::Cave | AppState::Castle => Some(Difficulty::Hard),
+                    AppState::Menu => None,
+                }
+            }
+        }
+
+        #[derive(Resource, Default)]
+        struct NameList(Vec<&'static str>);
+
+        fn push(name: &'static str) -> impl FnMut(ResMut<NameList>) {
+            move |mut r: ResMut<NameList>| r.0.push(name)
+        }
+
+        let mut world = World::new();
+        world.init_resource::<NameList>();
+        world.insert_resource(State::new(AppState::Menu));
+
+        let mut stage = SystemStage::parallel()
+            .with_system_set(State::<AppState>::get_driver())
+            .with_system_set(State::<Difficulty>::get_sub_state_driver())
+            .with_system_set(State::on_exit_set(AppState::Menu).with_system(push("exit Menu")))
+            .with_system_set(
+                State::on_enter_set(AppState::Forest).with_system(push("enter Forest")),
+            )
+            .with_system_set(State::on_exit_set(AppState::Forest).with_system(push("exit Forest")))
+            .with_system_set(State::on_enter_set(AppState::Cave).with_system(push("enter Cave")))
+            .with_system_set(State::on_exit_set(AppState::Cave).with_system(push("exit Cave")))
+            .with_system_set(
+                State::on_enter_set(AppState::Castle).with_system(push("enter Castle")),
+            )
+            .with_system_set(State::on_enter_set(Difficulty::Easy).with_system(push("enter Easy")))
+            .with_system_set(State::on_exit_set(Difficulty::Easy).with_system(push("exit Easy")))
+            .with_system_set(State::on_enter_set(Difficulty::Hard).with_system(push("enter Hard")))
+            .with_system_set(State::on_exit_set(Difficulty::Hard).with_system(push("exit Hard")));
+
+        stage.run(&mut world);
+
+        let mut run = |world: &mut World, next: AppState| {
+            world.resource_mut::<State<AppState>>().set(next).unwrap();
+            stage.run(world);
+            std::mem::take(&mut world.resource_mut::<NameList>().0)
+        };
+
+        assert_eq!(
+            run(&mut world, AppState::Forest),
+            ["exit Menu", "enter Forest", "enter Easy"]
+        );
+        // The computed value changes, so the sub-state exits before its parent and enters
+        // again once the parent has settled.
+        assert_eq!(
+            run(&mut world, AppState::Cave),
+            ["exit Easy", "exit Forest", "enter Cave", "enter Hard"]
+        );
+        // The computed value stays the same, so the sub-state is left alone.
+        assert_eq!(
+            run(&mut world, AppState::Castle),
+            ["exit Cave", "enter Castle"]
+        );
+        assert_eq!(
+            world.resource::<State<Difficulty>>().current(),
+            &Difficulty::Hard
+        );
+    }
 }
@@ -0,0 +1,416 @@
+use crate::{
+    self as bevy_ecs,
+    schedule::{GraphNode, StageLabel, StageLabelId, SystemContainer, SystemLabelId},
+    system::{AsSystemLabel, Resource},
+};
+use bevy_utils::HashSet;
+use std::borrow::Cow;
+
+/// What a [`Stepping`] resource does on the next updates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StepAction {
+    /// No stepped system runs.
+    #[default]
+    Wait,
+    /// Runs the next system, then waits.
+    System,
+    /// Runs the remaining systems of the current stage, then waits.
+    Stage,
+    /// Runs systems until a breakpoint or the end of the frame, then waits.
+    Continue,
+}
+
+/// Debugger-like control over the systems of chosen [`SystemStage`](super::SystemStage)s.
+///
+/// While enabled, the systems of the stages added with [`add_stage`](Self::add_stage) only
+/// run when asked to: each update, stepping resumes at its cursor and runs the systems allowed
+/// by the pending [`StepAction`]. Other stages run normally, so the world can be inspected
+/// between updates and the app stays responsive.
+///
+/// In a stage, systems are stepped through in this order: exclusive systems at the start,
+/// parallel systems in their topological order, exclusive systems before commands, then
+/// exclusive systems at the end. Only one system at a time runs when stepping system by
+/// system, regardless of the executor.
+///
+/// The stages must be run by a [`Schedule`](super::Schedule) for stepping to apply.
+///
+/// ```
+/// use bevy_ecs::{prelude::*, schedule::Stepping};
+///
+/// #[derive(StageLabel)]
+/// struct Gameplay;
+///
+/// #[derive(Resource, Default)]
+/// struct Log(Vec<&'static str>);
+///
+/// fn first(mut log: ResMut<Log>) { log.0.push("first"); }
+/// fn second(mut log: ResMut<Log>) { log.0.push("second"); }
+///
+/// let mut world = World::new();
+/// world.init_resource::<Log>();
+/// let mut stepping = Stepping::new();
+/// stepping.add_stage(Gameplay).enable();
+/// world.insert_resource(stepping);
+///
+/// let mut schedule = Schedule::default().with_stage(
+///     Gameplay,
+///     SystemStage::parallel()
+///         .with_system(first)
+///         .with_system(second.after(first)),
+/// );
+///
+/// // Nothing runs until a step is requested.
+/// schedule.run(&mut world);
+/// assert!(world.resource::<Log>().0.is_empty());
+///
+/// world.resource_mut::<Stepping>().step_system();
+/// schedule.run(&mut world);
+/// assert_eq!(world.resource::<Log>().0, ["first"]);
+///
+/// world.resource_mut::<Stepping>().continue_frame();
+/// schedule.run(&mut world);
+/// assert_eq!(world.resource::<Log>().0, ["first", "second"]);
+/// ```
+#[derive(Resource, Debug, Default)]
+pub struct Stepping {
+    enabled: bool,
+    stages: HashSet<StageLabelId>,
+    breakpoints: HashSet<SystemLabelId>,
+    action: StepAction,
+    /// Set when resuming from a breakpoint, so that it doesn't stop right away.
+    resuming: bool,
+    /// The stepped stages, in the order they were run.
+    order: Vec<StageLabelId>,
+    /// The position in `order` of the stage about to run, if it is stepped.
+    current: Option<usize>,
+    /// The position in `order` of the last stepped stage that ran.
+    last: Option<usize>,
+    /// The position of the next system to run, as a stage position in `order` and a system
+    /// index. `None` until the first frame.
+    cursor: Option<(usize, usize)>,
+    next_system: Option<Cow<'static, str>>,
+}
+
+impl Stepping {
+    /// Creates a disabled [`Stepping`], without stepped stages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Steps through the systems of the stage.
+    pub fn add_stage(&mut self, label: impl StageLabel) -> &mut Self {
+        self.stages.insert(label.as_label());
+        self
+    }
+
+    /// Stops stepping through the systems of the stage.
+    pub fn remove_stage(&mut self, label: impl StageLabel) -> &mut Self {
+        self.stages.remove(&label.as_label());
+        self
+    }
+
+    /// Starts stepping, waiting at the start of the next frame.
+    pub fn enable(&mut self) -> &mut Self {
+        if !self.enabled {
+            self.enabled = true;
+            self.action = StepAction::Wait;
+            self.cursor = None;
+            self.last = None;
+            self.next_system = None;
+        }
+        self
+    }
+
+    /// Stops stepping: all systems run normally.
+    pub fn disable(&mut self) -> &mut Self {
+        self.enabled = false;
+        self
+    }
+
+    /// Returns `true` if stepping is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Runs the next system on the next update.
+    pub fn step_system(&mut self) -> &mut Self {
+        self.set_action(StepAction::System)
+    }
+
+    /// Runs the remaining systems of the current stage on the next update.
+    pub fn step_stage(&mut self) -> &mut Self {
+        self.set_action(StepAction::Stage)
+    }
+
+    /// Runs systems until a breakpoint or the end of the frame.
+    ///
+    /// If the next system has a breakpoint, it runs.
+    pub fn continue_frame(&mut self) -> &mut Self {
+        self.set_action(StepAction::Continue)
+    }
+
+    /// Returns the pending action.
+    pub fn action(&self) -> StepAction {
+        self.action
+    }
+
+    fn set_action(&mut self, action: StepAction) -> &mut Self {
+        self.action = action;
+        self.resuming = true;
+        self
+    }
+
+    /// Stops [`continue_frame`](Self::continue_frame) before running the systems with this
+    /// label.
+    pub fn set_breakpoint<Marker>(&mut self, label: impl AsSystemLabel<Marker>) -> &mut Self {
+        self.breakpoints.insert(label.as_system_label());
+        self
+    }
+
+    /// Removes a breakpoint added with [`set_breakpoint`](Self::set_breakpoint).
+    pub fn clear_breakpoint<Marker>(&mut self, label: impl AsSystemLabel<Marker>) -> &mut Self {
+        self.breakpoints.remove(&label.as_system_label());
+        self
+    }
+
+    /// Returns the stage and the name of the next system to run, if known.
+    ///
+    /// The next system is known once the stage it belongs to has been reached.
+    pub fn next_system(&self) -> Option<(StageLabelId, &str)> {
+        let (stage, _) = self.cursor?;
+        Some((*self.order.get(stage)?, self.next_system.as_deref()?))
+    }
+
+    /// Called by [`Schedule`](super::Schedule) before running a stage.
+    pub(crate) fn begin_stage(&mut self, label: StageLabelId) {
+        self.current = None;
+        if !self.enabled || !self.stages.contains(&label) {
+            return;
+        }
+        let position = match self.order.iter().position(|stage| *stage == label) {
+            Some(position) => position,
+            None => {
+                self.order.push(label);
+                self.order.len() - 1
+            }
+        };
+        // Stages run in order, so going back means a new frame started.
+        if !matches!(self.last, Some(last) if last < position) {
+            self.begin_frame();
+        }
+        self.last = Some(position);
+        self.current = Some(position);
+    }
+
+    fn begin_frame(&mut self) {
+        match self.cursor {
+            Some((stage, _)) if stage < self.order.len() => {}
+            Some(_) => {
+                // The previous frame was stepped through entirely.
+                if self.action == StepAction::Continue {
+                    self.action = StepAction::Wait;
+                }
+                self.cursor = Some((0, 0));
+            }
+            None => self.cursor = Some((0, 0)),
+        }
+    }
+
+    /// Returns `true` if the stage about to run is stepped.
+    pub(crate) fn is_stepping_stage(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Returns which of the `systems` of the stage about to run may run, or `None` if the stage
+    /// isn't stepped.
+    pub(crate) fn plan_stage(&mut self, systems: &[&SystemContainer]) -> Option<Vec<bool>> {
+        let position = self.current.take()?;
+        let mut allowed = vec![false; systems.len()];
+        let (stage, mut index) = self.cursor?;
+        if stage != position {
+            return Some(allowed);
+        }
+
+        match self.action {
+            StepAction::Wait => {}
+            StepAction::System => {
+                if index < systems.len() {
+                    allowed[index] = true;
+                    index += 1;
+                    self.action = StepAction::Wait;
+                }
+            }
+            StepAction::Stage => {
+                // The stage may have fewer systems than when the cursor was set.
+                allowed[index.min(systems.len())..].fill(true);
+                index = systems.len();
+                self.action = StepAction::Wait;
+            }
+            StepAction::Continue => {
+                while index < systems.len() {
+                    let at_breakpoint = systems[index]
+                        .labels()
+                        .iter()
+                        .any(|label| self.breakpoints.contains(label));
+                    if at_breakpoint && !self.resuming {
+                        self.action = StepAction::Wait;
+                        break;
+                    }
+                    self.resuming = false;
+                    allowed[index] = true;
+                    index += 1;
+                }
+            }
+        }
+        if allowed.contains(&true) {
+            self.resuming = false;
+        }
+
+        if index < systems.len() {
+            self.cursor = Some((position, index));
+            self.next_system = Some(systems[index].name());
+        } else {
+            self.cursor = Some((position + 1, 0));
+            self.next_system = None;
+        }
+        Some(allowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stepping;
+    use crate::{
+        self as bevy_ecs,
+        prelude::*,
+        schedule::{Schedule, StageLabel},
+    };
+    use bevy_tasks::{ComputeTaskPool, TaskPool};
+
+    #[derive(StageLabel)]
+    struct Stepped;
+
+    #[derive(StageLabel)]
+    struct Free;
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    fn a(mut log: ResMut<Log>) {
+        log.0.push("a");
+    }
+
+    fn b(mut log: ResMut<Log>) {
+        log.0.push("b");
+    }
+
+    fn c(mut log: ResMut<Log>) {
+        log.0.push("c");
+    }
+
+    fn free(mut log: ResMut<Log>) {
+        log.0.push("free");
+    }
+
+    fn setup(stage: SystemStage) -> (World, Schedule) {
+        let mut world = World::new();
+        world.init_resource::<Log>();
+        let mut stepping = Stepping::new();
+        stepping.add_stage(Stepped).enable();
+        world.insert_resource(stepping);
+        let schedule = Schedule::default()
+            .with_stage(
+                Stepped,
+                stage
+                    .with_system(a)
+                    .with_system(b.after(a))
+                    .with_system(c.after(b)),
+            )
+            .with_stage(Free, SystemStage::single(free));
+        (world, schedule)
+    }
+
+    fn take_log(world: &mut World) -> Vec<&'static str> {
+        std::mem::take(&mut world.resource_mut::<Log>().0)
+    }
+
+    #[test]
+    fn step_systems_and_stages() {
+        let (mut world, mut schedule) = setup(SystemStage::single_threaded());
+
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["free"]);
+
+        world.resource_mut::<Stepping>().step_system();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "free"]);
+        assert_eq!(
+            world
+                .resource::<Stepping>()
+                .next_system()
+                .map(|(stage, name)| (stage, name.ends_with("::b"))),
+            Some((Stepped.as_label(), true))
+        );
+
+        world.resource_mut::<Stepping>().step_stage();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["b", "c", "free"]);
+
+        // The frame was stepped through, the next step starts over.
+        world.resource_mut::<Stepping>().step_system();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "free"]);
+
+        world.resource_mut::<Stepping>().disable();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "b", "c", "free"]);
+    }
+
+    #[test]
+    fn step_stage_with_fewer_systems() {
+        let (mut world, mut schedule) = setup(SystemStage::single_threaded());
+        schedule.run(&mut world);
+        for _ in 0..2 {
+            world.resource_mut::<Stepping>().step_system();
+            schedule.run(&mut world);
+        }
+        assert_eq!(take_log(&mut world), ["free", "a", "free", "b", "free"]);
+
+        // The cursor is past the end of a stage with the same label but a single system.
+        let mut schedule = Schedule::default()
+            .with_stage(Stepped, SystemStage::single_threaded().with_system(a))
+            .with_stage(Free, SystemStage::single(free));
+        world.resource_mut::<Stepping>().step_stage();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["free"]);
+
+        world.resource_mut::<Stepping>().step_stage();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "free"]);
+    }
+
+    #[test]
+    fn continue_to_breakpoint() {
+        ComputeTaskPool::init(TaskPool::default);
+        let (mut world, mut schedule) = setup(SystemStage::parallel());
+        world.resource_mut::<Stepping>().set_breakpoint(c);
+
+        world.resource_mut::<Stepping>().continue_frame();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "b", "free"]);
+
+        // Continuing from a breakpoint runs it, then stops at the end of the frame.
+        world.resource_mut::<Stepping>().continue_frame();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["c", "free"]);
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["free"]);
+
+        world
+            .resource_mut::<Stepping>()
+            .clear_breakpoint(c)
+            .continue_frame();
+        schedule.run(&mut world);
+        assert_eq!(take_log(&mut world), ["a", "b", "c", "free"]);
+    }
+}
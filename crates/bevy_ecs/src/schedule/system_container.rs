@@ -2,10 +2,13 @@ use crate::{
     component::ComponentId,
     query::Access,
     schedule::{
-        AmbiguityDetection, GraphNode, RunCriteriaLabelId, SystemDescriptor, SystemLabelId,
+        timing::add_run_time, AmbiguityDetection, GraphNode, RunCriteriaLabelId, SystemDescriptor,
+        SystemLabelId,
     },
     system::System,
+    world::World,
 };
+use bevy_utils::{Duration, Instant};
 use core::fmt::Debug;
 use std::borrow::Cow;
 
@@ -20,6 +23,8 @@ pub struct SystemContainer {
     before: Vec<SystemLabelId>,
     after: Vec<SystemLabelId>,
     pub(crate) ambiguity_detection: AmbiguityDetection,
+    /// Time spent running the system since it was last collected, if timing is enabled.
+    pub(crate) run_time: Option<Duration>,
 }
 
 impl SystemContainer {
@@ -35,9 +40,24 @@ impl SystemContainer {
             after: descriptor.after,
             ambiguity_detection: descriptor.ambiguity_detection,
             is_exclusive: descriptor.exclusive_insertion_point.is_some(),
+            run_time: None,
         }
     }
 
+    /// Runs the system, adding its run duration to `run_time` if `timed` is set.
+    pub(crate) fn run(&mut self, world: &mut World, timed: bool) {
+        let start = timed.then(Instant::now);
+        self.system.run((), world);
+        add_run_time(&mut self.run_time, start);
+    }
+
+    /// Returns the system along with its accumulated run duration.
+    pub(crate) fn system_and_run_time_mut(
+        &mut self,
+    ) -> (&mut dyn System<In = (), Out = ()>, &mut Option<Duration>) {
+        (&mut *self.system, &mut self.run_time)
+    }
+
     pub fn name(&self) -> Cow<'static, str> {
         GraphNode::name(self)
     }
@@ -108,4 +128,14 @@ impl GraphNode for SystemContainer {
     fn after(&self) -> &[SystemLabelId] {
         &self.after
     }
+
+    /// The labels added to the system, without the label of its type, whose name comes from
+    /// [`std::any::type_name`].
+    fn sorting_labels(&self) -> Vec<&SystemLabelId> {
+        let default_labels = self.system().default_labels();
+        self.labels
+            .iter()
+            .filter(|label| !default_labels.contains(label))
+            .collect()
+    }
 }
@@ -0,0 +1,172 @@
+use crate::{self as bevy_ecs, schedule::StageLabelId, system::Resource};
+use bevy_utils::{Duration, Instant};
+use std::borrow::Cow;
+
+/// Collects the run duration of systems and stages.
+///
+/// While this resource is in the world, [`SystemStage`](super::SystemStage)s and their
+/// executors time each system they run, and [`Schedule`](super::Schedule)s time each of their
+/// stages. Durations accumulate until they are taken with [`drain_systems`](Self::drain_systems)
+/// and [`drain_stages`](Self::drain_stages), or cleared.
+///
+/// Timing a system includes its run, but not the application of its commands.
+#[derive(Resource, Debug, Default)]
+pub struct SystemTimings {
+    systems: Vec<SystemTiming>,
+    stages: Vec<(StageLabelId, Duration)>,
+    /// The stage of the [`Schedule`](super::Schedule) that is running, if any.
+    current_stage: Option<StageLabelId>,
+}
+
+/// The run duration of a system, recorded in [`SystemTimings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemTiming {
+    /// The label of the stage the system ran in, or `None` if the stage was not run by a
+    /// [`Schedule`](super::Schedule).
+    pub stage: Option<StageLabelId>,
+    pub name: Cow<'static, str>,
+    pub duration: Duration,
+}
+
+impl SystemTimings {
+    /// Returns the recorded system durations, in recording order.
+    ///
+    /// A system is recorded once per stage run, even if a looping run criteria made it run
+    /// several times.
+    pub fn systems(&self) -> &[SystemTiming] {
+        &self.systems
+    }
+
+    /// Returns the recorded stage durations, in recording order.
+    pub fn stages(&self) -> &[(StageLabelId, Duration)] {
+        &self.stages
+    }
+
+    /// Takes the recorded system durations, leaving them empty.
+    pub fn drain_systems(&mut self) -> Vec<SystemTiming> {
+        std::mem::take(&mut self.systems)
+    }
+
+    /// Takes the recorded stage durations, leaving them empty.
+    pub fn drain_stages(&mut self) -> Vec<(StageLabelId, Duration)> {
+        std::mem::take(&mut self.stages)
+    }
+
+    /// Clears the recorded durations.
+    pub fn clear(&mut self) {
+        self.systems.clear();
+        self.stages.clear();
+    }
+
+    pub(crate) fn record_system(&mut self, name: Cow<'static, str>, duration: Duration) {
+        self.systems.push(SystemTiming {
+            stage: self.current_stage,
+            name,
+            duration,
+        });
+    }
+
+    /// Sets the stage the next recorded systems run in, returning the previous one.
+    pub(crate) fn set_current_stage(
+        &mut self,
+        stage: Option<StageLabelId>,
+    ) -> Option<StageLabelId> {
+        std::mem::replace(&mut self.current_stage, stage)
+    }
+
+    pub(crate) fn record_stage(&mut self, label: StageLabelId, duration: Duration) {
+        self.stages.push((label, duration));
+    }
+}
+
+/// Adds the time elapsed since `start` to `run_time`, if timing was started.
+pub(crate) fn add_run_time(run_time: &mut Option<Duration>, start: Option<Instant>) {
+    if let Some(start) = start {
+        *run_time = Some(run_time.unwrap_or_default() + start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SystemTimings;
+    use crate::{
+        self as bevy_ecs,
+        prelude::*,
+        schedule::{Schedule, StageLabel},
+    };
+    use bevy_tasks::{ComputeTaskPool, TaskPool};
+
+    #[derive(StageLabel)]
+    struct Single;
+
+    #[derive(StageLabel)]
+    struct Parallel;
+
+    fn first() {}
+    fn second() {}
+    fn exclusive(_world: &mut World) {}
+
+    #[test]
+    fn time_systems_and_stages() {
+        ComputeTaskPool::init(TaskPool::default);
+        let mut world = World::new();
+        let mut schedule = Schedule::default()
+            .with_stage(
+                Single,
+                SystemStage::single_threaded()
+                    .with_system(first)
+                    .with_system(exclusive.at_start()),
+            )
+            .with_stage(Parallel, SystemStage::parallel().with_system(second));
+
+        // Nothing is timed without the resource.
+        schedule.run(&mut world);
+        world.init_resource::<SystemTimings>();
+        schedule.run(&mut world);
+
+        let mut timings = world.resource_mut::<SystemTimings>();
+        let systems = timings.drain_systems();
+        let stages = timings.drain_stages();
+        let names: Vec<_> = systems
+            .iter()
+            .map(|timing| (timing.stage, timing.name.rsplit("::").next().unwrap()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (Some(Single.as_label()), "exclusive"),
+                (Some(Single.as_label()), "first"),
+                (Some(Parallel.as_label()), "second")
+            ]
+        );
+        let stages: Vec<_> = stages.iter().map(|(label, _)| *label).collect();
+        assert_eq!(stages, [Single.as_label(), Parallel.as_label()]);
+        assert!(timings.systems().is_empty());
+    }
+
+    fn stop_timing(mut commands: Commands) {
+        commands.remove_resource::<SystemTimings>();
+    }
+
+    fn remove_timings(world: &mut World) {
+        world.remove_resource::<SystemTimings>();
+    }
+
+    #[test]
+    fn remove_timings_during_stage() {
+        let mut world = World::new();
+        world.init_resource::<SystemTimings>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(stop_timing)
+            .with_system(first);
+        stage.run(&mut world);
+        assert!(!world.contains_resource::<SystemTimings>());
+
+        world.init_resource::<SystemTimings>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(remove_timings.at_start())
+            .with_system(first);
+        stage.run(&mut world);
+        assert!(!world.contains_resource::<SystemTimings>());
+    }
+}
@@ -152,6 +152,13 @@ impl Resources {
         self.resources.get(component_id)
     }
 
+    /// Iterates over the resources and their component ids.
+    ///
+    /// This includes resources that were initialized but are not present.
+    pub fn iter(&self) -> impl Iterator<Item = (ComponentId, &ResourceData)> {
+        self.resources.iter().map(|(id, data)| (*id, data))
+    }
+
     /// Gets mutable access to a resource, if it exists.
     #[inline]
     pub(crate) fn get_mut(&mut self, component_id: ComponentId) -> Option<&mut ResourceData> {
@@ -113,6 +113,12 @@ impl ComponentSparseSet {
         self.dense.len() == 0
     }
 
+    /// Returns the number of components the set can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.dense.capacity()
+    }
+
     /// Inserts the `entity` key and component `value` pair into this sparse
     /// set.
     ///
@@ -419,6 +425,11 @@ impl SparseSets {
         self.sets.get_mut(component_id)
     }
 
+    /// Iterates over the sparse sets and the ids of the components they store.
+    pub fn iter(&self) -> impl Iterator<Item = (ComponentId, &ComponentSparseSet)> {
+        self.sets.iter().map(|(id, set)| (*id, set))
+    }
+
     pub fn clear(&mut self) {
         for set in self.sets.values_mut() {
             set.clear();
@@ -53,6 +53,13 @@ impl Column {
         self.data.layout()
     }
 
+    /// Returns the number of components the column can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        // The data of zero-sized components never reallocates, so its capacity is `usize::MAX`.
+        self.ticks.capacity()
+    }
+
     /// Writes component data to the column at given row.
     /// Assumes the slot is uninitialized, drop is not called.
     /// To overwrite existing initialized value, use `replace` instead.
@@ -477,6 +484,11 @@ impl Table {
         self.columns.values()
     }
 
+    /// Returns the ids of the components stored in the table, in the order of [`Table::iter`].
+    pub fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.columns.indices()
+    }
+
     pub(crate) fn clear(&mut self) {
         self.entities.clear();
         for column in self.columns.values_mut() {
@@ -0,0 +1,384 @@
+use crate::{
+    self as bevy_ecs,
+    change_detection::DetectChanges,
+    component::Component,
+    entity::Entity,
+    system::{Command, Resource},
+    world::World,
+};
+use async_channel::{Receiver, Sender};
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use bevy_utils::synccell::SyncCell;
+use std::future::Future;
+
+/// Runs with the world on behalf of an async task, and returns `true` once it is done.
+type WorldRequest = Box<dyn FnMut(&mut World) -> bool + Send>;
+
+/// The requests of async tasks waiting for world access.
+///
+/// They are applied by [`apply_async_world_requests`], which is the sync point of the tasks
+/// spawned with [`EntityCommands::spawn_task`](crate::system::EntityCommands::spawn_task).
+#[derive(Resource)]
+pub struct AsyncWorldRequests {
+    sender: Sender<WorldRequest>,
+    receiver: Receiver<WorldRequest>,
+    /// The requests waiting for a condition.
+    pending: SyncCell<Vec<WorldRequest>>,
+}
+
+impl Default for AsyncWorldRequests {
+    fn default() -> Self {
+        let (sender, receiver) = async_channel::unbounded();
+        Self {
+            sender,
+            receiver,
+            pending: SyncCell::new(Vec::new()),
+        }
+    }
+}
+
+/// An exclusive system that runs the requests of async tasks with the world.
+///
+/// Each call is a frame for the tasks: requests sent while it runs are handled by the next call.
+/// `App::new` runs it at the start of `CoreStage::Update`.
+pub fn apply_async_world_requests(world: &mut World) {
+    let (receiver, mut pending) = match world.get_resource_mut::<AsyncWorldRequests>() {
+        Some(mut requests) => {
+            let requests = requests.bypass_change_detection();
+            (
+                requests.receiver.clone(),
+                std::mem::take(requests.pending.get()),
+            )
+        }
+        None => return,
+    };
+    for _ in 0..receiver.len() {
+        match receiver.try_recv() {
+            Ok(request) => pending.push(request),
+            Err(_) => break,
+        }
+    }
+
+    pending.retain_mut(|request| !request(world));
+
+    if let Some(mut requests) = world.get_resource_mut::<AsyncWorldRequests>() {
+        requests
+            .bypass_change_detection()
+            .pending
+            .get()
+            .append(&mut pending);
+    }
+}
+
+/// The async tasks owned by an entity, spawned with
+/// [`EntityCommands::spawn_task`](crate::system::EntityCommands::spawn_task).
+///
+/// The tasks are cancelled when the entity is despawned or this component is removed.
+#[derive(Component, Default)]
+pub struct AsyncTasks {
+    tasks: Vec<Task<()>>,
+}
+
+impl AsyncTasks {
+    /// Returns the number of tasks that have not finished yet.
+    pub fn len(&self) -> usize {
+        self.tasks.iter().filter(|task| !task.is_finished()).count()
+    }
+
+    /// Returns `true` if every task has finished.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Gives world access to an async task spawned with
+/// [`EntityCommands::spawn_task`](crate::system::EntityCommands::spawn_task).
+///
+/// Its futures complete when [`apply_async_world_requests`] runs, which is a frame for the task.
+/// They never complete if the [`AsyncWorldRequests`] resource is removed, or once the owner is
+/// despawned, even if the task has not been cancelled yet.
+#[derive(Clone)]
+pub struct AsyncWorld {
+    owner: Entity,
+    sender: Sender<WorldRequest>,
+}
+
+impl AsyncWorld {
+    /// Returns the entity that owns the task.
+    pub fn owner(&self) -> Entity {
+        self.owner
+    }
+
+    /// Waits for the next frame.
+    pub async fn next_frame(&self) {
+        self.request(|_| Some(())).await;
+    }
+
+    /// Waits for the first frame where `condition` returns `true`, checking it once per frame
+    /// starting from the next one.
+    pub async fn until(&self, mut condition: impl FnMut(&mut World) -> bool + Send + 'static) {
+        self.request(move |world| condition(world).then_some(()))
+            .await;
+    }
+
+    /// Runs `f` with the world at the next frame, and returns its result.
+    pub async fn run<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut World) -> R + Send + 'static,
+    ) -> R {
+        let mut f = Some(f);
+        self.request(move |world| f.take().map(|f| f(world))).await
+    }
+
+    /// Calls `poll` once per frame until it returns a result.
+    async fn request<R: Send + 'static>(
+        &self,
+        mut poll: impl FnMut(&mut World) -> Option<R> + Send + 'static,
+    ) -> R {
+        let (sender, receiver) = async_channel::bounded(1);
+        let owner = self.owner;
+        let request: WorldRequest = Box::new(move |world| {
+            // The task was cancelled, or is being cancelled with its owner.
+            if sender.is_closed() || world.get_entity(owner).is_none() {
+                return true;
+            }
+            match poll(world) {
+                Some(result) => {
+                    sender.try_send(result).ok();
+                    true
+                }
+                None => false,
+            }
+        });
+        if self.sender.try_send(request).is_ok() {
+            if let Ok(result) = receiver.recv().await {
+                return result;
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+/// A [`Command`] that spawns an async task on the [`AsyncComputeTaskPool`], owned by an entity.
+///
+/// The task is cancelled right away if the entity does not exist.
+pub struct SpawnTask<F> {
+    pub owner: Entity,
+    pub task: F,
+}
+
+impl<F, Fut> Command for SpawnTask<F>
+where
+    F: FnOnce(AsyncWorld) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    fn write(self, world: &mut World) {
+        if world.get_entity(self.owner).is_none() {
+            return;
+        }
+        let sender = world
+            .get_resource_or_insert_with(AsyncWorldRequests::default)
+            .sender
+            .clone();
+        let task = AsyncComputeTaskPool::get().spawn((self.task)(AsyncWorld {
+            owner: self.owner,
+            sender,
+        }));
+
+        let mut owner = world.entity_mut(self.owner);
+        match owner.get_mut::<AsyncTasks>() {
+            Some(mut tasks) => {
+                tasks.tasks.retain(|task| !task.is_finished());
+                tasks.tasks.push(task);
+            }
+            None => {
+                owner.insert(AsyncTasks { tasks: vec![task] });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_async_world_requests, AsyncWorld, AsyncWorldRequests, SpawnTask};
+    use crate::{
+        self as bevy_ecs,
+        prelude::*,
+        system::{AsyncTasks, Command, CommandQueue},
+    };
+    use bevy_tasks::{AsyncComputeTaskPool, TaskPool};
+    use futures_lite::future;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    #[derive(Resource, Default)]
+    struct Counter(u32);
+
+    #[derive(Resource, Debug, PartialEq)]
+    struct Done(u32);
+
+    type LocalTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    /// Creates a task owned by `owner`, which is polled on the test thread instead of a task pool.
+    fn local_task<Fut>(
+        world: &mut World,
+        owner: Entity,
+        task: impl FnOnce(AsyncWorld) -> Fut,
+    ) -> LocalTask
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let sender = world
+            .get_resource_or_insert_with(AsyncWorldRequests::default)
+            .sender
+            .clone();
+        Box::pin(task(AsyncWorld { owner, sender }))
+    }
+
+    /// Polls the task until it waits for a frame, then runs the frame. Returns `true` once the
+    /// task is done.
+    fn run_frame(world: &mut World, task: &mut LocalTask) -> bool {
+        let done = future::block_on(future::poll_once(task.as_mut())).is_some();
+        apply_async_world_requests(world);
+        done
+    }
+
+    #[test]
+    fn async_task_waits_and_runs_with_world() {
+        let mut world = World::new();
+        world.init_resource::<Counter>();
+        let owner = world.spawn_empty().id();
+        let mut task = local_task(&mut world, owner, |world| async move {
+            world.until(|w| w.resource::<Counter>().0 == 3).await;
+            world.next_frame().await;
+            let value = world.run(|w| w.resource::<Counter>().0 * 10).await;
+            world.run(move |w| w.insert_resource(Done(value))).await;
+        });
+
+        let mut frames = 0;
+        while !run_frame(&mut world, &mut task) {
+            frames += 1;
+            assert!(frames < 10, "The async task did not finish");
+            world.resource_mut::<Counter>().0 += 1;
+        }
+        assert_eq!(world.get_resource::<Done>(), Some(&Done(50)));
+        // The condition is met on the fourth frame, then the task waits for a frame and for its
+        // two requests, and finishes when it's polled after the last one.
+        assert_eq!(frames, 7);
+    }
+
+    #[test]
+    fn async_task_stops_with_owner() {
+        let mut world = World::new();
+        world.init_resource::<Counter>();
+        let owner = world.spawn_empty().id();
+        let mut task = local_task(&mut world, owner, |world| async move {
+            loop {
+                world.run(|w| w.resource_mut::<Counter>().0 += 1).await;
+            }
+        });
+        for _ in 0..3 {
+            run_frame(&mut world, &mut task);
+        }
+        assert_eq!(world.resource::<Counter>().0, 3);
+
+        world.despawn(owner);
+        for _ in 0..3 {
+            assert!(!run_frame(&mut world, &mut task));
+        }
+        assert_eq!(world.resource::<Counter>().0, 3);
+    }
+
+    #[test]
+    fn spawned_task_is_owned_by_entity() {
+        AsyncComputeTaskPool::init(TaskPool::default);
+        let mut world = World::new();
+        let owner = world.spawn_empty().id();
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &world)
+            .entity(owner)
+            .spawn_task(|world| async move { world.next_frame().await });
+        queue.apply(&mut world);
+        assert_eq!(world.get::<AsyncTasks>(owner).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn task_is_not_spawned_for_missing_owner() {
+        AsyncComputeTaskPool::init(TaskPool::default);
+        let mut world = World::new();
+        let owner = world.spawn_empty().id();
+        world.despawn(owner);
+
+        let created = Arc::new(AtomicBool::new(false));
+        let task_created = created.clone();
+        SpawnTask {
+            owner,
+            task: move |world: AsyncWorld| {
+                task_created.store(true, Ordering::SeqCst);
+                async move { world.next_frame().await }
+            },
+        }
+        .write(&mut world);
+
+        assert!(!created.load(Ordering::SeqCst));
+        assert!(!world.contains_resource::<AsyncWorldRequests>());
+        assert_eq!(world.query::<&AsyncTasks>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn spawned_task_stops_with_owner() {
+        /// Sets the flag when the task is dropped.
+        struct DropFlag(Arc<AtomicBool>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        /// Runs frames until `condition` holds, giving the pool threads time to respond.
+        fn run_frames_until(world: &mut World, mut condition: impl FnMut(&World) -> bool) {
+            for _ in 0..1000 {
+                if condition(world) {
+                    return;
+                }
+                apply_async_world_requests(world);
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            panic!("The condition was not met in time");
+        }
+
+        AsyncComputeTaskPool::init(TaskPool::default);
+        let mut world = World::new();
+        world.init_resource::<Counter>();
+        let owner = world.spawn_empty().id();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        SpawnTask {
+            owner,
+            task: move |world: AsyncWorld| async move {
+                let _flag = flag;
+                loop {
+                    world.run(|w| w.resource_mut::<Counter>().0 += 1).await;
+                }
+            },
+        }
+        .write(&mut world);
+        run_frames_until(&mut world, |world| world.resource::<Counter>().0 >= 2);
+
+        world.despawn(owner);
+        let count = world.resource::<Counter>().0;
+        run_frames_until(&mut world, |_| dropped.load(Ordering::SeqCst));
+        for _ in 0..10 {
+            apply_async_world_requests(&mut world);
+        }
+        assert_eq!(world.resource::<Counter>().0, count);
+    }
+}
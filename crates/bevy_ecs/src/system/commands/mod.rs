@@ -4,14 +4,14 @@ mod parallel_scope;
 use crate::{
     bundle::Bundle,
     entity::{Entities, Entity},
-    world::{FromWorld, World},
+    world::{CloneEntityOptions, FromWorld, World},
 };
 use bevy_utils::tracing::{error, info};
 pub use command_queue::CommandQueue;
 pub use parallel_scope::*;
-use std::marker::PhantomData;
+use std::{future::Future, marker::PhantomData};
 
-use super::Resource;
+use super::{AsyncWorld, Resource, SpawnTask};
 
 /// A [`World`] mutation.
 ///
@@ -719,6 +719,76 @@ impl<'w, 's, 'a> EntityCommands<'w, 's, 'a> {
         });
     }
 
+    /// Spawns a copy of the entity and returns the [`EntityCommands`] of the copy.
+    ///
+    /// See [`World::clone_entity_with`] for the components that are copied.
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if the associated entity does not exist.
+    pub fn clone_entity(&mut self) -> EntityCommands<'w, 's, '_> {
+        self.clone_entity_with(CloneEntityOptions::default())
+    }
+
+    /// Spawns a copy of the entity with the given `options` and returns the
+    /// [`EntityCommands`] of the copy.
+    ///
+    /// See [`World::clone_entity_with`] for the components that are copied.
+    ///
+    /// # Panics
+    ///
+    /// The command will panic when applied if the associated entity does not exist.
+    pub fn clone_entity_with(&mut self, options: CloneEntityOptions) -> EntityCommands<'w, 's, '_> {
+        let target = self.commands.spawn_empty().id();
+        self.commands.add(CloneEntity {
+            source: self.entity,
+            target,
+            options,
+        });
+        self.commands.entity(target)
+    }
+
+    /// Spawns an async task on the [`AsyncComputeTaskPool`](bevy_tasks::AsyncComputeTaskPool),
+    /// owned by the entity.
+    ///
+    /// The task is given an [`AsyncWorld`] to wait for frames and conditions, and to run
+    /// closures with the world when [`apply_async_world_requests`](super::apply_async_world_requests)
+    /// runs. It is cancelled when the entity is despawned, see [`AsyncTasks`](super::AsyncTasks).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Resource)]
+    /// struct Loaded(bool);
+    ///
+    /// #[derive(Component)]
+    /// struct Dialog(&'static str);
+    ///
+    /// fn start_tutorial(mut commands: Commands) {
+    ///     commands.spawn_empty().spawn_task(|world| async move {
+    ///         world.until(|world| world.resource::<Loaded>().0).await;
+    ///         let dialog = world.run(|world| world.spawn(Dialog("Welcome!")).id()).await;
+    ///         for _ in 0..60 {
+    ///             world.next_frame().await;
+    ///         }
+    ///         world.run(move |world| world.despawn(dialog)).await;
+    ///     });
+    /// }
+    /// # bevy_ecs::system::assert_is_system(start_tutorial);
+    /// ```
+    pub fn spawn_task<F, Fut>(&mut self, task: F) -> &mut Self
+    where
+        F: FnOnce(AsyncWorld) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.commands.add(SpawnTask {
+            owner: self.entity,
+            task,
+        });
+        self
+    }
+
     /// Logs the components of the entity at the info level.
     ///
     /// # Panics
@@ -818,6 +888,18 @@ pub struct Despawn {
     pub entity: Entity,
 }
 
+pub struct CloneEntity {
+    pub source: Entity,
+    pub target: Entity,
+    pub options: CloneEntityOptions,
+}
+
+impl Command for CloneEntity {
+    fn write(self, world: &mut World) {
+        world.clone_entity_into(self.source, self.target, &self.options);
+    }
+}
+
 impl Command for Despawn {
     fn write(self, world: &mut World) {
         world.despawn(self.entity);
@@ -1041,7 +1123,7 @@ mod tests {
             .iter(&world)
             .map(|v| v.0)
             .collect::<Vec<_>>();
-        assert_eq!(results_after_u64, vec![]);
+        assert_eq!(results_after_u64, Vec::<u64>::new());
     }
 
     #[test]
@@ -0,0 +1,364 @@
+use crate::{
+    self as bevy_ecs,
+    archetype::Archetype,
+    change_detection::DetectChanges,
+    component::{Component, ComponentHook, ComponentId},
+    entity::Entity,
+    query::{Changed, QueryState},
+    system::{
+        Query, ResMutState, Resource, SystemMeta, SystemParam, SystemParamFetch, SystemParamState,
+    },
+    world::{DeferredWorld, FromWorld, World},
+};
+use bevy_utils::{HashMap, Hashed};
+use std::{hash::Hash, marker::PhantomData};
+
+/// A lookup table from values of the component `T` to the entities that have them.
+///
+/// This resource is created the first time a system with an [`Index<T>`] parameter is
+/// initialized, or explicitly with [`World::init_resource`]. From then on, inserting and
+/// removing `T` keeps it up to date through the `on_insert` and `on_remove`
+/// [component hooks](crate::component::ComponentHooks) of `T`. Hooks of `T` registered before
+/// the index still run, before the ones of the index.
+///
+/// Values changed in place through `&mut T` are re-indexed by the [`Index<T>`] parameter, from
+/// the `T` components changed since its system last ran. Until then, the resource itself keeps
+/// them under their previous value.
+#[derive(Resource)]
+pub struct ComponentIndex<T: Component + Hash + Eq> {
+    /// Entities by the hash of their value.
+    entities: HashMap<u64, Vec<Entity>>,
+    hashes: HashMap<Entity, u64>,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// The hooks of `T` that were registered before the ones of [`ComponentIndex<T>`], which run
+/// them first.
+///
+/// Its presence also means that the hooks of the index are registered.
+#[derive(Resource)]
+struct IndexHooks<T: Component + Hash + Eq> {
+    on_insert: Option<ComponentHook>,
+    on_remove: Option<ComponentHook>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Component + Hash + Eq> ComponentIndex<T> {
+    fn hash(value: &T) -> u64 {
+        Hashed::<&T>::new(value).hash()
+    }
+
+    /// Returns the entities with a value of the given hash, which may include values with the
+    /// same hash.
+    fn candidates(&self, hash: u64) -> &[Entity] {
+        self.entities.get(&hash).map_or(&[], Vec::as_slice)
+    }
+
+    fn insert(&mut self, entity: Entity, hash: u64) {
+        if let Some(previous) = self.hashes.insert(entity, hash) {
+            if previous == hash {
+                return;
+            }
+            self.remove_candidate(previous, entity);
+        }
+        self.entities.entry(hash).or_default().push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(hash) = self.hashes.remove(&entity) {
+            self.remove_candidate(hash, entity);
+        }
+    }
+
+    fn remove_candidate(&mut self, hash: u64, entity: Entity) {
+        if let Some(entities) = self.entities.get_mut(&hash) {
+            if let Some(index) = entities.iter().position(|candidate| *candidate == entity) {
+                entities.swap_remove(index);
+            }
+            if entities.is_empty() {
+                self.entities.remove(&hash);
+            }
+        }
+    }
+}
+
+impl<T: Component + Hash + Eq> FromWorld for ComponentIndex<T> {
+    fn from_world(world: &mut World) -> Self {
+        if !world.contains_resource::<IndexHooks<T>>() {
+            let hooks = world.register_component_hooks::<T>();
+            let previous = IndexHooks::<T> {
+                on_insert: hooks.on_insert.replace(on_insert::<T>),
+                on_remove: hooks.on_remove.replace(on_remove::<T>),
+                marker: PhantomData,
+            };
+            world.insert_resource(previous);
+        }
+
+        let mut index = ComponentIndex {
+            entities: HashMap::default(),
+            hashes: HashMap::default(),
+            marker: PhantomData,
+        };
+        for (entity, value) in world.query::<(Entity, &T)>().iter(world) {
+            index.insert(entity, Self::hash(value));
+        }
+        index
+    }
+}
+
+fn on_insert<T: Component + Hash + Eq>(
+    mut world: DeferredWorld,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    if let Some(hook) = world
+        .get_resource::<IndexHooks<T>>()
+        .and_then(|hooks| hooks.on_insert)
+    {
+        hook(world.reborrow(), entity, component_id);
+    }
+    let hash = match world.get::<T>(entity) {
+        Some(value) => ComponentIndex::hash(value),
+        None => return,
+    };
+    if let Some(mut index) = world.get_resource_mut::<ComponentIndex<T>>() {
+        index.bypass_change_detection().insert(entity, hash);
+    }
+}
+
+fn on_remove<T: Component + Hash + Eq>(
+    mut world: DeferredWorld,
+    entity: Entity,
+    component_id: ComponentId,
+) {
+    if let Some(hook) = world
+        .get_resource::<IndexHooks<T>>()
+        .and_then(|hooks| hooks.on_remove)
+    {
+        hook(world.reborrow(), entity, component_id);
+    }
+    if let Some(mut index) = world.get_resource_mut::<ComponentIndex<T>>() {
+        index.bypass_change_detection().remove(entity);
+    }
+}
+
+/// Finds entities by the value of their `T` component, without scanning all of them.
+///
+/// Using this parameter creates the [`ComponentIndex<T>`] resource if needed. It reads `T` and
+/// writes the index, so systems using it don't run in parallel with systems writing `T` or with
+/// each other.
+///
+/// Values are indexed right away when `T` is inserted or removed. Before each run of the
+/// system, the entities whose `T` changed in place are re-indexed under their new value.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::system::Index;
+/// #[derive(Component, Hash, PartialEq, Eq)]
+/// struct NetworkId(u64);
+///
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// fn damage(index: Index<NetworkId>, mut health: Query<&mut Health>) {
+///     for entity in index.get(&NetworkId(42)) {
+///         if let Ok(mut health) = health.get_mut(entity) {
+///             health.0 = health.0.saturating_sub(10);
+///         }
+///     }
+/// }
+/// # bevy_ecs::system::assert_is_system(damage);
+/// ```
+pub struct Index<'w, 's, T: Component + Hash + Eq> {
+    index: &'w ComponentIndex<T>,
+    query: Query<'w, 's, &'static T>,
+}
+
+impl<'w, 's, T: Component + Hash + Eq> Index<'w, 's, T> {
+    /// Returns the entities whose `T` is equal to `value`.
+    pub fn get<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Entity> + 'a {
+        self.index
+            .candidates(ComponentIndex::hash(value))
+            .iter()
+            .copied()
+            .filter(move |entity| {
+                matches!(self.query.get(*entity), Ok(candidate) if candidate == value)
+            })
+    }
+
+    /// Returns `true` if an entity's `T` is equal to `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.get(value).next().is_some()
+    }
+}
+
+/// The [`SystemParamState`] of [`Index<T>`].
+#[doc(hidden)]
+pub struct IndexState<T: Component + Hash + Eq> {
+    index: ResMutState<ComponentIndex<T>>,
+    query: QueryState<&'static T>,
+    changed: QueryState<(Entity, &'static T), Changed<T>>,
+}
+
+impl<'w, 's, T: Component + Hash + Eq> SystemParam for Index<'w, 's, T> {
+    type Fetch = IndexState<T>;
+}
+
+// SAFETY: The access of the index resource and of the queries over `T` is applied to
+// SystemMeta by their own states, which panic on conflicting access.
+unsafe impl<T: Component + Hash + Eq> SystemParamState for IndexState<T> {
+    fn init(world: &mut World, system_meta: &mut SystemMeta) -> Self {
+        world.init_resource::<ComponentIndex<T>>();
+        Self {
+            index: SystemParamState::init(world, system_meta),
+            query: SystemParamState::init(world, system_meta),
+            changed: SystemParamState::init(world, system_meta),
+        }
+    }
+
+    fn new_archetype(&mut self, archetype: &Archetype, system_meta: &mut SystemMeta) {
+        SystemParamState::new_archetype(&mut self.query, archetype, system_meta);
+        SystemParamState::new_archetype(&mut self.changed, archetype, system_meta);
+    }
+}
+
+impl<'w, 's, T: Component + Hash + Eq> SystemParamFetch<'w, 's> for IndexState<T> {
+    type Item = Index<'w, 's, T>;
+
+    #[inline]
+    unsafe fn get_param(
+        state: &'s mut Self,
+        system_meta: &SystemMeta,
+        world: &'w World,
+        change_tick: u32,
+    ) -> Self::Item {
+        // The index is only refreshed, so it isn't marked as changed.
+        let index = <ResMutState<ComponentIndex<T>> as SystemParamFetch>::get_param(
+            &mut state.index,
+            system_meta,
+            world,
+            change_tick,
+        )
+        .value;
+        let changed = <QueryState<_, _> as SystemParamFetch>::get_param(
+            &mut state.changed,
+            system_meta,
+            world,
+            change_tick,
+        );
+        for (entity, value) in changed.iter() {
+            index.insert(entity, ComponentIndex::hash(value));
+        }
+        let query = <QueryState<_> as SystemParamFetch>::get_param(
+            &mut state.query,
+            system_meta,
+            world,
+            change_tick,
+        );
+        Index { index, query }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentIndex, Index};
+    use crate::{self as bevy_ecs, prelude::*, system::SystemState};
+
+    #[derive(Component, Debug, Clone, Hash, PartialEq, Eq)]
+    struct Name(&'static str);
+
+    fn find(
+        world: &mut World,
+        state: &mut SystemState<Index<Name>>,
+        name: &'static str,
+    ) -> Vec<Entity> {
+        let index = state.get_mut(world);
+        let mut entities: Vec<_> = index.get(&Name(name)).collect();
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn index_follows_insert_and_remove() {
+        let mut world = World::new();
+        let player = world.spawn(Name("Player")).id();
+        let mut state = SystemState::<Index<Name>>::new(&mut world);
+        assert!(world.contains_resource::<ComponentIndex<Name>>());
+        assert_eq!(find(&mut world, &mut state, "Player"), [player]);
+
+        let enemies = [
+            world.spawn(Name("Enemy")).id(),
+            world.spawn(Name("Enemy")).id(),
+        ];
+        assert_eq!(find(&mut world, &mut state, "Enemy"), enemies);
+
+        world.entity_mut(enemies[0]).insert(Name("Boss"));
+        assert_eq!(find(&mut world, &mut state, "Enemy"), [enemies[1]]);
+        assert_eq!(find(&mut world, &mut state, "Boss"), [enemies[0]]);
+
+        world.entity_mut(enemies[1]).insert(Name("Player"));
+        assert_eq!(find(&mut world, &mut state, "Player"), [player, enemies[1]]);
+
+        world.entity_mut(player).remove::<Name>();
+        world.despawn(enemies[1]);
+        assert!(find(&mut world, &mut state, "Player").is_empty());
+        assert!(!state.get_mut(&mut world).contains(&Name("Enemy")));
+    }
+
+    #[test]
+    fn values_changed_in_place_are_reindexed() {
+        let mut world = World::new();
+        let enemy = world.spawn(Name("Enemy")).id();
+        let other = world.spawn(Name("Enemy")).id();
+        let mut state = SystemState::<Index<Name>>::new(&mut world);
+        assert_eq!(find(&mut world, &mut state, "Enemy"), [enemy, other]);
+
+        world.get_mut::<Name>(enemy).unwrap().0 = "Boss";
+        assert_eq!(find(&mut world, &mut state, "Enemy"), [other]);
+        assert_eq!(find(&mut world, &mut state, "Boss"), [enemy]);
+
+        // A system using the index for the first time also sees earlier changes.
+        world.get_mut::<Name>(other).unwrap().0 = "Boss";
+        let mut other_state = SystemState::<Index<Name>>::new(&mut world);
+        assert!(find(&mut world, &mut other_state, "Enemy").is_empty());
+        assert_eq!(find(&mut world, &mut other_state, "Boss"), [enemy, other]);
+        let index = world.resource::<ComponentIndex<Name>>();
+        assert_eq!(
+            index.candidates(ComponentIndex::hash(&Name("Boss"))).len(),
+            2
+        );
+        assert!(index
+            .candidates(ComponentIndex::hash(&Name("Enemy")))
+            .is_empty());
+    }
+
+    #[derive(Resource, Default)]
+    struct HookCalls(usize);
+
+    #[test]
+    fn index_runs_existing_hooks() {
+        let mut world = World::new();
+        world.init_resource::<HookCalls>();
+        world
+            .register_component_hooks::<Name>()
+            .on_insert(|mut world, _, _| world.resource_mut::<HookCalls>().0 += 1)
+            .on_remove(|mut world, _, _| world.resource_mut::<HookCalls>().0 += 10);
+        let entity = world.spawn(Name("Player")).id();
+        assert_eq!(world.resource::<HookCalls>().0, 1);
+
+        let mut state = SystemState::<Index<Name>>::new(&mut world);
+        world.entity_mut(entity).insert(Name("Enemy"));
+        assert_eq!(world.resource::<HookCalls>().0, 2);
+        assert_eq!(find(&mut world, &mut state, "Enemy"), [entity]);
+
+        // Recreating the index doesn't register its hooks twice.
+        world.remove_resource::<ComponentIndex<Name>>();
+        let mut state = SystemState::<Index<Name>>::new(&mut world);
+        assert_eq!(find(&mut world, &mut state, "Enemy"), [entity]);
+        world.despawn(entity);
+        assert_eq!(world.resource::<HookCalls>().0, 12);
+        assert!(find(&mut world, &mut state, "Enemy").is_empty());
+    }
+}
@@ -68,20 +68,24 @@
 //! - All tuples between 1 to 16 elements where each element implements [`SystemParam`]
 //! - [`()` (unit primitive type)](https://doc.rust-lang.org/stable/std/primitive.unit.html)
 
+mod async_task;
 mod commands;
 mod exclusive_function_system;
 mod exclusive_system_param;
 mod function_system;
+mod index;
 mod query;
 #[allow(clippy::module_inception)]
 mod system;
 mod system_param;
 mod system_piping;
 
+pub use async_task::*;
 pub use commands::*;
 pub use exclusive_function_system::*;
 pub use exclusive_system_param::*;
 pub use function_system::*;
+pub use index::*;
 pub use query::*;
 pub use system::*;
 pub use system_param::*;
@@ -0,0 +1,141 @@
+use crate::{
+    change_detection::Mut,
+    component::{ComponentHook, ComponentHooks, ComponentId, Components},
+    entity::Entity,
+    event::Event,
+    prelude::Component,
+    system::{CommandQueue, Commands, Resource},
+    world::{EntityRef, World},
+};
+
+/// A [`World`] reference that disallows structural changes such as spawning entities or
+/// inserting and removing components.
+///
+/// It is handed to [component hooks](ComponentHooks), which run while an entity is being
+/// changed. Structural changes can still be queued through [`DeferredWorld::commands`]: they
+/// are applied once the operation that triggered the hook has completed.
+pub struct DeferredWorld<'w> {
+    world: &'w mut World,
+    queue: &'w mut CommandQueue,
+}
+
+impl<'w> DeferredWorld<'w> {
+    pub(crate) fn new(world: &'w mut World, queue: &'w mut CommandQueue) -> Self {
+        Self { world, queue }
+    }
+
+    /// Returns a [`DeferredWorld`] with a shorter lifetime, for example to call another hook.
+    #[inline]
+    pub fn reborrow(&mut self) -> DeferredWorld<'_> {
+        DeferredWorld {
+            world: self.world,
+            queue: self.queue,
+        }
+    }
+
+    /// Returns a read-only reference to the underlying [`World`].
+    #[inline]
+    pub fn world(&self) -> &World {
+        self.world
+    }
+
+    /// Returns a [`Commands`] instance whose commands are applied once the operation that
+    /// triggered the hook has completed.
+    #[inline]
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        Commands::new(self.queue, self.world)
+    }
+
+    /// Retrieves an [`EntityRef`] that exposes read-only operations for the given `entity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `entity` does not exist.
+    #[inline]
+    pub fn entity(&self, entity: Entity) -> EntityRef {
+        self.world.entity(entity)
+    }
+
+    /// Retrieves an [`EntityRef`] for the given `entity`, or [`None`] if it does not exist.
+    #[inline]
+    pub fn get_entity(&self, entity: Entity) -> Option<EntityRef> {
+        self.world.get_entity(entity)
+    }
+
+    /// Retrieves a reference to the given `entity`'s [`Component`] of the given type.
+    #[inline]
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.world.get(entity)
+    }
+
+    /// Retrieves a mutable reference to the given `entity`'s [`Component`] of the given type.
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<T>> {
+        self.world.get_mut(entity)
+    }
+
+    /// Gets a reference to the resource of the given type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    #[inline]
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.world.resource()
+    }
+
+    /// Gets a mutable reference to the resource of the given type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    #[inline]
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.world.resource_mut()
+    }
+
+    /// Gets a reference to the resource of the given type if it exists.
+    #[inline]
+    pub fn get_resource<R: Resource>(&self) -> Option<&R> {
+        self.world.get_resource()
+    }
+
+    /// Gets a mutable reference to the resource of the given type if it exists.
+    #[inline]
+    pub fn get_resource_mut<R: Resource>(&mut self) -> Option<Mut<'_, R>> {
+        self.world.get_resource_mut()
+    }
+
+    /// Sends an [`Event`].
+    #[inline]
+    pub fn send_event<E: Event>(&mut self, event: E) {
+        self.world.send_event(event);
+    }
+}
+
+/// Collects the hooks selected by `hook` for the given components.
+pub(crate) fn collect_hooks(
+    components: &Components,
+    component_ids: impl IntoIterator<Item = ComponentId>,
+    hook: impl Fn(&ComponentHooks) -> Option<ComponentHook>,
+) -> Vec<(ComponentHook, ComponentId)> {
+    component_ids
+        .into_iter()
+        .filter_map(|id| {
+            let info = components.get_info(id)?;
+            Some((hook(info.hooks())?, id))
+        })
+        .collect()
+}
+
+/// Runs `hooks` for `entity`, queueing their commands into `queue`.
+pub(crate) fn run_hooks(
+    world: &mut World,
+    queue: &mut CommandQueue,
+    entity: Entity,
+    hooks: &[(ComponentHook, ComponentId)],
+) {
+    for &(hook, component_id) in hooks {
+        hook(DeferredWorld::new(world, queue), entity, component_id);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{
+    component::{Component, ComponentId},
+    entity::{Entity, EntityMap},
+    world::World,
+};
+use bevy_utils::{Entry, HashSet};
+use std::{any::TypeId, ops::Deref};
+
+#[cfg(feature = "bevy_reflect")]
+use crate::reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities};
+
+/// Pushes the entities an entity refers to through one of its components.
+type RelatedEntitiesFn = fn(&World, Entity, &mut Vec<Entity>);
+
+/// Chooses how [`World::clone_entity_with`] copies an entity.
+///
+/// By default, only the given entity is cloned, with all of its components that can be cloned.
+#[derive(Clone, Default)]
+pub struct CloneEntityOptions {
+    skip: HashSet<TypeId>,
+    related: Vec<RelatedEntitiesFn>,
+}
+
+impl CloneEntityOptions {
+    /// Doesn't copy the component `T`.
+    pub fn skip<T: Component>(mut self) -> Self {
+        self.skip.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Also clones the entities listed by the component `C` of every cloned entity, recursively.
+    ///
+    /// Using the `Children` component of `bevy_hierarchy` clones a whole hierarchy, with the
+    /// `Parent` and `Children` of the clones pointing to each other. The copy of the root is
+    /// added to the children of the original's parent, see
+    /// `bevy_hierarchy::clone_with_children_recursive`.
+    pub fn recursive<C: Component + Deref<Target = [Entity]>>(mut self) -> Self {
+        self.related.push(push_related::<C>);
+        self
+    }
+}
+
+fn push_related<C: Component + Deref<Target = [Entity]>>(
+    world: &World,
+    entity: Entity,
+    related: &mut Vec<Entity>,
+) {
+    if let Some(component) = world.get::<C>(entity) {
+        related.extend(component.iter().copied());
+    }
+}
+
+impl World {
+    /// Spawns a copy of `entity` and returns it.
+    ///
+    /// See [`World::clone_entity_with`] for the components that are copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entity` does not exist.
+    pub fn clone_entity(&mut self, entity: Entity) -> Entity {
+        self.clone_entity_with(entity, &CloneEntityOptions::default())
+    }
+
+    /// Spawns a copy of `entity`, and of its related entities if the `options` ask for it, and
+    /// returns the copy of `entity`.
+    ///
+    /// Components whose clone function was registered with
+    /// [`World::register_component_clone`] or [`World::register_component_clone_with`] are
+    /// copied with it. Other components are copied through their `ReflectComponent`, found in
+    /// the `AppTypeRegistry` resource. Components that have neither are not copied.
+    ///
+    /// Once every entity is copied, the entities held by components with `ReflectMapEntities`
+    /// are mapped from the original entities to their copies. References to entities that were
+    /// not cloned keep pointing to the same entities. Mapping a component stops at its first
+    /// reference to an entity that doesn't exist anymore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entity` does not exist.
+    pub fn clone_entity_with(&mut self, entity: Entity, options: &CloneEntityOptions) -> Entity {
+        let target = self.spawn_empty().id();
+        self.clone_entity_into(entity, target, options);
+        target
+    }
+
+    /// Clones `source` into the existing `target` entity, see [`World::clone_entity_with`].
+    pub(crate) fn clone_entity_into(
+        &mut self,
+        source: Entity,
+        target: Entity,
+        options: &CloneEntityOptions,
+    ) {
+        assert!(
+            self.get_entity(source).is_some(),
+            "Cannot clone entity {source:?} because it does not exist"
+        );
+
+        let mut entity_map = EntityMap::default();
+        entity_map.insert(source, target);
+        let mut sources = vec![source];
+        let mut related = Vec::new();
+        let mut index = 0;
+        while index < sources.len() {
+            for push_related in &options.related {
+                push_related(self, sources[index], &mut related);
+            }
+            for entity in related.drain(..) {
+                if self.get_entity(entity).is_none() {
+                    continue;
+                }
+                if let Entry::Vacant(entry) = entity_map.entry(entity) {
+                    entry.insert(self.spawn_empty().id());
+                    sources.push(entity);
+                }
+            }
+            index += 1;
+        }
+
+        #[cfg(feature = "bevy_reflect")]
+        let registry = self.get_resource::<AppTypeRegistry>().cloned();
+        #[cfg(feature = "bevy_reflect")]
+        let registry = registry.as_ref().map(|registry| registry.read());
+
+        let mut copied = Vec::new();
+        for source in sources {
+            let target = entity_map.get(source).unwrap();
+            let components: Vec<ComponentId> =
+                self.entity(source).archetype().components().collect();
+            for id in components {
+                let info = self.components().get_info(id).unwrap();
+                let type_id = info.type_id();
+                if matches!(type_id, Some(type_id) if options.skip.contains(&type_id)) {
+                    continue;
+                }
+                if let Some(clone) = info.clone_fn() {
+                    clone(self, source, target, &entity_map);
+                    copied.push((target, type_id));
+                    continue;
+                }
+                #[cfg(feature = "bevy_reflect")]
+                if let Some(reflect_component) =
+                    registry
+                        .as_ref()
+                        .zip(type_id)
+                        .and_then(|(registry, type_id)| {
+                            registry.get_type_data::<ReflectComponent>(type_id)
+                        })
+                {
+                    let value = reflect_component
+                        .reflect(self, source)
+                        .unwrap()
+                        .clone_value();
+                    reflect_component.insert(self, target, &*value);
+                    copied.push((target, type_id));
+                }
+            }
+        }
+
+        #[cfg(feature = "bevy_reflect")]
+        if let Some(registry) = registry {
+            let to_map: Vec<_> = copied
+                .into_iter()
+                .filter_map(|(target, type_id)| {
+                    registry
+                        .get_type_data::<ReflectMapEntities>(type_id?)
+                        .map(|map_entities| (target, map_entities))
+                })
+                .collect();
+            if !to_map.is_empty() {
+                // The entities that were not cloned are mapped to themselves.
+                for entity in self.iter_entities() {
+                    if let Entry::Vacant(entry) = entity_map.entry(entity) {
+                        entry.insert(entity);
+                    }
+                }
+            }
+            for (target, map_entities) in to_map {
+                // Only references to despawned entities fail to be mapped.
+                map_entities
+                    .map_specific_entities(self, &entity_map, &[target])
+                    .ok();
+            }
+        }
+        #[cfg(not(feature = "bevy_reflect"))]
+        drop(copied);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CloneEntityOptions;
+    use crate::{
+        self as bevy_ecs,
+        entity::{Entity, EntityMap, MapEntities, MapEntitiesError},
+        prelude::*,
+        reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
+        system::CommandQueue,
+    };
+    use bevy_reflect::Reflect;
+    use std::ops::Deref;
+
+    #[derive(Component, Clone, Debug, PartialEq)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Name(String);
+
+    #[derive(Component)]
+    struct NotCloneable;
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, MapEntities)]
+    struct Links(Vec<Entity>);
+
+    impl MapEntities for Links {
+        fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+            for entity in &mut self.0 {
+                *entity = entity_map.get(*entity)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Deref for Links {
+        type Target = [Entity];
+
+        fn deref(&self) -> &[Entity] {
+            &self.0
+        }
+    }
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component, MapEntities)]
+    struct Target(Entity);
+
+    impl FromWorld for Target {
+        fn from_world(_world: &mut World) -> Self {
+            Target(Entity::from_raw(u32::MAX))
+        }
+    }
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+            self.0 = entity_map.get(self.0)?;
+            Ok(())
+        }
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register_component_clone::<Health>();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Name>();
+            registry.register::<Links>();
+            registry.register::<Target>();
+        }
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn clone_entity_copies_registered_components() {
+        let mut world = world();
+        let entity = world
+            .spawn((Health(10), Name("orc".to_string()), NotCloneable))
+            .id();
+
+        let clone = world.clone_entity(entity);
+        assert_ne!(clone, entity);
+        assert_eq!(world.get::<Health>(clone), Some(&Health(10)));
+        assert_eq!(world.get::<Name>(clone), Some(&Name("orc".to_string())));
+        assert!(world.get::<NotCloneable>(clone).is_none());
+
+        let options = CloneEntityOptions::default().skip::<Health>();
+        let clone = world.clone_entity_with(entity, &options);
+        assert!(world.get::<Health>(clone).is_none());
+        assert!(world.get::<Name>(clone).is_some());
+
+        let mut queue = CommandQueue::default();
+        let clone = Commands::new(&mut queue, &world)
+            .entity(entity)
+            .clone_entity()
+            .id();
+        queue.apply(&mut world);
+        assert_eq!(world.get::<Health>(clone), Some(&Health(10)));
+    }
+
+    #[test]
+    fn clone_entity_recursively_maps_entities() {
+        let mut world = world();
+        let outside = world.spawn_empty().id();
+        let leaf = world.spawn(Name("leaf".to_string())).id();
+        let middle = world.spawn((Links(vec![leaf]), Target(outside))).id();
+        let root = world.spawn(Links(vec![middle])).id();
+        world.entity_mut(leaf).insert(Target(root));
+
+        let options = CloneEntityOptions::default().recursive::<Links>();
+        let root_clone = world.clone_entity_with(root, &options);
+        let middle_clone = world.get::<Links>(root_clone).unwrap().0[0];
+        let leaf_clone = world.get::<Links>(middle_clone).unwrap().0[0];
+        assert!(![root, middle, leaf].contains(&middle_clone));
+        assert!(![root, middle, leaf].contains(&leaf_clone));
+        assert_eq!(
+            world.get::<Name>(leaf_clone),
+            Some(&Name("leaf".to_string()))
+        );
+        assert_eq!(world.get::<Target>(leaf_clone), Some(&Target(root_clone)));
+        // The middle entity refers to an entity that was not cloned, which its copy keeps.
+        assert_eq!(world.get::<Target>(middle_clone), Some(&Target(outside)));
+        assert_eq!(world.get::<Target>(middle), Some(&Target(outside)));
+
+        let middle_only = world.clone_entity(middle);
+        assert_eq!(world.get::<Links>(middle_only), Some(&Links(vec![leaf])));
+        assert_eq!(world.get::<Target>(middle_only), Some(&Target(outside)));
+    }
+
+    #[derive(Component, Debug, PartialEq)]
+    struct Owner(Entity);
+
+    #[test]
+    fn clone_functions_see_the_cloned_entities() {
+        let mut world = world();
+        world.register_component_clone_with::<Owner>(|world, source, target, entity_map| {
+            let owner = world.get::<Owner>(source).unwrap().0;
+            // Only keep owners that are cloned too.
+            if let Ok(owner) = entity_map.get(owner) {
+                world.entity_mut(target).insert(Owner(owner));
+            }
+        });
+        let owner = world.spawn_empty().id();
+        let owned = world.spawn(Owner(owner)).id();
+        let root = world.spawn(Links(vec![owner, owned])).id();
+
+        let options = CloneEntityOptions::default().recursive::<Links>();
+        let root_clone = world.clone_entity_with(root, &options);
+        let clones = world.get::<Links>(root_clone).unwrap().0.clone();
+        assert_eq!(world.get::<Owner>(clones[1]), Some(&Owner(clones[0])));
+
+        let owned_clone = world.clone_entity(owned);
+        assert!(world.get::<Owner>(owned_clone).is_none());
+    }
+}
@@ -2,15 +2,18 @@ use crate::{
     archetype::{Archetype, ArchetypeId, Archetypes},
     bundle::{Bundle, BundleInfo},
     change_detection::{MutUntyped, Ticks},
-    component::{Component, ComponentId, ComponentTicks, Components, StorageType},
+    component::{Component, ComponentHook, ComponentId, ComponentTicks, Components, StorageType},
     entity::{Entities, Entity, EntityLocation},
     storage::{SparseSet, Storages},
+    system::CommandQueue,
     world::{Mut, World},
 };
 use bevy_ptr::{OwningPtr, Ptr, UnsafeCellDeref};
 use bevy_utils::tracing::debug;
 use std::{any::TypeId, cell::UnsafeCell};
 
+use super::deferred_world::{collect_hooks, run_hooks};
+
 /// A read-only reference to a particular [`Entity`] and all of its components
 #[derive(Copy, Clone)]
 pub struct EntityRef<'w> {
@@ -256,6 +259,21 @@ impl<'w> EntityMut<'w> {
             .world
             .bundles
             .init_info::<T>(&mut self.world.components, &mut self.world.storages);
+        let old_archetype = &self.world.archetypes[self.location.archetype_id];
+        let components = bundle_info.components();
+        let on_add = collect_hooks(
+            &self.world.components,
+            components
+                .iter()
+                .copied()
+                .filter(|&id| !old_archetype.contains(id)),
+            |hooks| hooks.on_add,
+        );
+        let on_insert = collect_hooks(
+            &self.world.components,
+            components.iter().copied(),
+            |hooks| hooks.on_insert,
+        );
         let mut bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
@@ -268,10 +286,72 @@ impl<'w> EntityMut<'w> {
         unsafe {
             self.location = bundle_inserter.insert(self.entity, self.location.index, bundle);
         }
+        self.run_insert_hooks(&on_add, &on_insert);
 
         self
     }
 
+    /// Runs the given `on_add` hooks, then the `on_insert` hooks, and applies the commands
+    /// they queued.
+    pub(crate) fn run_insert_hooks(
+        &mut self,
+        on_add: &[(ComponentHook, ComponentId)],
+        on_insert: &[(ComponentHook, ComponentId)],
+    ) {
+        if on_add.is_empty() && on_insert.is_empty() {
+            return;
+        }
+        let mut queue = CommandQueue::default();
+        run_hooks(self.world, &mut queue, self.entity, on_add);
+        run_hooks(self.world, &mut queue, self.entity, on_insert);
+        self.apply_hook_commands(queue);
+    }
+
+    /// Runs the `on_remove` hooks of the components of `T` that are about to be removed from
+    /// this entity, returning the commands they queued if any hook ran.
+    fn run_remove_hooks<T: Bundle>(&mut self, intersection: bool) -> Option<CommandQueue> {
+        let world = &mut *self.world;
+        let bundle_info = world
+            .bundles
+            .init_info::<T>(&mut world.components, &mut world.storages);
+        let archetype = &world.archetypes[self.location.archetype_id];
+        let components = bundle_info.components();
+        // Unless removing an intersection, nothing is removed if a component is missing.
+        if !intersection && !components.iter().all(|&id| archetype.contains(id)) {
+            return None;
+        }
+        let on_remove = collect_hooks(
+            &world.components,
+            components
+                .iter()
+                .copied()
+                .filter(|&id| archetype.contains(id)),
+            |hooks| hooks.on_remove,
+        );
+        if on_remove.is_empty() {
+            return None;
+        }
+        let mut queue = CommandQueue::default();
+        run_hooks(world, &mut queue, self.entity, &on_remove);
+        Some(queue)
+    }
+
+    /// Applies commands queued by component hooks and updates the location of this entity,
+    /// which these commands may have changed.
+    fn apply_hook_commands(&mut self, mut queue: CommandQueue) {
+        queue.apply(self.world);
+        // If the commands despawned this entity, the invalid location makes any further access
+        // through this `EntityMut` panic instead of reading another entity's data.
+        self.location = self
+            .world
+            .entities
+            .get(self.entity)
+            .unwrap_or(EntityLocation {
+                archetype_id: ArchetypeId::INVALID,
+                index: usize::MAX,
+            });
+    }
+
     #[deprecated(
         since = "0.9.0",
         note = "Use `remove` instead, which now accepts bundles, components, and tuples of bundles and components."
@@ -285,6 +365,15 @@ impl<'w> EntityMut<'w> {
     ///
     /// Returns `None` if the entity does not contain the bundle.
     pub fn remove<T: Bundle>(&mut self) -> Option<T> {
+        let hook_commands = self.run_remove_hooks::<T>(false);
+        let result = self.take::<T>();
+        if let Some(queue) = hook_commands {
+            self.apply_hook_commands(queue);
+        }
+        result
+    }
+
+    fn take<T: Bundle>(&mut self) -> Option<T> {
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -418,6 +507,14 @@ impl<'w> EntityMut<'w> {
     // TODO: move to BundleInfo
     /// Remove any components in the bundle that the entity has.
     pub fn remove_intersection<T: Bundle>(&mut self) {
+        let hook_commands = self.run_remove_hooks::<T>(true);
+        self.remove_intersection_inner::<T>();
+        if let Some(queue) = hook_commands {
+            self.apply_hook_commands(queue);
+        }
+    }
+
+    fn remove_intersection_inner<T: Bundle>(&mut self) {
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -482,6 +579,18 @@ impl<'w> EntityMut<'w> {
 
     pub fn despawn(self) {
         debug!("Despawning entity {:?}", self.entity);
+        let on_remove = collect_hooks(
+            &self.world.components,
+            self.archetype().components(),
+            |hooks| hooks.on_remove,
+        );
+        let mut hook_commands = None;
+        if !on_remove.is_empty() {
+            let mut queue = CommandQueue::default();
+            run_hooks(self.world, &mut queue, self.entity, &on_remove);
+            hook_commands = Some(queue);
+        }
+
         let world = self.world;
         world.flush();
         let location = world
@@ -519,6 +628,10 @@ impl<'w> EntityMut<'w> {
             world.archetypes[moved_location.archetype_id]
                 .set_entity_table_row(moved_location.index, table_row);
         }
+
+        if let Some(mut queue) = hook_commands {
+            queue.apply(world);
+        }
     }
 
     #[inline]
@@ -959,7 +1072,7 @@ mod tests {
         let b = vec![1];
         super::sorted_remove(&mut a, &b);
 
-        assert_eq!(a, vec![]);
+        assert!(a.is_empty());
 
         let mut a = vec![1];
         let b = vec![2];
@@ -1034,4 +1147,128 @@ mod tests {
         assert!(entity.get_by_id(invalid_component_id).is_none());
         assert!(entity.get_mut_by_id(invalid_component_id).is_none());
     }
+
+    #[derive(Resource, Default)]
+    struct HookLog(Vec<&'static str>);
+
+    #[derive(Component)]
+    struct Hooked(u32);
+
+    #[derive(Component)]
+    struct Marker;
+
+    #[derive(Component)]
+    struct Missing;
+
+    fn hooked_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<HookLog>();
+        world
+            .register_component_hooks::<Hooked>()
+            .on_add(|mut world, _, _| world.resource_mut::<HookLog>().0.push("add"))
+            .on_insert(|mut world, _, _| world.resource_mut::<HookLog>().0.push("insert"))
+            .on_remove(|mut world, _, _| world.resource_mut::<HookLog>().0.push("remove"));
+        world
+    }
+
+    fn take_log(world: &mut World) -> Vec<&'static str> {
+        std::mem::take(&mut world.resource_mut::<HookLog>().0)
+    }
+
+    #[test]
+    fn component_hooks_run_on_archetype_changes() {
+        let mut world = hooked_world();
+
+        let entity = world.spawn(Hooked(0)).id();
+        assert_eq!(take_log(&mut world), ["add", "insert"]);
+
+        world.entity_mut(entity).insert(Hooked(1));
+        assert_eq!(take_log(&mut world), ["insert"]);
+
+        world.entity_mut(entity).insert(Marker);
+        assert!(take_log(&mut world).is_empty());
+
+        // Not removed because `entity` doesn't have the whole bundle.
+        world.entity_mut(entity).remove::<(Hooked, Missing)>();
+        assert!(take_log(&mut world).is_empty());
+
+        world.entity_mut(entity).remove::<Hooked>();
+        assert_eq!(take_log(&mut world), ["remove"]);
+
+        world.entity_mut(entity).insert(Hooked(2));
+        world
+            .entity_mut(entity)
+            .remove_intersection::<(Hooked, Missing)>();
+        assert_eq!(take_log(&mut world), ["add", "insert", "remove"]);
+
+        world.entity_mut(entity).insert(Hooked(3));
+        world.despawn(entity);
+        assert_eq!(take_log(&mut world), ["add", "insert", "remove"]);
+    }
+
+    #[test]
+    fn component_hooks_run_for_batches() {
+        let mut world = hooked_world();
+
+        let entities: Vec<_> = world.spawn_batch([Hooked(0), Hooked(1)]).collect();
+        assert_eq!(take_log(&mut world), ["add", "insert", "add", "insert"]);
+
+        world
+            .insert_or_spawn_batch(entities.iter().map(|&entity| (entity, Hooked(2))))
+            .unwrap();
+        assert_eq!(take_log(&mut world), ["insert", "insert"]);
+        assert_eq!(world.get::<Hooked>(entities[0]).unwrap().0, 2);
+    }
+
+    #[test]
+    fn on_remove_hook_reads_removed_value() {
+        #[derive(Resource, Default)]
+        struct Removed(Vec<u32>);
+
+        let mut world = World::new();
+        world.init_resource::<Removed>();
+        world
+            .register_component_hooks::<Hooked>()
+            .on_remove(|mut world, entity, _| {
+                let value = world.get::<Hooked>(entity).unwrap().0;
+                world.resource_mut::<Removed>().0.push(value);
+            });
+
+        let entity = world.spawn(Hooked(1)).id();
+        assert_eq!(world.entity_mut(entity).remove::<Hooked>().unwrap().0, 1);
+        world.entity_mut(entity).insert(Hooked(2));
+        world.despawn(entity);
+        assert_eq!(world.resource::<Removed>().0, [1, 2]);
+    }
+
+    #[test]
+    fn component_hook_commands_are_applied() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<Hooked>()
+            .on_add(|mut world, entity, _| {
+                world.commands().entity(entity).insert(Marker);
+            })
+            .on_remove(|mut world, entity, _| {
+                world.commands().entity(entity).remove::<Marker>();
+            });
+
+        let mut entity = world.spawn(Hooked(0));
+        assert!(entity.contains::<Marker>());
+        entity.remove::<Hooked>();
+        assert!(!entity.contains::<Marker>());
+    }
+
+    #[test]
+    fn component_hook_despawning_entity() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<Hooked>()
+            .on_insert(|mut world, entity, _| {
+                world.commands().entity(entity).despawn();
+            });
+
+        let entity = world.spawn(Hooked(0)).id();
+        assert!(world.get_entity(entity).is_none());
+    }
 }
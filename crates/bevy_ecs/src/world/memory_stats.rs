@@ -0,0 +1,394 @@
+use crate::{
+    component::{ComponentId, ComponentTicks, Components},
+    entity::Entity,
+    storage::{Column, Table},
+    world::World,
+};
+use serde::Serialize;
+use std::{cell::UnsafeCell, mem::size_of};
+
+/// A summary of how the entities, components and resources of a [`World`] are stored,
+/// created by [`World::memory_stats`].
+///
+/// Byte counts include the change ticks stored next to each component. The stats implement
+/// [`Serialize`], so they can be exported to JSON or any other `serde` format.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// let mut world = World::new();
+/// world.spawn(Enemy);
+///
+/// let stats = world.memory_stats();
+/// for archetype in stats.fragmented_archetypes(8) {
+///     println!("{} entities with {:?}", archetype.entity_count, archetype.components);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorldMemoryStats {
+    /// The number of entities in the world.
+    pub entity_count: usize,
+    pub archetypes: Vec<ArchetypeStats>,
+    pub tables: Vec<TableStats>,
+    pub sparse_sets: Vec<SparseSetStats>,
+    pub resources: Vec<ResourceStats>,
+}
+
+impl WorldMemoryStats {
+    /// Returns the archetypes with at least one entity but fewer than `min_entities`.
+    ///
+    /// Many of them usually mean that entities are spread over too many combinations of
+    /// components, for example by marker components.
+    pub fn fragmented_archetypes(
+        &self,
+        min_entities: usize,
+    ) -> impl Iterator<Item = &ArchetypeStats> {
+        self.archetypes.iter().filter(move |archetype| {
+            archetype.entity_count > 0 && archetype.entity_count < min_entities
+        })
+    }
+
+    /// Returns the bytes used by components and resources.
+    pub fn bytes(&self) -> usize {
+        self.tables.iter().map(|table| table.bytes).sum::<usize>()
+            + self.sparse_sets.iter().map(|set| set.bytes).sum::<usize>()
+            + self
+                .resources
+                .iter()
+                .map(|resource| resource.bytes)
+                .sum::<usize>()
+    }
+
+    /// Returns the bytes allocated for components and resources, including unused capacity.
+    pub fn allocated_bytes(&self) -> usize {
+        self.tables
+            .iter()
+            .map(|table| table.allocated_bytes)
+            .sum::<usize>()
+            + self
+                .sparse_sets
+                .iter()
+                .map(|set| set.allocated_bytes)
+                .sum::<usize>()
+            + self
+                .resources
+                .iter()
+                .map(|resource| resource.bytes)
+                .sum::<usize>()
+    }
+}
+
+/// The byte totals of the components and resources of a [`World`], created by
+/// [`World::memory_totals`].
+///
+/// These are the same sums as [`WorldMemoryStats::bytes`] and
+/// [`WorldMemoryStats::allocated_bytes`], split between components and resources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WorldMemoryTotals {
+    /// The bytes used by the components in tables and sparse sets.
+    pub component_bytes: usize,
+    /// The bytes allocated for components in tables and sparse sets, including unused capacity.
+    pub allocated_component_bytes: usize,
+    /// The bytes used by resources, not counting their heap allocations.
+    ///
+    /// See [`ResourceStats::bytes`].
+    pub resource_bytes: usize,
+}
+
+/// The stats of an [`Archetype`](crate::archetype::Archetype).
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchetypeStats {
+    /// The index of the [`ArchetypeId`](crate::archetype::ArchetypeId).
+    pub id: usize,
+    pub entity_count: usize,
+    /// The index of the [`TableId`](crate::storage::TableId) of the table components.
+    pub table_id: usize,
+    /// The names of the components, table components first.
+    pub components: Vec<String>,
+}
+
+/// The stats of a [`Table`](crate::storage::Table).
+#[derive(Debug, Clone, Serialize)]
+pub struct TableStats {
+    /// The index of the [`TableId`](crate::storage::TableId).
+    pub id: usize,
+    pub entity_count: usize,
+    pub entity_capacity: usize,
+    /// The names of the components stored in the table.
+    pub components: Vec<String>,
+    pub bytes: usize,
+    pub allocated_bytes: usize,
+}
+
+/// The stats of a [`ComponentSparseSet`](crate::storage::ComponentSparseSet).
+#[derive(Debug, Clone, Serialize)]
+pub struct SparseSetStats {
+    /// The name of the stored component.
+    pub component: String,
+    pub entity_count: usize,
+    pub capacity: usize,
+    pub bytes: usize,
+    pub allocated_bytes: usize,
+}
+
+/// The stats of a resource present in the world.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceStats {
+    pub name: String,
+    /// The `size_of` the resource type.
+    ///
+    /// Heap allocations owned by the resource, such as the contents of a `Vec`, are not
+    /// counted.
+    pub bytes: usize,
+}
+
+fn component_name(components: &Components, id: ComponentId) -> String {
+    components
+        .get_info(id)
+        .map_or_else(|| format!("{id:?}"), |info| info.name().to_string())
+}
+
+/// Returns the bytes used by `count` components of the column, with their change ticks.
+fn column_bytes(column: &Column, count: usize) -> usize {
+    count * (column.item_layout().size() + size_of::<UnsafeCell<ComponentTicks>>())
+}
+
+fn table_bytes(table: &Table) -> usize {
+    table.entity_count() * size_of::<Entity>()
+        + table
+            .iter()
+            .map(|column| column_bytes(column, column.len()))
+            .sum::<usize>()
+}
+
+fn table_allocated_bytes(table: &Table) -> usize {
+    table.entity_capacity() * size_of::<Entity>()
+        + table
+            .iter()
+            .map(|column| column_bytes(column, column.capacity()))
+            .sum::<usize>()
+}
+
+/// Returns the bytes used by each component of a sparse set, with its change ticks and entity.
+fn sparse_set_item_bytes(components: &Components, id: ComponentId) -> usize {
+    components
+        .get_info(id)
+        .map_or(0, |info| info.layout().size())
+        + size_of::<UnsafeCell<ComponentTicks>>()
+        + size_of::<Entity>()
+}
+
+fn resource_bytes(components: &Components, id: ComponentId) -> usize {
+    components
+        .get_info(id)
+        .map_or(0, |info| info.layout().size())
+}
+
+impl World {
+    /// Collects a [`WorldMemoryStats`] summary of the archetypes, tables, sparse sets and
+    /// resources of the world.
+    ///
+    /// This walks over all of the storages and allocates the names of the components, so it
+    /// is meant for debugging and diagnostics rather than for every frame of a game.
+    pub fn memory_stats(&self) -> WorldMemoryStats {
+        let components = self.components();
+        let storages = self.storages();
+
+        let archetypes = self
+            .archetypes()
+            .iter()
+            .map(|archetype| ArchetypeStats {
+                id: archetype.id().index(),
+                entity_count: archetype.len(),
+                table_id: archetype.table_id().index(),
+                components: archetype
+                    .components()
+                    .map(|id| component_name(components, id))
+                    .collect(),
+            })
+            .collect();
+
+        let tables = storages
+            .tables
+            .iter()
+            .enumerate()
+            .map(|(id, table)| TableStats {
+                id,
+                entity_count: table.entity_count(),
+                entity_capacity: table.entity_capacity(),
+                components: table
+                    .component_ids()
+                    .map(|id| component_name(components, id))
+                    .collect(),
+                bytes: table_bytes(table),
+                allocated_bytes: table_allocated_bytes(table),
+            })
+            .collect();
+
+        let sparse_sets = storages
+            .sparse_sets
+            .iter()
+            .map(|(id, set)| {
+                let item_bytes = sparse_set_item_bytes(components, id);
+                SparseSetStats {
+                    component: component_name(components, id),
+                    entity_count: set.len(),
+                    capacity: set.capacity(),
+                    bytes: set.len() * item_bytes,
+                    allocated_bytes: set.capacity() * item_bytes,
+                }
+            })
+            .collect();
+
+        let resources = storages
+            .resources
+            .iter()
+            .filter(|(_, data)| data.is_present())
+            .map(|(id, _)| ResourceStats {
+                name: component_name(components, id),
+                bytes: resource_bytes(components, id),
+            })
+            .collect();
+
+        WorldMemoryStats {
+            entity_count: self.entities().len() as usize,
+            archetypes,
+            tables,
+            sparse_sets,
+            resources,
+        }
+    }
+
+    /// Sums up the bytes of the components and resources of the world into a
+    /// [`WorldMemoryTotals`].
+    ///
+    /// Unlike [`World::memory_stats`], this doesn't allocate, so it can be used every frame.
+    pub fn memory_totals(&self) -> WorldMemoryTotals {
+        let components = self.components();
+        let storages = self.storages();
+        let mut totals = WorldMemoryTotals::default();
+        for table in storages.tables.iter() {
+            totals.component_bytes += table_bytes(table);
+            totals.allocated_component_bytes += table_allocated_bytes(table);
+        }
+        for (id, set) in storages.sparse_sets.iter() {
+            let item_bytes = sparse_set_item_bytes(components, id);
+            totals.component_bytes += set.len() * item_bytes;
+            totals.allocated_component_bytes += set.capacity() * item_bytes;
+        }
+        totals.resource_bytes = storages
+            .resources
+            .iter()
+            .filter(|(_, data)| data.is_present())
+            .map(|(id, _)| resource_bytes(components, id))
+            .sum();
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{self as bevy_ecs, component::Component, system::Resource, world::World};
+
+    #[derive(Component)]
+    struct Position(#[allow(dead_code)] [f32; 3]);
+
+    #[derive(Component)]
+    #[component(storage = "SparseSet")]
+    struct Selected(#[allow(dead_code)] u64);
+
+    #[derive(Component)]
+    struct Marker<const N: usize>;
+
+    #[derive(Resource)]
+    struct Settings(#[allow(dead_code)] u32);
+
+    #[test]
+    fn memory_stats() {
+        let mut world = World::new();
+        world.insert_resource(Settings(0));
+        for _ in 0..10 {
+            world.spawn(Position([0.0; 3]));
+        }
+        world.spawn((Position([0.0; 3]), Marker::<0>));
+        world.spawn((Position([0.0; 3]), Marker::<1>, Selected(0)));
+
+        let stats = world.memory_stats();
+        assert_eq!(stats.entity_count, 12);
+
+        let position = std::any::type_name::<Position>();
+        let archetype = stats
+            .archetypes
+            .iter()
+            .find(|archetype| archetype.components == [position])
+            .unwrap();
+        assert_eq!(archetype.entity_count, 10);
+        let table = &stats.tables[archetype.table_id];
+        assert_eq!(table.entity_count, 10);
+        assert_eq!(table.components, [position]);
+        assert!(table.bytes >= 10 * std::mem::size_of::<Position>());
+        assert!(table.allocated_bytes >= table.bytes);
+
+        let fragmented: Vec<_> = stats.fragmented_archetypes(2).collect();
+        assert_eq!(fragmented.len(), 2);
+        assert!(fragmented
+            .iter()
+            .all(|archetype| archetype.entity_count == 1));
+        assert!(stats
+            .fragmented_archetypes(11)
+            .any(|a| a.id == archetype.id));
+
+        assert_eq!(stats.sparse_sets.len(), 1);
+        assert_eq!(
+            stats.sparse_sets[0].component,
+            std::any::type_name::<Selected>()
+        );
+        assert_eq!(stats.sparse_sets[0].entity_count, 1);
+
+        assert_eq!(stats.resources.len(), 1);
+        assert_eq!(stats.resources[0].bytes, std::mem::size_of::<Settings>());
+        assert!(stats.bytes() > 0);
+        assert!(stats.allocated_bytes() >= stats.bytes());
+
+        let totals = world.memory_totals();
+        assert_eq!(totals.resource_bytes, std::mem::size_of::<Settings>());
+        assert_eq!(
+            totals.component_bytes + totals.resource_bytes,
+            stats.bytes()
+        );
+        assert_eq!(
+            totals.allocated_component_bytes + totals.resource_bytes,
+            stats.allocated_bytes()
+        );
+    }
+
+    #[test]
+    fn export_json() {
+        let mut world = World::new();
+        world.insert_resource(Settings(0));
+        world.spawn((Position([0.0; 3]), Selected(0)));
+
+        let json = serde_json::to_value(world.memory_stats()).unwrap();
+        assert_eq!(json["entity_count"], 1);
+        let position = std::any::type_name::<Position>();
+        assert!(json["tables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|table| table["components"] == serde_json::json!([position])
+                && table["entity_count"] == 1));
+        assert_eq!(
+            json["sparse_sets"][0]["component"],
+            std::any::type_name::<Selected>()
+        );
+        assert_eq!(
+            json["resources"][0]["bytes"],
+            std::mem::size_of::<Settings>()
+        );
+
+        let json = serde_json::to_value(world.memory_totals()).unwrap();
+        assert_eq!(json["resource_bytes"], std::mem::size_of::<Settings>());
+    }
+}
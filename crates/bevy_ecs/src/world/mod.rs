@@ -1,9 +1,17 @@
+mod deferred_world;
+mod entity_clone;
 mod entity_ref;
+mod memory_stats;
+mod snapshot;
 mod spawn_batch;
 mod world_cell;
 
 pub use crate::change_detection::Mut;
+pub use deferred_world::DeferredWorld;
+pub use entity_clone::*;
 pub use entity_ref::*;
+pub use memory_stats::*;
+pub use snapshot::*;
 pub use spawn_batch::*;
 pub use world_cell::*;
 
@@ -12,7 +20,8 @@ use crate::{
     bundle::{Bundle, BundleInserter, BundleSpawner, Bundles},
     change_detection::{MutUntyped, Ticks},
     component::{
-        Component, ComponentDescriptor, ComponentId, ComponentInfo, ComponentTicks, Components,
+        Component, ComponentCloneFn, ComponentDescriptor, ComponentHooks, ComponentId,
+        ComponentInfo, ComponentTicks, Components,
     },
     entity::{AllocAtWithoutReplacement, Entities, Entity},
     query::{QueryState, ReadOnlyWorldQuery, WorldQuery},
@@ -21,6 +30,7 @@ use crate::{
 };
 use bevy_ptr::{OwningPtr, Ptr, UnsafeCellDeref};
 use bevy_utils::tracing::warn;
+use deferred_world::collect_hooks;
 use std::{
     any::TypeId,
     cell::UnsafeCell,
@@ -172,6 +182,56 @@ impl World {
             .init_component_with_descriptor(&mut self.storages, descriptor)
     }
 
+    /// Returns a mutable reference to the [`ComponentHooks`] of the [`Component`] type `T`,
+    /// initializing the component if needed.
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct PhysicsBody;
+    ///
+    /// let mut world = World::new();
+    /// world
+    ///     .register_component_hooks::<PhysicsBody>()
+    ///     .on_add(|world, entity, _| println!("{entity:?} joined the simulation"))
+    ///     .on_remove(|world, entity, _| println!("{entity:?} left the simulation"));
+    /// ```
+    pub fn register_component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        let id = self.init_component::<T>();
+        self.components.get_hooks_mut(id).unwrap()
+    }
+
+    /// Returns a mutable reference to the [`ComponentHooks`] of the component with the given `id`.
+    ///
+    /// Returns [`None`] if no component was initialized with this `id`.
+    pub fn register_component_hooks_by_id(
+        &mut self,
+        id: ComponentId,
+    ) -> Option<&mut ComponentHooks> {
+        self.components.get_hooks_mut(id)
+    }
+
+    /// Lets [`World::clone_entity`] copy the component `T` with [`Clone`], rather than through
+    /// reflection.
+    pub fn register_component_clone<T: Component + Clone>(&mut self) {
+        self.register_component_clone_with::<T>(|world, source, target, _| {
+            let component = world.get::<T>(source).unwrap().clone();
+            world.entity_mut(target).insert(component);
+        });
+    }
+
+    /// Lets [`World::clone_entity`] copy the component `T` with the function `clone`, which
+    /// replaces any function registered before.
+    ///
+    /// This is meant for components that have to stay consistent with other entities, like the
+    /// two sides of a relation, which can look up the copies of the cloned entities to decide
+    /// what to insert.
+    pub fn register_component_clone_with<T: Component>(&mut self, clone: ComponentCloneFn) {
+        let id = self.init_component::<T>();
+        self.components.set_clone_fn(id, clone);
+    }
+
     /// Returns the [`ComponentId`] of the given [`Component`] type `T`.
     ///
     /// The returned `ComponentId` is specific to the `World` instance
@@ -451,10 +511,17 @@ impl World {
     pub fn spawn<B: Bundle>(&mut self, bundle: B) -> EntityMut {
         self.flush();
         let entity = self.entities.alloc();
-        let entity_location = {
+        let (entity_location, on_add, on_insert) = {
             let bundle_info = self
                 .bundles
                 .init_info::<B>(&mut self.components, &mut self.storages);
+            let components = bundle_info.components();
+            let on_add = collect_hooks(&self.components, components.iter().copied(), |hooks| {
+                hooks.on_add
+            });
+            let on_insert = collect_hooks(&self.components, components.iter().copied(), |hooks| {
+                hooks.on_insert
+            });
             let mut spawner = bundle_info.get_bundle_spawner(
                 &mut self.entities,
                 &mut self.archetypes,
@@ -464,11 +531,14 @@ impl World {
             );
 
             // SAFETY: bundle's type matches `bundle_info`, entity is allocated but non-existent
-            unsafe { spawner.spawn_non_existent(entity, bundle) }
+            let location = unsafe { spawner.spawn_non_existent(entity, bundle) };
+            (location, on_add, on_insert)
         };
 
         // SAFETY: entity and location are valid, as they were just created above
-        unsafe { EntityMut::new(self, entity, entity_location) }
+        let mut entity_mut = unsafe { EntityMut::new(self, entity, entity_location) };
+        entity_mut.run_insert_hooks(&on_add, &on_insert);
+        entity_mut
     }
 
     /// # Safety
@@ -1058,6 +1128,25 @@ impl World {
         let bundle_info = self
             .bundles
             .init_info::<B>(&mut self.components, &mut self.storages);
+
+        if bundle_info.has_insert_hooks(&self.components) {
+            // Hooks need access to the whole world, so entities are handled one at a time.
+            let mut invalid_entities = Vec::new();
+            for (entity, bundle) in iter {
+                match self.get_or_spawn(entity) {
+                    Some(mut entity_mut) => {
+                        entity_mut.insert(bundle);
+                    }
+                    None => invalid_entities.push(entity),
+                }
+            }
+            return if invalid_entities.is_empty() {
+                Ok(())
+            } else {
+                Err(invalid_entities)
+            };
+        }
+
         enum SpawnOrInsert<'a, 'b> {
             Spawn(BundleSpawner<'a, 'b>),
             Insert(BundleInserter<'a, 'b>, ArchetypeId),
@@ -0,0 +1,623 @@
+use crate::{
+    self as bevy_ecs,
+    change_detection::DetectChanges,
+    component::{Component, ComponentId},
+    entity::Entity,
+    system::Resource,
+    world::World,
+};
+use bevy_utils::tracing::warn;
+use std::{any::Any, collections::VecDeque, sync::Arc};
+
+#[cfg(feature = "bevy_reflect")]
+use crate::reflect::{ReflectComponent, ReflectResource};
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::{Reflect, TypeRegistration};
+#[cfg(feature = "bevy_reflect")]
+use std::any::TypeId;
+
+type SnapshotData = Box<dyn Any + Send + Sync>;
+
+/// Captures and restores one component type for a [`WorldSnapshot`].
+trait SnapshotComponent: Send + Sync + 'static {
+    fn component_id(&self, world: &World) -> Option<ComponentId>;
+
+    /// Returns the component values, sorted by entity.
+    fn capture(&self, world: &World) -> SnapshotData;
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool);
+}
+
+/// Captures and restores one resource type for a [`WorldSnapshot`].
+trait SnapshotResource: Send + Sync + 'static {
+    fn capture(&self, world: &World) -> SnapshotData;
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool);
+}
+
+/// Chooses which component and resource types are copied by a [`WorldSnapshot`].
+///
+/// Types added with [`with_component`](Self::with_component) and
+/// [`with_resource`](Self::with_resource) are copied with [`Clone`], which is the fastest path.
+/// Types only known through the type registry can be added with
+/// [`with_reflect_component`](Self::with_reflect_component) and
+/// [`with_reflect_resource`](Self::with_reflect_resource).
+///
+/// Entities having at least one of the chosen components are tracked by snapshots: restoring
+/// a snapshot respawns the tracked entities with their original ids, and despawns tracked
+/// entities that were spawned after it was captured. Other entities are left untouched.
+#[derive(Clone, Default)]
+pub struct SnapshotConfig {
+    components: Vec<Arc<dyn SnapshotComponent>>,
+    resources: Vec<Arc<dyn SnapshotResource>>,
+    detect_changes: bool,
+}
+
+impl SnapshotConfig {
+    /// Copies the component `T` with [`Clone`].
+    pub fn with_component<T: Component + Clone>(mut self) -> Self {
+        self.components
+            .push(Arc::new(CloneComponent::<T>(Default::default())));
+        self
+    }
+
+    /// Copies the resource `R` with [`Clone`].
+    pub fn with_resource<R: Resource + Clone>(mut self) -> Self {
+        self.resources
+            .push(Arc::new(CloneResource::<R>(Default::default())));
+        self
+    }
+
+    /// Copies the component described by `registration` with its [`ReflectComponent`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type has no [`ReflectComponent`] type data.
+    #[cfg(feature = "bevy_reflect")]
+    pub fn with_reflect_component(mut self, registration: &TypeRegistration) -> Self {
+        let reflect_component = registration
+            .data::<ReflectComponent>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} is not registered with ReflectComponent",
+                    registration.type_name()
+                )
+            })
+            .clone();
+        self.components.push(Arc::new(ReflectedComponent {
+            type_id: registration.type_id(),
+            reflect_component,
+        }));
+        self
+    }
+
+    /// Copies the resource described by `registration` with its [`ReflectResource`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type has no [`ReflectResource`] type data.
+    #[cfg(feature = "bevy_reflect")]
+    pub fn with_reflect_resource(mut self, registration: &TypeRegistration) -> Self {
+        let reflect_resource = registration
+            .data::<ReflectResource>()
+            .unwrap_or_else(|| {
+                panic!(
+                    "{} is not registered with ReflectResource",
+                    registration.type_name()
+                )
+            })
+            .clone();
+        self.resources
+            .push(Arc::new(ReflectedResource(reflect_resource)));
+        self
+    }
+
+    /// Sets whether restoring a snapshot marks the restored values as changed.
+    ///
+    /// Defaults to `false`, so that systems filtering on [`Changed`](crate::query::Changed)
+    /// don't react to a rollback. Inserting, removing and despawning are always visible to
+    /// change detection.
+    pub fn with_change_detection(mut self, detect_changes: bool) -> Self {
+        self.detect_changes = detect_changes;
+        self
+    }
+}
+
+/// A copy of the state selected by a [`SnapshotConfig`], captured from a [`World`].
+///
+/// Unlike a `DynamicScene` of `bevy_scene`, a snapshot keeps the component values unboxed when
+/// they are copied with [`Clone`], and is restored into the world it was captured from, keeping
+/// the entity ids.
+pub struct WorldSnapshot {
+    config: SnapshotConfig,
+    /// The tracked entities, sorted.
+    entities: Vec<Entity>,
+    components: Vec<SnapshotData>,
+    resources: Vec<SnapshotData>,
+}
+
+impl WorldSnapshot {
+    /// Captures the state of `world` selected by `config`.
+    pub fn capture(world: &World, config: &SnapshotConfig) -> Self {
+        let mut entities = Vec::new();
+        let component_ids: Vec<_> = config
+            .components
+            .iter()
+            .filter_map(|component| component.component_id(world))
+            .collect();
+        for archetype in world.archetypes().iter() {
+            if component_ids.iter().any(|id| archetype.contains(*id)) {
+                entities.extend(archetype.entities().iter().map(|entity| entity.entity()));
+            }
+        }
+        entities.sort_unstable();
+
+        let components = config
+            .components
+            .iter()
+            .map(|component| component.capture(world))
+            .collect();
+        let resources = config
+            .resources
+            .iter()
+            .map(|resource| resource.capture(world))
+            .collect();
+
+        Self {
+            config: config.clone(),
+            entities,
+            components,
+            resources,
+        }
+    }
+
+    /// Returns the tracked entities, sorted by id.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Restores the captured state into `world`.
+    ///
+    /// Tracked entities spawned since the capture are despawned, and despawned ones are
+    /// respawned with the same [`Entity`] id. Components and resources that were added since
+    /// the capture are removed.
+    ///
+    /// `world` should be the world this snapshot was captured from: entity ids are restored as
+    /// is.
+    pub fn restore(&self, world: &mut World) {
+        let mut spawned: Vec<_> = self
+            .config
+            .components
+            .iter()
+            .flat_map(|component| entities_with(world, component.component_id(world)))
+            .filter(|entity| self.entities.binary_search(entity).is_err())
+            .collect();
+        // An entity is listed once per tracked component it has.
+        spawned.sort_unstable();
+        spawned.dedup();
+        for entity in spawned {
+            world.despawn(entity);
+        }
+
+        for &entity in &self.entities {
+            if world.get_or_spawn(entity).is_none() {
+                warn!(
+                    "Failed to restore {:?} from a snapshot, its id is used by a newer entity",
+                    entity
+                );
+            }
+        }
+
+        let detect_changes = self.config.detect_changes;
+        for (component, data) in self.config.components.iter().zip(&self.components) {
+            component.restore(world, data, detect_changes);
+        }
+        for (resource, data) in self.config.resources.iter().zip(&self.resources) {
+            resource.restore(world, data, detect_changes);
+        }
+    }
+}
+
+/// A ring buffer of [`WorldSnapshot`]s, indexed by frame.
+///
+/// Capturing a new snapshot once the buffer is full discards the oldest one.
+///
+/// ```
+/// use bevy_ecs::{prelude::*, world::{SnapshotConfig, WorldSnapshots}};
+///
+/// #[derive(Component, Clone, PartialEq, Debug)]
+/// struct Position(i32);
+///
+/// let mut world = World::new();
+/// let mut snapshots = WorldSnapshots::new(SnapshotConfig::default().with_component::<Position>(), 8);
+///
+/// let entity = world.spawn(Position(0)).id();
+/// snapshots.capture(0, &world);
+///
+/// world.get_mut::<Position>(entity).unwrap().0 = 5;
+/// let projectile = world.spawn(Position(10)).id();
+///
+/// snapshots.restore(0, &mut world);
+/// assert_eq!(world.get::<Position>(entity), Some(&Position(0)));
+/// assert!(world.get_entity(projectile).is_none());
+/// ```
+#[derive(Resource)]
+pub struct WorldSnapshots {
+    config: SnapshotConfig,
+    capacity: usize,
+    snapshots: VecDeque<(u64, WorldSnapshot)>,
+}
+
+impl WorldSnapshots {
+    /// Creates a buffer keeping the last `capacity` snapshots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(config: SnapshotConfig, capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "WorldSnapshots needs a capacity of at least 1"
+        );
+        Self {
+            config,
+            capacity,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the config used to capture snapshots.
+    pub fn config(&self) -> &SnapshotConfig {
+        &self.config
+    }
+
+    /// Returns the maximum number of snapshots kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of snapshots kept.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if no snapshot is kept.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Captures a snapshot of `world` for `frame`.
+    ///
+    /// Snapshots of `frame` and of later frames are discarded first, as they belong to a
+    /// timeline that has been rolled back.
+    pub fn capture(&mut self, frame: u64, world: &World) {
+        self.discard_from(frame);
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        let snapshot = WorldSnapshot::capture(world, &self.config);
+        self.snapshots.push_back((frame, snapshot));
+    }
+
+    /// Returns the snapshot captured for `frame`.
+    pub fn get(&self, frame: u64) -> Option<&WorldSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|(snapshot_frame, _)| *snapshot_frame == frame)
+            .map(|(_, snapshot)| snapshot)
+    }
+
+    /// Restores the snapshot captured for `frame` into `world`.
+    ///
+    /// Returns `false` if there is no snapshot for `frame`. The snapshot is kept, so the same
+    /// frame can be restored several times.
+    pub fn restore(&self, frame: u64, world: &mut World) -> bool {
+        match self.get(frame) {
+            Some(snapshot) => {
+                snapshot.restore(world);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the oldest frame that can be restored.
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.snapshots.front().map(|(frame, _)| *frame)
+    }
+
+    /// Returns the latest frame that can be restored.
+    pub fn latest_frame(&self) -> Option<u64> {
+        self.snapshots.back().map(|(frame, _)| *frame)
+    }
+
+    /// Discards the snapshots of `frame` and later frames.
+    pub fn discard_from(&mut self, frame: u64) {
+        while matches!(self.snapshots.back(), Some((last, _)) if *last >= frame) {
+            self.snapshots.pop_back();
+        }
+    }
+
+    /// Discards all snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Returns the entities having the component, or none if it is not registered.
+fn entities_with(
+    world: &World,
+    component_id: Option<ComponentId>,
+) -> impl Iterator<Item = Entity> + '_ {
+    world
+        .archetypes()
+        .iter()
+        .filter(move |archetype| matches!(component_id, Some(id) if archetype.contains(id)))
+        .flat_map(|archetype| archetype.entities().iter().map(|entity| entity.entity()))
+}
+
+struct CloneComponent<T>(std::marker::PhantomData<fn() -> T>);
+
+impl<T: Component + Clone> SnapshotComponent for CloneComponent<T> {
+    fn component_id(&self, world: &World) -> Option<ComponentId> {
+        world.components().component_id::<T>()
+    }
+
+    fn capture(&self, world: &World) -> SnapshotData {
+        let mut values: Vec<(Entity, T)> = entities_with(world, self.component_id(world))
+            .map(|entity| (entity, world.get::<T>(entity).unwrap().clone()))
+            .collect();
+        values.sort_unstable_by_key(|(entity, _)| *entity);
+        Box::new(values)
+    }
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool) {
+        let values = data.downcast_ref::<Vec<(Entity, T)>>().unwrap();
+        remove_missing(world, self.component_id(world), values, |world, entity| {
+            world.entity_mut(entity).remove::<T>();
+        });
+        for (entity, value) in values {
+            match world.get_mut::<T>(*entity) {
+                Some(mut current) if detect_changes => *current = value.clone(),
+                Some(mut current) => *current.bypass_change_detection() = value.clone(),
+                None => {
+                    if let Some(mut entity_mut) = world.get_entity_mut(*entity) {
+                        entity_mut.insert(value.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Removes the component from the entities that have it but are not in `values`.
+fn remove_missing<V>(
+    world: &mut World,
+    component_id: Option<ComponentId>,
+    values: &[(Entity, V)],
+    remove: impl Fn(&mut World, Entity),
+) {
+    let added: Vec<_> = entities_with(world, component_id)
+        .filter(|entity| {
+            values
+                .binary_search_by_key(entity, |(entity, _)| *entity)
+                .is_err()
+        })
+        .collect();
+    for entity in added {
+        remove(world, entity);
+    }
+}
+
+struct CloneResource<R>(std::marker::PhantomData<fn() -> R>);
+
+impl<R: Resource + Clone> SnapshotResource for CloneResource<R> {
+    fn capture(&self, world: &World) -> SnapshotData {
+        Box::new(world.get_resource::<R>().cloned())
+    }
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool) {
+        let value = data.downcast_ref::<Option<R>>().unwrap();
+        match (value, world.get_resource_mut::<R>()) {
+            (Some(value), Some(mut current)) if detect_changes => *current = value.clone(),
+            (Some(value), Some(mut current)) => {
+                *current.bypass_change_detection() = value.clone();
+            }
+            (Some(value), None) => world.insert_resource(value.clone()),
+            (None, Some(_)) => {
+                world.remove_resource::<R>();
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(feature = "bevy_reflect")]
+struct ReflectedComponent {
+    type_id: TypeId,
+    reflect_component: ReflectComponent,
+}
+
+#[cfg(feature = "bevy_reflect")]
+impl SnapshotComponent for ReflectedComponent {
+    fn component_id(&self, world: &World) -> Option<ComponentId> {
+        world.components().get_id(self.type_id)
+    }
+
+    fn capture(&self, world: &World) -> SnapshotData {
+        let mut values: Vec<(Entity, Box<dyn Reflect>)> =
+            entities_with(world, self.component_id(world))
+                .map(|entity| {
+                    let value = self.reflect_component.reflect(world, entity).unwrap();
+                    (entity, value.clone_value())
+                })
+                .collect();
+        values.sort_unstable_by_key(|(entity, _)| *entity);
+        Box::new(values)
+    }
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool) {
+        let values = data
+            .downcast_ref::<Vec<(Entity, Box<dyn Reflect>)>>()
+            .unwrap();
+        remove_missing(world, self.component_id(world), values, |world, entity| {
+            self.reflect_component.remove(world, entity);
+        });
+        for (entity, value) in values {
+            if world.get_entity(*entity).is_none() {
+                continue;
+            }
+            match self.reflect_component.reflect_mut(world, *entity) {
+                Some(mut current) if detect_changes => current.apply(&**value),
+                Some(mut current) => current.bypass_change_detection().apply(&**value),
+                None => self.reflect_component.insert(world, *entity, &**value),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bevy_reflect")]
+struct ReflectedResource(ReflectResource);
+
+#[cfg(feature = "bevy_reflect")]
+impl SnapshotResource for ReflectedResource {
+    fn capture(&self, world: &World) -> SnapshotData {
+        let value = self.0.reflect(world).map(Reflect::clone_value);
+        Box::new(value)
+    }
+
+    fn restore(&self, world: &mut World, data: &SnapshotData, detect_changes: bool) {
+        let value = data.downcast_ref::<Option<Box<dyn Reflect>>>().unwrap();
+        match (value, self.0.reflect_mut(world)) {
+            (Some(value), Some(mut current)) if detect_changes => current.apply(&**value),
+            (Some(value), Some(mut current)) => current.bypass_change_detection().apply(&**value),
+            (Some(value), None) => self.0.insert(world, &**value),
+            (None, Some(_)) => self.0.remove(world),
+            (None, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnapshotConfig, WorldSnapshot, WorldSnapshots};
+    use crate::{
+        self as bevy_ecs, component::Component, entity::Entity, query::Changed, system::Resource,
+        world::World,
+    };
+
+    #[derive(Component, Clone, Debug, PartialEq)]
+    struct Position(i32);
+
+    #[derive(Component, Clone, Debug, PartialEq)]
+    #[component(storage = "SparseSet")]
+    struct Health(u32);
+
+    #[derive(Component)]
+    struct Untracked;
+
+    #[derive(Resource, Clone, Debug, PartialEq)]
+    struct Score(u32);
+
+    fn config() -> SnapshotConfig {
+        SnapshotConfig::default()
+            .with_component::<Position>()
+            .with_component::<Health>()
+            .with_resource::<Score>()
+    }
+
+    #[test]
+    fn restore_entities_and_components() {
+        let mut world = World::new();
+        world.insert_resource(Score(1));
+        let e1 = world.spawn((Position(1), Health(10))).id();
+        let e2 = world.spawn(Position(2)).id();
+        let bystander = world.spawn(Untracked).id();
+        let snapshot = WorldSnapshot::capture(&world, &config());
+        assert_eq!(snapshot.entities(), [e1, e2]);
+
+        world.get_mut::<Position>(e1).unwrap().0 = 100;
+        world.entity_mut(e1).remove::<Health>();
+        world.entity_mut(e2).insert(Health(20));
+        world.despawn(e2);
+        let spawned = world.spawn(Position(3)).id();
+        world.insert_resource(Score(5));
+
+        snapshot.restore(&mut world);
+        assert_eq!(world.get::<Position>(e1), Some(&Position(1)));
+        assert_eq!(world.get::<Health>(e1), Some(&Health(10)));
+        assert_eq!(world.get::<Position>(e2), Some(&Position(2)));
+        assert_eq!(world.get::<Health>(e2), None);
+        assert!(world.get_entity(spawned).is_none());
+        assert!(world.get_entity(bystander).is_some());
+        assert_eq!(world.resource::<Score>(), &Score(1));
+
+        // Restoring the same snapshot again is possible.
+        world.get_mut::<Position>(e1).unwrap().0 = 100;
+        snapshot.restore(&mut world);
+        assert_eq!(world.get::<Position>(e1), Some(&Position(1)));
+    }
+
+    #[test]
+    fn restore_without_change_detection() {
+        let mut world = World::new();
+        let entity = world.spawn(Position(1)).id();
+        let snapshot = WorldSnapshot::capture(&world, &config());
+        world.clear_trackers();
+
+        snapshot.restore(&mut world);
+        let mut changed = world.query_filtered::<Entity, Changed<Position>>();
+        assert_eq!(changed.iter(&world).count(), 0);
+
+        let snapshot = WorldSnapshot::capture(&world, &config().with_change_detection(true));
+        snapshot.restore(&mut world);
+        assert_eq!(changed.iter(&world).collect::<Vec<_>>(), [entity]);
+    }
+
+    #[test]
+    fn restore_reflected_components() {
+        use crate::reflect::ReflectComponent;
+        use bevy_reflect::{FromReflect, Reflect, TypeRegistry};
+
+        #[derive(Component, Reflect, FromReflect, Default, Debug, PartialEq)]
+        #[reflect(Component)]
+        struct Velocity(i32);
+
+        let mut registry = TypeRegistry::default();
+        registry.register::<Velocity>();
+        let config = SnapshotConfig::default()
+            .with_reflect_component(registry.get(std::any::TypeId::of::<Velocity>()).unwrap());
+
+        let mut world = World::new();
+        let entity = world.spawn(Velocity(1)).id();
+        let snapshot = WorldSnapshot::capture(&world, &config);
+
+        world.despawn(entity);
+        let spawned = world.spawn(Velocity(2)).id();
+        snapshot.restore(&mut world);
+        assert_eq!(world.get::<Velocity>(entity), Some(&Velocity(1)));
+        assert!(world.get_entity(spawned).is_none());
+    }
+
+    #[test]
+    fn ring_buffer_discards_old_and_future_frames() {
+        let mut world = World::new();
+        let entity = world.spawn(Position(0)).id();
+        let mut snapshots = WorldSnapshots::new(config(), 3);
+        for frame in 0..5 {
+            world.get_mut::<Position>(entity).unwrap().0 = frame as i32;
+            snapshots.capture(frame, &world);
+        }
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots.oldest_frame(), Some(2));
+        assert!(!snapshots.restore(1, &mut world));
+
+        assert!(snapshots.restore(3, &mut world));
+        assert_eq!(world.get::<Position>(entity), Some(&Position(3)));
+
+        // Resimulating frame 3 replaces the snapshots of frames 3 and 4.
+        snapshots.capture(3, &world);
+        assert_eq!(snapshots.latest_frame(), Some(3));
+        assert_eq!(snapshots.len(), 2);
+    }
+}
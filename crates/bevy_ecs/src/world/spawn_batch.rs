@@ -11,7 +11,13 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    spawner: BundleSpawner<'w, 'w>,
+    spawner: BatchSpawner<'w>,
+}
+
+enum BatchSpawner<'w> {
+    Batched(BundleSpawner<'w, 'w>),
+    /// Used when the bundle has component hooks, which need access to the whole world.
+    Individual(&'w mut World),
 }
 
 impl<'w, I> SpawnBatchIter<'w, I>
@@ -28,6 +34,17 @@ where
         let (lower, upper) = iter.size_hint();
         let length = upper.unwrap_or(lower);
 
+        let has_hooks = world
+            .bundles
+            .init_info::<I::Item>(&mut world.components, &mut world.storages)
+            .has_insert_hooks(&world.components);
+        if has_hooks {
+            return Self {
+                inner: iter,
+                spawner: BatchSpawner::Individual(world),
+            };
+        }
+
         let bundle_info = world
             .bundles
             .init_info::<I::Item>(&mut world.components, &mut world.storages);
@@ -43,7 +60,7 @@ where
 
         Self {
             inner: iter,
-            spawner,
+            spawner: BatchSpawner::Batched(spawner),
         }
     }
 }
@@ -67,8 +84,11 @@ where
 
     fn next(&mut self) -> Option<Entity> {
         let bundle = self.inner.next()?;
-        // SAFETY: bundle matches spawner type
-        unsafe { Some(self.spawner.spawn(bundle)) }
+        match &mut self.spawner {
+            // SAFETY: bundle matches spawner type
+            BatchSpawner::Batched(spawner) => unsafe { Some(spawner.spawn(bundle)) },
+            BatchSpawner::Individual(world) => Some(world.spawn(bundle).id()),
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -1,9 +1,23 @@
-use bevy_input::gamepad::{Gamepad, GamepadAxisType, GamepadButtonType};
+use bevy_input::gamepad::{Gamepad, GamepadAxisType, GamepadButtonType, GamepadInfo};
+use gilrs::MappingSource;
 
 pub fn convert_gamepad_id(gamepad_id: gilrs::GamepadId) -> Gamepad {
     Gamepad::new(gamepad_id.into())
 }
 
+pub fn convert_gamepad_info(gamepad: &gilrs::Gamepad) -> GamepadInfo {
+    GamepadInfo {
+        name: gamepad.name().into(),
+        has_mapping: has_mapping(gamepad.mapping_source()),
+    }
+}
+
+/// Only SDL mappings translate the gamepad to the standard layout; gamepads without one
+/// report the layout of their driver.
+fn has_mapping(source: MappingSource) -> bool {
+    source == MappingSource::SdlMappings
+}
+
 pub fn convert_button(button: gilrs::Button) -> Option<GamepadButtonType> {
     match button {
         gilrs::Button::South => Some(GamepadButtonType::South),
@@ -43,3 +57,16 @@ pub fn convert_axis(axis: gilrs::Axis) -> Option<GamepadAxisType> {
         gilrs::Axis::Unknown | gilrs::Axis::DPadX | gilrs::Axis::DPadY => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::has_mapping;
+    use gilrs::MappingSource;
+
+    #[test]
+    fn only_sdl_mappings_count_as_mapped() {
+        assert!(has_mapping(MappingSource::SdlMappings));
+        assert!(!has_mapping(MappingSource::Driver));
+        assert!(!has_mapping(MappingSource::None));
+    }
+}
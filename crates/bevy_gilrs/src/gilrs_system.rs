@@ -1,15 +1,12 @@
-use crate::converter::{convert_axis, convert_button, convert_gamepad_id};
+use crate::converter::{convert_axis, convert_button, convert_gamepad_id, convert_gamepad_info};
 use bevy_ecs::event::EventWriter;
 use bevy_ecs::system::{NonSend, NonSendMut};
-use bevy_input::gamepad::GamepadInfo;
 use bevy_input::{gamepad::GamepadEventRaw, prelude::*};
 use gilrs::{ev::filter::axis_dpad_to_button, EventType, Filter, Gilrs};
 
 pub fn gilrs_event_startup_system(gilrs: NonSend<Gilrs>, mut events: EventWriter<GamepadEventRaw>) {
     for (id, gamepad) in gilrs.gamepads() {
-        let info = GamepadInfo {
-            name: gamepad.name().into(),
-        };
+        let info = convert_gamepad_info(&gamepad);
 
         events.send(GamepadEventRaw::new(
             convert_gamepad_id(id),
@@ -27,10 +24,7 @@ pub fn gilrs_event_system(mut gilrs: NonSendMut<Gilrs>, mut events: EventWriter<
 
         match gilrs_event.event {
             EventType::Connected => {
-                let pad = gilrs.gamepad(gilrs_event.id);
-                let info = GamepadInfo {
-                    name: pad.name().into(),
-                };
+                let info = convert_gamepad_info(&gilrs.gamepad(gilrs_event.id));
 
                 events.send(GamepadEventRaw::new(
                     convert_gamepad_id(gilrs_event.id),
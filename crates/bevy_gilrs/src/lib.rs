@@ -1,25 +1,35 @@
 mod converter;
 mod gilrs_system;
+mod mapping;
+mod rumble;
+
+pub use mapping::{
+    GamepadMappingSettings, GamepadMappings, GamepadMappingsLoader, SDL_GAMECONTROLLERCONFIG_FILE,
+};
 
 use bevy_app::{App, CoreStage, Plugin, StartupStage};
+use bevy_asset::{AddAsset, AssetServer, Handle};
 use bevy_ecs::schedule::IntoSystemDescriptor;
 use bevy_input::InputSystem;
-use bevy_utils::tracing::error;
-use gilrs::GilrsBuilder;
+use bevy_utils::tracing::{error, warn};
 use gilrs_system::{gilrs_event_startup_system, gilrs_event_system};
+use mapping::{gamepad_mappings_reload_system, GamepadMappingsHandle};
+use rumble::{play_gilrs_rumble, RunningRumbleEffects};
 
+/// Adds gamepad support through gilrs.
 #[derive(Default)]
-pub struct GilrsPlugin;
+pub struct GilrsPlugin {
+    /// The gamepad mappings used to recognize controllers.
+    pub mapping_settings: GamepadMappingSettings,
+}
 
 impl Plugin for GilrsPlugin {
     fn build(&self, app: &mut App) {
-        match GilrsBuilder::new()
-            .with_default_filters(false)
-            .set_update_state(false)
-            .build()
-        {
+        match self.mapping_settings.build_gilrs(None) {
             Ok(gilrs) => {
                 app.insert_non_send_resource(gilrs)
+                    .init_non_send_resource::<RunningRumbleEffects>()
+                    .insert_resource(self.mapping_settings.clone())
                     .add_startup_system_to_stage(
                         StartupStage::PreStartup,
                         gilrs_event_startup_system,
@@ -27,9 +37,33 @@ impl Plugin for GilrsPlugin {
                     .add_system_to_stage(
                         CoreStage::PreUpdate,
                         gilrs_event_system.before(InputSystem),
-                    );
+                    )
+                    .add_system_to_stage(CoreStage::PostUpdate, play_gilrs_rumble);
+            }
+            Err(err) => {
+                error!("Failed to start Gilrs. {}", err);
+                return;
+            }
+        }
+
+        if !app.world.contains_resource::<AssetServer>() {
+            if let Some(path) = &self.mapping_settings.asset_path {
+                warn!("Gamepad mappings at {path} are ignored because there is no AssetServer");
             }
-            Err(err) => error!("Failed to start Gilrs. {}", err),
+            return;
+        }
+
+        app.add_asset::<GamepadMappings>()
+            .init_asset_loader::<GamepadMappingsLoader>();
+
+        if let Some(path) = &self.mapping_settings.asset_path {
+            let handle: Handle<GamepadMappings> =
+                app.world.resource::<AssetServer>().load(path.as_str());
+            app.insert_resource(GamepadMappingsHandle(handle))
+                .add_system_to_stage(
+                    CoreStage::PreUpdate,
+                    gamepad_mappings_reload_system.before(gilrs_event_system),
+                );
         }
     }
 }
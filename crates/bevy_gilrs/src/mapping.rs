@@ -0,0 +1,319 @@
+//! Loading of SDL gamepad mapping databases.
+use crate::{
+    converter::{convert_gamepad_id, convert_gamepad_info},
+    rumble::RunningRumbleEffects,
+};
+use bevy_asset::{AssetEvent, AssetLoader, Assets, Handle, LoadContext, LoadedAsset};
+use bevy_ecs::{
+    event::{EventReader, EventWriter},
+    system::{NonSendMut, Res, Resource},
+};
+use bevy_input::gamepad::{GamepadEventRaw, GamepadEventType};
+use bevy_reflect::TypeUuid;
+use bevy_utils::{
+    tracing::{error, info, warn},
+    BoxedFuture,
+};
+use gilrs::{Gilrs, GilrsBuilder};
+use std::{env, fs};
+
+/// Environment variable holding the path of a mapping file to load when
+/// [`GamepadMappingSettings::env_mappings`] is enabled.
+///
+/// This complements `SDL_GAMECONTROLLERCONFIG`, which holds the mappings themselves.
+pub const SDL_GAMECONTROLLERCONFIG_FILE: &str = "SDL_GAMECONTROLLERCONFIG_FILE";
+
+/// Gamepad mappings in the [SDL_GameControllerDB] format.
+///
+/// Each non-empty line that does not start with `#` maps the buttons and axes of one
+/// controller model to the standard gamepad layout.
+///
+/// [SDL_GameControllerDB]: https://github.com/gabomdq/SDL_GameControllerDB
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "49945c0c-0f72-4ba0-9ef3-f6c15a26ad16"]
+pub struct GamepadMappings {
+    mappings: String,
+}
+
+impl GamepadMappings {
+    /// Creates mappings from the contents of an SDL_GameControllerDB file.
+    ///
+    /// The contents are not validated; gilrs skips the lines it cannot parse.
+    pub fn new(mappings: impl Into<String>) -> Self {
+        Self {
+            mappings: mappings.into(),
+        }
+    }
+
+    /// Returns the raw contents of the mapping database.
+    pub fn as_str(&self) -> &str {
+        &self.mappings
+    }
+
+    /// Returns an iterator over the mapping lines, skipping comments and empty lines.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.mappings
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    }
+}
+
+/// Loads `.gamecontrollerdb` and `.gamecontrollerdb.txt` files as [`GamepadMappings`].
+#[derive(Default)]
+pub struct GamepadMappingsLoader;
+
+impl AssetLoader for GamepadMappingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mappings = std::str::from_utf8(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(GamepadMappings::new(mappings)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gamecontrollerdb", "gamecontrollerdb.txt"]
+    }
+}
+
+/// Controls which gamepad mappings are given to gilrs.
+///
+/// Mappings are applied when the [`GilrsPlugin`](crate::GilrsPlugin) is built, and again
+/// whenever the asset at [`asset_path`](Self::asset_path) is loaded or hot reloaded.
+/// Whether a connected gamepad matched one of them is reported by
+/// [`GamepadInfo::has_mapping`](bevy_input::gamepad::GamepadInfo::has_mapping).
+///
+/// Mappings from the bundled database and from `SDL_GAMECONTROLLERCONFIG` take precedence
+/// over custom ones describing the same controller.
+#[derive(Resource, Debug, Clone)]
+pub struct GamepadMappingSettings {
+    /// Use the mapping database bundled with gilrs.
+    pub included_mappings: bool,
+    /// Use the mappings found in the `SDL_GAMECONTROLLERCONFIG` environment variable and
+    /// in the file pointed to by [`SDL_GAMECONTROLLERCONFIG_FILE`].
+    pub env_mappings: bool,
+    /// Path of a [`GamepadMappings`] asset to load, relative to the asset folder.
+    ///
+    /// Requires the `AssetPlugin` to be added before the `GilrsPlugin`.
+    pub asset_path: Option<String>,
+}
+
+impl Default for GamepadMappingSettings {
+    fn default() -> Self {
+        Self {
+            included_mappings: true,
+            env_mappings: true,
+            asset_path: None,
+        }
+    }
+}
+
+impl GamepadMappingSettings {
+    /// Builds a [`Gilrs`] instance using these settings and the given custom `mappings`.
+    pub(crate) fn build_gilrs(
+        &self,
+        mappings: Option<&GamepadMappings>,
+    ) -> Result<Gilrs, gilrs::Error> {
+        GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .add_included_mappings(self.included_mappings)
+            .add_env_mappings(self.env_mappings)
+            .add_mappings(&self.custom_mappings(mappings))
+            .build()
+    }
+
+    /// Collects the mappings that gilrs does not load by itself: the contents of the file at
+    /// [`SDL_GAMECONTROLLERCONFIG_FILE`] if [`env_mappings`](Self::env_mappings) is enabled,
+    /// followed by the given `mappings`.
+    fn custom_mappings(&self, mappings: Option<&GamepadMappings>) -> String {
+        let mut custom = String::new();
+        if self.env_mappings {
+            if let Ok(path) = env::var(SDL_GAMECONTROLLERCONFIG_FILE) {
+                match fs::read_to_string(&path) {
+                    Ok(file_mappings) => {
+                        custom.push_str(&file_mappings);
+                        custom.push('\n');
+                    }
+                    Err(err) => warn!("Failed to read gamepad mappings from {path}: {err}"),
+                }
+            }
+        }
+        if let Some(mappings) = mappings {
+            custom.push_str(mappings.as_str());
+        }
+        custom
+    }
+}
+
+/// Handle to the [`GamepadMappings`] asset loaded from [`GamepadMappingSettings::asset_path`].
+#[derive(Resource)]
+pub(crate) struct GamepadMappingsHandle(pub Handle<GamepadMappings>);
+
+/// Returns `true` if `events` contain a load or hot reload of the asset behind `handle`.
+fn mappings_changed<'a>(
+    events: impl IntoIterator<Item = &'a AssetEvent<GamepadMappings>>,
+    handle: &Handle<GamepadMappings>,
+) -> bool {
+    events.into_iter().any(|event| match event {
+        AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed } => {
+            changed == handle
+        }
+        AssetEvent::Removed { .. } => false,
+    })
+}
+
+/// Rebuilds gilrs with the custom mappings whenever their asset is (re)loaded.
+///
+/// Gilrs only reads its mapping database when it is created, so all gamepads are reported as
+/// disconnected and reconnected with their new mapping.
+pub(crate) fn gamepad_mappings_reload_system(
+    mut gilrs: NonSendMut<Gilrs>,
+    mut running_rumbles: NonSendMut<RunningRumbleEffects>,
+    mut asset_events: EventReader<AssetEvent<GamepadMappings>>,
+    mut events: EventWriter<GamepadEventRaw>,
+    settings: Res<GamepadMappingSettings>,
+    handle: Res<GamepadMappingsHandle>,
+    assets: Res<Assets<GamepadMappings>>,
+) {
+    if !mappings_changed(asset_events.iter(), &handle.0) {
+        return;
+    }
+    let mappings = match assets.get(&handle.0) {
+        Some(mappings) => mappings,
+        None => return,
+    };
+
+    let new_gilrs = match settings.build_gilrs(Some(mappings)) {
+        Ok(new_gilrs) => new_gilrs,
+        Err(err) => {
+            error!("Failed to apply gamepad mappings. {}", err);
+            return;
+        }
+    };
+    info!(
+        "Applied {} custom gamepad mappings",
+        mappings.iter().count()
+    );
+
+    for (id, _) in gilrs.gamepads() {
+        events.send(GamepadEventRaw::new(
+            convert_gamepad_id(id),
+            GamepadEventType::Disconnected,
+        ));
+    }
+    // Effects belong to the previous gilrs instance.
+    *running_rumbles = RunningRumbleEffects::default();
+    *gilrs = new_gilrs;
+    for (id, gamepad) in gilrs.gamepads() {
+        events.send(GamepadEventRaw::new(
+            convert_gamepad_id(id),
+            GamepadEventType::Connected(convert_gamepad_info(&gamepad)),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        mappings_changed, GamepadMappingSettings, GamepadMappings, SDL_GAMECONTROLLERCONFIG_FILE,
+    };
+    use bevy_asset::{AssetEvent, Handle, HandleId};
+    use std::{env, fs};
+
+    const LINUX_MAPPING: &str =
+        "03000000a30c00002700000011010000,Retro Pad,a:b1,b:b2,platform:Linux,";
+    const WINDOWS_MAPPING: &str =
+        "03000000a30c00002700000000000000,Retro Pad,a:b1,b:b2,platform:Windows,";
+
+    #[test]
+    fn mapping_lines_skip_comments() {
+        let mappings = GamepadMappings::new(
+            "# Linux\n\
+             03000000a30c00002700000011010000,Retro Pad,a:b1,b:b2,platform:Linux,\n\
+             \n\
+             # Windows\n  \
+             03000000a30c00002700000000000000,Retro Pad,a:b1,b:b2,platform:Windows,\n",
+        );
+        let lines: Vec<_> = mappings.iter().collect();
+        assert_eq!(
+            lines,
+            [
+                "03000000a30c00002700000011010000,Retro Pad,a:b1,b:b2,platform:Linux,",
+                "03000000a30c00002700000000000000,Retro Pad,a:b1,b:b2,platform:Windows,",
+            ]
+        );
+    }
+
+    #[test]
+    fn env_file_mappings_come_before_asset_mappings() {
+        // This is the only test touching the variable, so it cannot race with other tests.
+        let path = env::temp_dir().join(format!("bevy_gilrs_mappings_{}.txt", std::process::id()));
+        fs::write(&path, LINUX_MAPPING).unwrap();
+        env::set_var(SDL_GAMECONTROLLERCONFIG_FILE, &path);
+
+        let asset = GamepadMappings::new(WINDOWS_MAPPING);
+        let settings = GamepadMappingSettings::default();
+        let custom = GamepadMappings::new(settings.custom_mappings(Some(&asset)));
+        assert_eq!(
+            custom.iter().collect::<Vec<_>>(),
+            [LINUX_MAPPING, WINDOWS_MAPPING]
+        );
+
+        let settings = GamepadMappingSettings {
+            env_mappings: false,
+            ..Default::default()
+        };
+        let custom = GamepadMappings::new(settings.custom_mappings(Some(&asset)));
+        assert_eq!(custom.iter().collect::<Vec<_>>(), [WINDOWS_MAPPING]);
+
+        // A missing file is skipped with a warning.
+        fs::remove_file(&path).unwrap();
+        let settings = GamepadMappingSettings::default();
+        assert!(settings.custom_mappings(None).trim().is_empty());
+
+        env::remove_var(SDL_GAMECONTROLLERCONFIG_FILE);
+    }
+
+    #[test]
+    fn only_loads_of_the_mapping_asset_trigger_a_reload() {
+        let handle = Handle::<GamepadMappings>::weak(HandleId::random::<GamepadMappings>());
+        let other = Handle::<GamepadMappings>::weak(HandleId::random::<GamepadMappings>());
+
+        assert!(!mappings_changed(&[], &handle));
+        assert!(mappings_changed(
+            &[AssetEvent::Created {
+                handle: handle.clone_weak()
+            }],
+            &handle
+        ));
+        assert!(mappings_changed(
+            &[
+                AssetEvent::Modified {
+                    handle: other.clone_weak()
+                },
+                AssetEvent::Modified {
+                    handle: handle.clone_weak()
+                },
+            ],
+            &handle
+        ));
+        assert!(!mappings_changed(
+            &[AssetEvent::Modified {
+                handle: other.clone_weak()
+            }],
+            &handle
+        ));
+        assert!(!mappings_changed(
+            &[AssetEvent::Removed {
+                handle: handle.clone_weak()
+            }],
+            &handle
+        ));
+    }
+}
@@ -0,0 +1,373 @@
+//! Handle user specified rumble request events.
+use crate::converter::convert_gamepad_id;
+use bevy_ecs::{
+    prelude::{EventReader, Res},
+    system::NonSendMut,
+};
+use bevy_input::gamepad::{Gamepad, GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy_time::Time;
+use bevy_utils::{
+    tracing::{debug, warn},
+    Duration, HashMap,
+};
+use gilrs::{
+    ff::{self, BaseEffect, BaseEffectType, Repeat, Replay},
+    Gilrs,
+};
+use thiserror::Error;
+
+/// A rumble effect that is currently in effect.
+struct RunningRumble<E> {
+    /// Duration from app startup when this effect will be finished
+    deadline: Duration,
+    /// A ref-counted handle to the specific force-feedback effect
+    ///
+    /// Dropping it will cause the effect to stop
+    #[allow(dead_code)]
+    effect: E,
+}
+
+#[derive(Error, Debug)]
+enum RumbleError {
+    #[error("gamepad not found")]
+    GamepadNotFound,
+    #[error("gamepad does not support force feedback")]
+    FfNotSupported,
+    #[error("gilrs error while rumbling gamepad: {0}")]
+    GilrsError(#[from] ff::Error),
+}
+
+/// Contains the gilrs rumble effects that are currently running for each gamepad
+///
+/// The effects are only generic so that their lifetimes can be tested without a gamepad.
+pub(crate) struct RunningRumbleEffects<E = ff::Effect> {
+    /// If multiple rumbles are running at the same time, their resulting rumble
+    /// will be the saturated sum of their strengths up until [`u16::MAX`]
+    rumbles: HashMap<Gamepad, Vec<RunningRumble<E>>>,
+}
+
+impl<E> Default for RunningRumbleEffects<E> {
+    fn default() -> Self {
+        Self {
+            rumbles: HashMap::default(),
+        }
+    }
+}
+
+impl<E> RunningRumbleEffects<E> {
+    /// Drops the effects whose deadline is before `current_time`, which stops them.
+    fn remove_expired(&mut self, current_time: Duration) {
+        for rumbles in self.rumbles.values_mut() {
+            rumbles.retain(|RunningRumble { deadline, .. }| *deadline >= current_time);
+        }
+        self.rumbles.retain(|_gamepad, rumbles| !rumbles.is_empty());
+    }
+
+    /// Stops the effects replaced or stopped by `rumble`, then starts the effect created by
+    /// `play` for the other requests.
+    ///
+    /// Effects are stopped even if `play` fails, so that a stop request always silences the
+    /// gamepad.
+    fn handle_request(
+        &mut self,
+        rumble: GamepadRumbleRequest,
+        current_time: Duration,
+        play: impl FnOnce(GamepadRumbleIntensity, Duration) -> Result<E, RumbleError>,
+    ) -> Result<(), RumbleError> {
+        let gamepad = rumble.gamepad();
+        let (intensity, duration) = match rumble {
+            GamepadRumbleRequest::Stop { .. } => {
+                // `ff::Effect` uses RAII, dropping = deactivating
+                self.rumbles.remove(&gamepad);
+                return Ok(());
+            }
+            GamepadRumbleRequest::Replace {
+                intensity,
+                duration,
+                ..
+            } => {
+                self.rumbles.remove(&gamepad);
+                (intensity, duration)
+            }
+            GamepadRumbleRequest::Add {
+                intensity,
+                duration,
+                ..
+            } => (intensity, duration),
+        };
+
+        let effect = play(intensity, duration)?;
+        let deadline = current_time + duration;
+        self.rumbles
+            .entry(gamepad)
+            .or_default()
+            .push(RunningRumble { deadline, effect });
+        Ok(())
+    }
+}
+
+/// gilrs uses magnitudes from 0 to [`u16::MAX`], while ours go from `0.0` to `1.0` ([`f32`])
+fn to_gilrs_magnitude(ratio: f32) -> u16 {
+    (ratio.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+}
+
+fn get_base_effects(
+    GamepadRumbleIntensity {
+        weak_motor,
+        strong_motor,
+    }: GamepadRumbleIntensity,
+    duration: Duration,
+) -> Vec<BaseEffect> {
+    let mut effects = Vec::new();
+    if strong_motor > 0. {
+        effects.push(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: to_gilrs_magnitude(strong_motor),
+            },
+            scheduling: Replay {
+                play_for: duration.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+    if weak_motor > 0. {
+        effects.push(BaseEffect {
+            kind: BaseEffectType::Weak {
+                magnitude: to_gilrs_magnitude(weak_motor),
+            },
+            scheduling: Replay {
+                play_for: duration.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+    effects
+}
+
+fn handle_rumble_request(
+    running_rumbles: &mut RunningRumbleEffects,
+    gilrs: &mut Gilrs,
+    rumble: GamepadRumbleRequest,
+    current_time: Duration,
+) -> Result<(), RumbleError> {
+    let gamepad = rumble.gamepad();
+
+    let (gamepad_id, ff_supported) = gilrs
+        .gamepads()
+        .find(|(pad_id, _)| convert_gamepad_id(*pad_id) == gamepad)
+        .map(|(pad_id, pad)| (pad_id, pad.is_ff_supported()))
+        .ok_or(RumbleError::GamepadNotFound)?;
+
+    running_rumbles.handle_request(rumble, current_time, |intensity, duration| {
+        if !ff_supported {
+            return Err(RumbleError::FfNotSupported);
+        }
+
+        let mut effect_builder = ff::EffectBuilder::new();
+        for effect in get_base_effects(intensity, duration) {
+            effect_builder.add_effect(effect);
+        }
+        let effect = effect_builder
+            .repeat(Repeat::For(duration.into()))
+            .gamepads(&[gamepad_id])
+            .finish(gilrs)?;
+        effect.play()?;
+        Ok(effect)
+    })
+}
+
+/// Plays the [`GamepadRumbleRequest`]s through gilrs and stops the effects once they expire.
+pub(crate) fn play_gilrs_rumble(
+    time: Res<Time>,
+    mut gilrs: NonSendMut<Gilrs>,
+    mut requests: EventReader<GamepadRumbleRequest>,
+    mut running_rumbles: NonSendMut<RunningRumbleEffects>,
+) {
+    let current_time = time.raw_elapsed();
+    // Remove outdated rumble effects.
+    running_rumbles.remove_expired(current_time);
+
+    // Add new effects.
+    for rumble in requests.iter().cloned() {
+        let gamepad = rumble.gamepad();
+        match handle_rumble_request(&mut running_rumbles, &mut gilrs, rumble, current_time) {
+            Ok(()) => {}
+            Err(RumbleError::GamepadNotFound) => {
+                warn!("Tried to handle rumble request for {gamepad:?} but it doesn't exist!");
+            }
+            // Rumble is best effort, so unsupported devices or platforms are not an error.
+            Err(err) => {
+                debug!(
+                    "Tried to handle rumble request for {gamepad:?} but an error occurred: {err}"
+                );
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_base_effects, to_gilrs_magnitude, RumbleError, RunningRumbleEffects};
+    use bevy_input::gamepad::{Gamepad, GamepadRumbleIntensity, GamepadRumbleRequest};
+    use bevy_utils::Duration;
+    use gilrs::ff::BaseEffectType;
+    use std::{cell::Cell, rc::Rc};
+
+    /// Stands in for a gilrs effect, counting how many were dropped and so stopped.
+    struct TestEffect(Rc<Cell<usize>>);
+
+    impl Drop for TestEffect {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn running(effects: &RunningRumbleEffects<TestEffect>, gamepad: Gamepad) -> usize {
+        effects.rumbles.get(&gamepad).map_or(0, Vec::len)
+    }
+
+    fn add(gamepad: Gamepad, duration: Duration) -> GamepadRumbleRequest {
+        GamepadRumbleRequest::Add {
+            duration,
+            intensity: GamepadRumbleIntensity::MAX,
+            gamepad,
+        }
+    }
+
+    #[test]
+    fn rumble_requests_follow_their_policy() {
+        let stopped = Rc::new(Cell::new(0));
+        let play = |_, _| Ok(TestEffect(stopped.clone()));
+        let mut effects = RunningRumbleEffects::default();
+        let (first, second) = (Gamepad::new(0), Gamepad::new(1));
+        let duration = Duration::from_secs(1);
+
+        effects
+            .handle_request(add(first, duration), Duration::ZERO, play)
+            .unwrap();
+        effects
+            .handle_request(add(first, duration), Duration::ZERO, play)
+            .unwrap();
+        effects
+            .handle_request(add(second, duration), Duration::ZERO, play)
+            .unwrap();
+        assert_eq!(running(&effects, first), 2);
+        assert_eq!(stopped.get(), 0);
+
+        let replace = GamepadRumbleRequest::Replace {
+            duration,
+            intensity: GamepadRumbleIntensity::WEAK_MAX,
+            gamepad: first,
+        };
+        effects
+            .handle_request(replace, Duration::ZERO, play)
+            .unwrap();
+        assert_eq!(running(&effects, first), 1);
+        assert_eq!(stopped.get(), 2);
+
+        let stop = GamepadRumbleRequest::Stop { gamepad: first };
+        effects
+            .handle_request(stop, Duration::ZERO, |_, _| unreachable!())
+            .unwrap();
+        assert_eq!(running(&effects, first), 0);
+        assert_eq!(running(&effects, second), 1);
+        assert_eq!(stopped.get(), 3);
+    }
+
+    #[test]
+    fn unsupported_rumble_still_replaces_running_effects() {
+        let stopped = Rc::new(Cell::new(0));
+        let mut effects = RunningRumbleEffects::default();
+        let gamepad = Gamepad::new(0);
+        let duration = Duration::from_secs(1);
+        effects
+            .handle_request(add(gamepad, duration), Duration::ZERO, |_, _| {
+                Ok(TestEffect(stopped.clone()))
+            })
+            .unwrap();
+
+        let replace = GamepadRumbleRequest::Replace {
+            duration,
+            intensity: GamepadRumbleIntensity::MAX,
+            gamepad,
+        };
+        let result = effects.handle_request(replace, Duration::ZERO, |_, _| {
+            Err(RumbleError::FfNotSupported)
+        });
+        assert!(matches!(result, Err(RumbleError::FfNotSupported)));
+        assert_eq!(running(&effects, gamepad), 0);
+        assert_eq!(stopped.get(), 1);
+    }
+
+    #[test]
+    fn expired_rumble_effects_are_stopped() {
+        let stopped = Rc::new(Cell::new(0));
+        let play = |_, _| Ok(TestEffect(stopped.clone()));
+        let mut effects = RunningRumbleEffects::default();
+        let gamepad = Gamepad::new(0);
+        let start = Duration::from_secs(10);
+        effects
+            .handle_request(add(gamepad, Duration::from_secs(1)), start, play)
+            .unwrap();
+        effects
+            .handle_request(add(gamepad, Duration::from_secs(3)), start, play)
+            .unwrap();
+
+        effects.remove_expired(start + Duration::from_secs(1));
+        assert_eq!(running(&effects, gamepad), 2);
+        effects.remove_expired(start + Duration::from_secs(2));
+        assert_eq!(running(&effects, gamepad), 1);
+        assert_eq!(stopped.get(), 1);
+        effects.remove_expired(start + Duration::from_secs(4));
+        assert!(effects.rumbles.is_empty());
+        assert_eq!(stopped.get(), 2);
+    }
+
+    #[test]
+    fn base_effects_skip_idle_motors() {
+        let duration = Duration::from_millis(500);
+        let effects = get_base_effects(GamepadRumbleIntensity::MAX, duration);
+        assert_eq!(effects.len(), 2);
+        assert!(matches!(
+            effects[0].kind,
+            BaseEffectType::Strong {
+                magnitude: u16::MAX
+            }
+        ));
+        assert!(matches!(
+            effects[1].kind,
+            BaseEffectType::Weak {
+                magnitude: u16::MAX
+            }
+        ));
+
+        let effects = get_base_effects(GamepadRumbleIntensity::WEAK_MAX, duration);
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(effects[0].kind, BaseEffectType::Weak { .. }));
+        assert!(get_base_effects(
+            GamepadRumbleIntensity {
+                strong_motor: 0.0,
+                weak_motor: 0.0
+            },
+            duration
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn magnitude_conversion() {
+        assert_eq!(to_gilrs_magnitude(1.0), u16::MAX);
+        assert_eq!(to_gilrs_magnitude(0.0), 0);
+
+        // bevy magnitudes of 2.0 don't really make sense, but just make sure
+        // they convert to something sensible in gilrs anyway.
+        assert_eq!(to_gilrs_magnitude(2.0), u16::MAX);
+
+        // negative bevy magnitudes don't really make sense, but just make sure
+        // they convert to something sensible in gilrs anyway.
+        assert_eq!(to_gilrs_magnitude(-1.0), 0);
+        assert_eq!(to_gilrs_magnitude(-0.1), 0);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::components::Children;
+use bevy_ecs::{
+    entity::{Disabled, Entity},
+    system::{Command, EntityCommands},
+    world::{EntityMut, World},
+};
+use bevy_utils::tracing::debug;
+
+/// Disables the given entity and all its descendants
+#[derive(Debug)]
+pub struct DisableRecursive {
+    /// Target entity
+    pub entity: Entity,
+}
+
+/// Enables the given entity and all its descendants
+#[derive(Debug)]
+pub struct EnableRecursive {
+    /// Target entity
+    pub entity: Entity,
+}
+
+/// Function for inserting [`Disabled`] on an entity and all its descendants
+pub fn disable_with_children_recursive(world: &mut World, entity: Entity) {
+    set_disabled_recursive(world, entity, true);
+}
+
+/// Function for removing [`Disabled`] from an entity and all its descendants
+pub fn enable_with_children_recursive(world: &mut World, entity: Entity) {
+    set_disabled_recursive(world, entity, false);
+}
+
+fn set_disabled_recursive(world: &mut World, entity: Entity, disabled: bool) {
+    let mut entity_mut = match world.get_entity_mut(entity) {
+        Some(entity_mut) => entity_mut,
+        None => {
+            debug!("Failed to find entity {:?}", entity);
+            return;
+        }
+    };
+    if disabled {
+        entity_mut.insert(Disabled);
+    } else {
+        entity_mut.remove::<Disabled>();
+    }
+
+    // `Children` is read directly, so disabled descendants are visited as well.
+    let children = world
+        .get::<Children>(entity)
+        .map(|children| children.to_vec())
+        .unwrap_or_default();
+    for child in children {
+        set_disabled_recursive(world, child, disabled);
+    }
+}
+
+impl Command for DisableRecursive {
+    fn write(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "command",
+            name = "DisableRecursive",
+            entity = bevy_utils::tracing::field::debug(self.entity)
+        )
+        .entered();
+        disable_with_children_recursive(world, self.entity);
+    }
+}
+
+impl Command for EnableRecursive {
+    fn write(self, world: &mut World) {
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "command",
+            name = "EnableRecursive",
+            entity = bevy_utils::tracing::field::debug(self.entity)
+        )
+        .entered();
+        enable_with_children_recursive(world, self.entity);
+    }
+}
+
+/// Trait that holds functions for disabling and enabling entities down the hierarchy
+///
+/// See [`Disabled`] for the effect of disabling an entity.
+pub trait DisableRecursiveExt {
+    /// Disables the provided entity alongside all descendants.
+    fn disable_recursive(&mut self) -> &mut Self;
+
+    /// Enables the provided entity alongside all descendants.
+    fn enable_recursive(&mut self) -> &mut Self;
+}
+
+impl<'w, 's, 'a> DisableRecursiveExt for EntityCommands<'w, 's, 'a> {
+    fn disable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(DisableRecursive { entity });
+        self
+    }
+
+    fn enable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(EnableRecursive { entity });
+        self
+    }
+}
+
+impl<'w> DisableRecursiveExt for EntityMut<'w> {
+    fn disable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        // SAFETY: The location is updated.
+        unsafe {
+            disable_with_children_recursive(self.world_mut(), entity);
+            self.update_location();
+        }
+        self
+    }
+
+    fn enable_recursive(&mut self) -> &mut Self {
+        let entity = self.id();
+        // SAFETY: The location is updated.
+        unsafe {
+            enable_with_children_recursive(self.world_mut(), entity);
+            self.update_location();
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        component::Component,
+        entity::Disabled,
+        system::{CommandQueue, Commands},
+        world::World,
+    };
+
+    use super::DisableRecursiveExt;
+    use crate::child_builder::BuildWorldChildren;
+
+    #[derive(Component)]
+    struct Node;
+
+    #[test]
+    fn disable_and_enable_recursive() {
+        let mut world = World::default();
+        let mut child = None;
+        let root = world
+            .spawn(Node)
+            .with_children(|parent| {
+                child = Some(parent.spawn(Node).id());
+            })
+            .id();
+        let child = child.unwrap();
+        let grandchild = world.spawn(Node).id();
+        world.entity_mut(child).push_children(&[grandchild]);
+        let bystander = world.spawn(Node).id();
+
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &world)
+            .entity(root)
+            .disable_recursive();
+        queue.apply(&mut world);
+
+        for entity in [root, child, grandchild] {
+            assert!(world.entity(entity).contains::<Disabled>());
+        }
+        let mut query = world.query::<&Node>();
+        assert_eq!(query.iter(&world).count(), 1);
+        assert!(query.get(&world, bystander).is_ok());
+
+        world.entity_mut(root).enable_recursive();
+        assert_eq!(query.iter(&world).count(), 4);
+    }
+}
@@ -1,10 +1,14 @@
-use crate::components::{Children, Parent};
+use crate::{
+    child_builder::BuildWorldChildren,
+    components::{Children, Parent},
+};
 use bevy_ecs::{
-    entity::Entity,
+    entity::{Entity, EntityMap},
     system::{Command, EntityCommands},
-    world::{EntityMut, World},
+    world::{CloneEntityOptions, EntityMut, World},
 };
 use bevy_utils::tracing::debug;
+use smallvec::SmallVec;
 
 /// Despawns the given entity and all its children recursively
 #[derive(Debug)]
@@ -80,6 +84,81 @@ impl Command for DespawnChildrenRecursive {
     }
 }
 
+/// Lets [`World::clone_entity`] copy [`Parent`] and [`Children`] without breaking the
+/// hierarchy.
+///
+/// The copy of an entity whose parent is cloned with it becomes a child of the copy of the
+/// parent. The copy of any other child is added to the children of the original parent. The
+/// copy of an entity only keeps the children that are cloned with it, so the original children
+/// stay with the original entity.
+///
+/// This is done by the [`HierarchyPlugin`](crate::HierarchyPlugin),
+/// [`clone_with_children_recursive`] and [`CloneRecursiveExt`].
+pub fn register_hierarchy_clone(world: &mut World) {
+    world.register_component_clone_with::<Parent>(clone_parent);
+    world.register_component_clone_with::<Children>(clone_children);
+}
+
+fn clone_parent(world: &mut World, source: Entity, target: Entity, entity_map: &EntityMap) {
+    let parent = world.get::<Parent>(source).unwrap().get();
+    if let Ok(parent_clone) = entity_map.get(parent) {
+        world.entity_mut(target).insert(Parent(parent_clone));
+    } else if world.get_entity(parent).is_some() {
+        world.entity_mut(parent).push_children(&[target]);
+    }
+}
+
+fn clone_children(world: &mut World, source: Entity, target: Entity, entity_map: &EntityMap) {
+    let children: SmallVec<[Entity; 8]> = world
+        .get::<Children>(source)
+        .unwrap()
+        .iter()
+        .filter_map(|child| entity_map.get(*child).ok())
+        .collect();
+    if !children.is_empty() {
+        world.entity_mut(target).insert(Children(children));
+    }
+}
+
+/// Function for cloning an entity and all its children
+///
+/// The copy of `entity` is added to the children of its parent, if it has one. See
+/// [`World::clone_entity_with`] for the components that are copied.
+///
+/// # Panics
+///
+/// Panics if `entity` does not exist.
+pub fn clone_with_children_recursive(
+    world: &mut World,
+    entity: Entity,
+    options: CloneEntityOptions,
+) -> Entity {
+    register_hierarchy_clone(world);
+    world.clone_entity_with(entity, &options.recursive::<Children>())
+}
+
+/// Trait that holds functions for cloning recursively down the transform hierarchy
+pub trait CloneRecursiveExt<'w, 's> {
+    /// Clones the provided entity alongside all descendants, and returns the
+    /// [`EntityCommands`] of the copy.
+    fn clone_recursive(&mut self) -> EntityCommands<'w, 's, '_>;
+
+    /// Clones the provided entity alongside all descendants with the given `options`, and
+    /// returns the [`EntityCommands`] of the copy.
+    fn clone_recursive_with(&mut self, options: CloneEntityOptions) -> EntityCommands<'w, 's, '_>;
+}
+
+impl<'w, 's, 'a> CloneRecursiveExt<'w, 's> for EntityCommands<'w, 's, 'a> {
+    fn clone_recursive(&mut self) -> EntityCommands<'w, 's, '_> {
+        self.clone_recursive_with(CloneEntityOptions::default())
+    }
+
+    fn clone_recursive_with(&mut self, options: CloneEntityOptions) -> EntityCommands<'w, 's, '_> {
+        self.commands().add(register_hierarchy_clone);
+        self.clone_entity_with(options.recursive::<Children>())
+    }
+}
+
 /// Trait that holds functions for despawning recursively down the transform hierarchy
 pub trait DespawnRecursiveExt {
     /// Despawns the provided entity alongside all descendants.
@@ -139,12 +218,19 @@ impl<'w> DespawnRecursiveExt for EntityMut<'w> {
 mod tests {
     use bevy_ecs::{
         component::Component,
+        reflect::AppTypeRegistry,
         system::{CommandQueue, Commands},
-        world::World,
+        world::{CloneEntityOptions, World},
     };
 
-    use super::DespawnRecursiveExt;
-    use crate::{child_builder::BuildChildren, components::Children};
+    use super::{
+        clone_with_children_recursive, register_hierarchy_clone, CloneRecursiveExt,
+        DespawnRecursiveExt,
+    };
+    use crate::{
+        child_builder::{BuildChildren, BuildWorldChildren},
+        components::{Children, Parent},
+    };
 
     #[derive(Component, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Debug)]
     struct Idx(u32);
@@ -226,4 +312,82 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn clone_hierarchy() {
+        let mut world = World::default();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<Parent>();
+            registry.register::<Children>();
+        }
+        world.insert_resource(registry);
+        world.register_component_clone::<Idx>();
+
+        let child = world.spawn(Idx(2)).id();
+        let parent = world.spawn(Idx(1)).push_children(&[child]).id();
+        let grandparent = world.spawn(Idx(0)).push_children(&[parent]).id();
+
+        let parent_clone =
+            clone_with_children_recursive(&mut world, parent, CloneEntityOptions::default());
+        let child_clone = world.get::<Children>(parent_clone).unwrap()[0];
+        assert_ne!(child_clone, child);
+        assert_eq!(world.get::<Idx>(child_clone), Some(&Idx(2)));
+        assert_eq!(
+            world.get::<Parent>(child_clone).unwrap().get(),
+            parent_clone
+        );
+        assert_eq!(
+            world.get::<Parent>(parent_clone).unwrap().get(),
+            grandparent
+        );
+        assert_eq!(
+            &**world.get::<Children>(grandparent).unwrap(),
+            &[parent, parent_clone]
+        );
+
+        let mut queue = CommandQueue::default();
+        let child_clone = Commands::new(&mut queue, &world)
+            .entity(child)
+            .clone_recursive()
+            .id();
+        queue.apply(&mut world);
+        assert_eq!(world.get::<Idx>(child_clone), Some(&Idx(2)));
+        assert_eq!(
+            &**world.get::<Children>(parent).unwrap(),
+            &[child, child_clone]
+        );
+    }
+
+    #[test]
+    fn clone_entity_keeps_hierarchy_consistent() {
+        let mut world = World::default();
+        register_hierarchy_clone(&mut world);
+        world.register_component_clone::<Idx>();
+        let child = world.spawn(Idx(1)).id();
+        let parent = world.spawn(Idx(0)).push_children(&[child]).id();
+
+        let child_clone = world.clone_entity(child);
+        assert_eq!(world.get::<Parent>(child_clone).unwrap().get(), parent);
+        assert_eq!(
+            &**world.get::<Children>(parent).unwrap(),
+            &[child, child_clone]
+        );
+
+        let mut queue = CommandQueue::default();
+        let child_clone = Commands::new(&mut queue, &world)
+            .entity(child)
+            .clone_entity()
+            .id();
+        queue.apply(&mut world);
+        assert_eq!(world.get::<Parent>(child_clone).unwrap().get(), parent);
+        assert_eq!(world.get::<Children>(parent).unwrap().len(), 3);
+
+        // The children are not cloned, so they stay with the original parent.
+        let parent_clone = world.clone_entity(parent);
+        assert_eq!(world.get::<Idx>(parent_clone), Some(&Idx(0)));
+        assert!(world.get::<Children>(parent_clone).is_none());
+        assert_eq!(world.get::<Parent>(child).unwrap().get(), parent);
+    }
 }
@@ -10,6 +10,9 @@ pub use components::*;
 mod hierarchy;
 pub use hierarchy::*;
 
+mod disable;
+pub use disable::*;
+
 mod child_builder;
 pub use child_builder::*;
 
@@ -22,12 +25,15 @@ pub use valid_parent_check_plugin::*;
 mod query_extension;
 pub use query_extension::*;
 
+mod relation;
+pub use relation::*;
+
 #[doc(hidden)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        child_builder::*, components::*, hierarchy::*, query_extension::*, HierarchyPlugin,
-        ValidParentCheckPlugin,
+        child_builder::*, components::*, disable::*, hierarchy::*, query_extension::*, relation::*,
+        HierarchyPlugin, ValidParentCheckPlugin,
     };
 }
 
@@ -42,5 +48,6 @@ impl Plugin for HierarchyPlugin {
         app.register_type::<Children>()
             .register_type::<Parent>()
             .add_event::<HierarchyEvent>();
+        register_hierarchy_clone(&mut app.world);
     }
 }
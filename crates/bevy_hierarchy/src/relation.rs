@@ -0,0 +1,568 @@
+use crate::despawn_with_children_recursive;
+use bevy_ecs::{
+    component::{Component, ComponentHooks, ComponentId, TableStorage},
+    entity::Entity,
+    query::{ReadOnlyWorldQuery, With, WorldQuery},
+    system::{Command, EntityCommands, Query},
+    world::{DeferredWorld, EntityMut, World},
+};
+use smallvec::SmallVec;
+use std::{fmt, marker::PhantomData, ops::Deref};
+
+/// A kind of directed relationship between entities, such as `Targets`, `OwnedBy` or
+/// `ConnectedTo`.
+///
+/// Relating a source entity to a target entity stores the target in the source's
+/// [`RelationTargets`] and the source in the target's [`RelationSources`]. Both sides are
+/// kept consistent when relations are added or removed through [`BuildRelations`] and when
+/// either entity is despawned.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_hierarchy::prelude::*;
+/// struct OwnedBy;
+///
+/// impl Relation for OwnedBy {
+///     // Items are despawned along with their owner.
+///     const ON_DESPAWN: DespawnPolicy = DespawnPolicy::Cascade;
+/// }
+///
+/// let mut world = World::new();
+/// let player = world.spawn_empty().id();
+/// let sword = world.spawn_empty().relate::<OwnedBy>(player).id();
+///
+/// assert!(world.get::<RelationTargets<OwnedBy>>(sword).unwrap().contains(player));
+/// assert_eq!(**world.get::<RelationSources<OwnedBy>>(player).unwrap(), [sword]);
+///
+/// world.despawn(player);
+/// assert!(world.get_entity(sword).is_none());
+/// ```
+pub trait Relation: Send + Sync + 'static {
+    /// What happens to the sources of this relation when one of their targets is despawned.
+    const ON_DESPAWN: DespawnPolicy = DespawnPolicy::Unlink;
+}
+
+/// What happens to the sources of a [`Relation`] when their target is despawned.
+///
+/// The policy is also applied when the target's [`RelationSources`] component is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DespawnPolicy {
+    /// Sources are despawned recursively, along with their children.
+    Cascade,
+    /// The target is removed from the relation of its sources, which keep their other targets.
+    Unlink,
+    /// The relation is removed from its sources, which lose all their targets.
+    Orphan,
+}
+
+/// The entities this entity is related to by the [`Relation`] `R`.
+///
+/// Filter on [`HasRelation`] to find entities with at least one target. To find the entities
+/// related to a given target, read its [`RelationSources`] with
+/// [`RelationQueryExt::related_to`] and look them up with [`Query::iter_many`]:
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_hierarchy::prelude::*;
+/// # struct Targets;
+/// # impl Relation for Targets {}
+/// # #[derive(Component)]
+/// # struct Health(u32);
+/// fn damage_attackers(
+///     sources: Query<&RelationSources<Targets>>,
+///     mut attackers: Query<&mut Health>,
+///     player: Query<Entity, With<Player>>,
+/// ) {
+///     let player = player.single();
+///     let mut iter = attackers.iter_many_mut(sources.related_to(player));
+///     while let Some(mut health) = iter.fetch_next() {
+///         health.0 -= 1;
+///     }
+/// }
+/// # #[derive(Component)]
+/// # struct Player;
+/// # bevy_ecs::system::assert_is_system(damage_attackers);
+/// ```
+pub struct RelationTargets<R: Relation> {
+    entities: SmallVec<[Entity; 4]>,
+    marker: PhantomData<fn() -> R>,
+}
+
+/// The entities related to this entity by the [`Relation`] `R`.
+///
+/// See [`RelationTargets`] for the other side of the relation.
+pub struct RelationSources<R: Relation> {
+    entities: SmallVec<[Entity; 4]>,
+    marker: PhantomData<fn() -> R>,
+}
+
+/// Query filter for entities that are related to at least one entity by `R`.
+pub type HasRelation<R> = With<RelationTargets<R>>;
+
+/// Query filter for entities that at least one entity is related to by `R`.
+pub type IsRelationTarget<R> = With<RelationSources<R>>;
+
+/// An extension trait for [`Query`] that adds [`Relation`] related methods.
+pub trait RelationQueryExt<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> {
+    /// Returns an [`Iterator`] over the entities related to `target` by `R`.
+    ///
+    /// Can only be called on a [`Query`] of [`RelationSources`]
+    /// (i.e. `Query<&RelationSources<R>>`). Yields nothing if `target` doesn't match the query.
+    fn related_to<R: Relation>(&'w self, target: Entity) -> RelatedIter<'w>
+    where
+        Q::ReadOnly: WorldQuery<Item<'w> = &'w RelationSources<R>>;
+
+    /// Returns an [`Iterator`] over the entities `source` is related to by `R`.
+    ///
+    /// Can only be called on a [`Query`] of [`RelationTargets`]
+    /// (i.e. `Query<&RelationTargets<R>>`). Yields nothing if `source` doesn't match the query.
+    fn targets_of<R: Relation>(&'w self, source: Entity) -> RelatedIter<'w>
+    where
+        Q::ReadOnly: WorldQuery<Item<'w> = &'w RelationTargets<R>>;
+}
+
+impl<'w, 's, Q: WorldQuery, F: ReadOnlyWorldQuery> RelationQueryExt<'w, 's, Q, F>
+    for Query<'w, 's, Q, F>
+{
+    fn related_to<R: Relation>(&'w self, target: Entity) -> RelatedIter<'w>
+    where
+        Q::ReadOnly: WorldQuery<Item<'w> = &'w RelationSources<R>>,
+    {
+        let sources = self.get(target).map_or(&[][..], |sources| &sources[..]);
+        sources.iter().copied()
+    }
+
+    fn targets_of<R: Relation>(&'w self, source: Entity) -> RelatedIter<'w>
+    where
+        Q::ReadOnly: WorldQuery<Item<'w> = &'w RelationTargets<R>>,
+    {
+        let targets = self.get(source).map_or(&[][..], |targets| &targets[..]);
+        targets.iter().copied()
+    }
+}
+
+/// An [`Iterator`] of [`Entity`]s on one side of a [`Relation`].
+///
+/// Returned by the methods of [`RelationQueryExt`].
+pub type RelatedIter<'w> = std::iter::Copied<std::slice::Iter<'w, Entity>>;
+
+macro_rules! impl_relation_entities {
+    ($name:ident, $on_remove:ident) => {
+        impl<R: Relation> $name<R> {
+            fn new(entity: Entity) -> Self {
+                Self {
+                    entities: smallvec::smallvec![entity],
+                    marker: PhantomData,
+                }
+            }
+
+            /// Returns `true` if `entity` is part of this relation.
+            pub fn contains(&self, entity: Entity) -> bool {
+                self.entities.contains(&entity)
+            }
+        }
+
+        impl<R: Relation> Component for $name<R> {
+            type Storage = TableStorage;
+
+            fn register_component_hooks(hooks: &mut ComponentHooks) {
+                hooks.on_remove($on_remove::<R>);
+            }
+        }
+
+        impl<R: Relation> Deref for $name<R> {
+            type Target = [Entity];
+
+            fn deref(&self) -> &Self::Target {
+                &self.entities[..]
+            }
+        }
+
+        impl<R: Relation> fmt::Debug for $name<R> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.entities)
+                    .finish()
+            }
+        }
+    };
+}
+
+impl_relation_entities!(RelationTargets, on_remove_targets);
+impl_relation_entities!(RelationSources, on_remove_sources);
+
+/// Removes `entity` from the relation side `C` stored on `owner`, removing `C` once empty.
+fn remove_entity<C: Component + Deref<Target = [Entity]>>(
+    world: &mut World,
+    owner: Entity,
+    entity: Entity,
+    entities: fn(&mut C) -> &mut SmallVec<[Entity; 4]>,
+) {
+    let mut owner = match world.get_entity_mut(owner) {
+        Some(owner) => owner,
+        None => return,
+    };
+    if let Some(mut component) = owner.get_mut::<C>() {
+        entities(&mut component).retain(|e| *e != entity);
+        if component.is_empty() {
+            owner.remove::<C>();
+        }
+    }
+}
+
+fn remove_source<R: Relation>(world: &mut World, target: Entity, source: Entity) {
+    remove_entity::<RelationSources<R>>(world, target, source, |sources| &mut sources.entities);
+}
+
+fn remove_target<R: Relation>(world: &mut World, source: Entity, target: Entity) {
+    remove_entity::<RelationTargets<R>>(world, source, target, |targets| &mut targets.entities);
+}
+
+fn on_remove_targets<R: Relation>(mut world: DeferredWorld, source: Entity, _: ComponentId) {
+    let targets = world
+        .get::<RelationTargets<R>>(source)
+        .unwrap()
+        .entities
+        .clone();
+    for target in targets {
+        world
+            .commands()
+            .add(move |world: &mut World| remove_source::<R>(world, target, source));
+    }
+}
+
+fn on_remove_sources<R: Relation>(mut world: DeferredWorld, target: Entity, _: ComponentId) {
+    let sources = world
+        .get::<RelationSources<R>>(target)
+        .unwrap()
+        .entities
+        .clone();
+    for source in sources {
+        world.commands().add(move |world: &mut World| {
+            if world.get_entity(source).is_none() {
+                return;
+            }
+            match R::ON_DESPAWN {
+                DespawnPolicy::Cascade => despawn_with_children_recursive(world, source),
+                DespawnPolicy::Unlink => remove_target::<R>(world, source, target),
+                DespawnPolicy::Orphan => {
+                    world.entity_mut(source).remove::<RelationTargets<R>>();
+                }
+            }
+        });
+    }
+}
+
+/// Command that relates `source` to `target` by the [`Relation`] `R`.
+///
+/// # Panics
+///
+/// Panics if either entity doesn't exist, before changing any of them.
+pub struct AddRelation<R: Relation> {
+    /// Entity the relation starts from
+    pub source: Entity,
+    /// Entity the relation points to
+    pub target: Entity,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<R: Relation> AddRelation<R> {
+    /// Creates a command relating `source` to `target`.
+    pub fn new(source: Entity, target: Entity) -> Self {
+        Self {
+            source,
+            target,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> Command for AddRelation<R> {
+    fn write(self, world: &mut World) {
+        for entity in [self.source, self.target] {
+            assert!(
+                world.get_entity(entity).is_some(),
+                "Attempting to relate {:?} to {:?} by {}, but {:?} doesn't exist.",
+                self.source,
+                self.target,
+                std::any::type_name::<R>(),
+                entity,
+            );
+        }
+
+        let mut source = world.entity_mut(self.source);
+        if let Some(mut targets) = source.get_mut::<RelationTargets<R>>() {
+            if targets.contains(self.target) {
+                return;
+            }
+            targets.entities.push(self.target);
+        } else {
+            source.insert(RelationTargets::<R>::new(self.target));
+        }
+
+        let mut target = world.entity_mut(self.target);
+        if let Some(mut sources) = target.get_mut::<RelationSources<R>>() {
+            sources.entities.push(self.source);
+        } else {
+            target.insert(RelationSources::<R>::new(self.source));
+        }
+    }
+}
+
+/// Command that removes the [`Relation`] `R` between `source` and `target`.
+pub struct RemoveRelation<R: Relation> {
+    /// Entity the relation starts from
+    pub source: Entity,
+    /// Entity the relation points to
+    pub target: Entity,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<R: Relation> RemoveRelation<R> {
+    /// Creates a command removing the relation between `source` and `target`.
+    pub fn new(source: Entity, target: Entity) -> Self {
+        Self {
+            source,
+            target,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> Command for RemoveRelation<R> {
+    fn write(self, world: &mut World) {
+        // Update the target side first, so that removing an emptied `RelationTargets` doesn't
+        // reach back to it.
+        remove_source::<R>(world, self.target, self.source);
+        remove_target::<R>(world, self.source, self.target);
+    }
+}
+
+/// Trait for managing the [`Relation`]s of an entity.
+pub trait BuildRelations {
+    /// Relates this entity to `target` by `R`.
+    fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self;
+    /// Removes the relation `R` from this entity to `target`.
+    fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self;
+    /// Removes the relation `R` from this entity to all of its targets.
+    fn clear_relations<R: Relation>(&mut self) -> &mut Self;
+}
+
+impl<'w, 's, 'a> BuildRelations for EntityCommands<'w, 's, 'a> {
+    fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        self.commands().add(AddRelation::<R>::new(source, target));
+        self
+    }
+
+    fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        self.commands()
+            .add(RemoveRelation::<R>::new(source, target));
+        self
+    }
+
+    fn clear_relations<R: Relation>(&mut self) -> &mut Self {
+        self.remove::<RelationTargets<R>>();
+        self
+    }
+}
+
+impl<'w> BuildRelations for EntityMut<'w> {
+    fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        self.world_scope(|world| AddRelation::<R>::new(source, target).write(world));
+        self
+    }
+
+    fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        self.world_scope(|world| RemoveRelation::<R>::new(source, target).write(world));
+        self
+    }
+
+    fn clear_relations<R: Relation>(&mut self) -> &mut Self {
+        self.remove::<RelationTargets<R>>();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BuildRelations, DespawnPolicy, Relation, RelationQueryExt, RelationSources, RelationTargets,
+    };
+    use crate::BuildWorldChildren;
+    use bevy_ecs::{
+        entity::Entity,
+        system::{CommandQueue, Commands, Query, SystemState},
+        world::World,
+    };
+
+    struct Targets;
+    impl Relation for Targets {}
+
+    struct OwnedBy;
+    impl Relation for OwnedBy {
+        const ON_DESPAWN: DespawnPolicy = DespawnPolicy::Cascade;
+    }
+
+    struct ConnectedTo;
+    impl Relation for ConnectedTo {
+        const ON_DESPAWN: DespawnPolicy = DespawnPolicy::Orphan;
+    }
+
+    fn targets<R: Relation>(world: &World, entity: Entity) -> Vec<Entity> {
+        world
+            .get::<RelationTargets<R>>(entity)
+            .map_or_else(Vec::new, |targets| targets.to_vec())
+    }
+
+    fn sources<R: Relation>(world: &World, entity: Entity) -> Vec<Entity> {
+        world
+            .get::<RelationSources<R>>(entity)
+            .map_or_else(Vec::new, |sources| sources.to_vec())
+    }
+
+    #[test]
+    fn relate_and_unrelate_commands() {
+        let mut world = World::default();
+        let [a, b, c] = [(); 3].map(|_| world.spawn_empty().id());
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.entity(a).relate::<Targets>(b).relate::<Targets>(c);
+        commands.entity(b).relate::<Targets>(c);
+        // Relating twice has no effect.
+        commands.entity(a).relate::<Targets>(b);
+        queue.apply(&mut world);
+
+        assert_eq!(targets::<Targets>(&world, a), [b, c]);
+        assert_eq!(targets::<Targets>(&world, b), [c]);
+        assert_eq!(sources::<Targets>(&world, b), [a]);
+        assert_eq!(sources::<Targets>(&world, c), [a, b]);
+        // Relations are independent of each other.
+        assert!(targets::<OwnedBy>(&world, a).is_empty());
+
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.entity(a).unrelate::<Targets>(c);
+        commands.entity(b).clear_relations::<Targets>();
+        queue.apply(&mut world);
+
+        assert_eq!(targets::<Targets>(&world, a), [b]);
+        assert!(world.get::<RelationTargets<Targets>>(b).is_none());
+        assert!(world.get::<RelationSources<Targets>>(c).is_none());
+    }
+
+    #[test]
+    fn relate_and_unrelate_world() {
+        let mut world = World::default();
+        let [a, b] = [(); 2].map(|_| world.spawn_empty().id());
+
+        world.entity_mut(a).relate::<Targets>(b);
+        assert_eq!(targets::<Targets>(&world, a), [b]);
+        assert_eq!(sources::<Targets>(&world, b), [a]);
+
+        world.entity_mut(a).unrelate::<Targets>(b);
+        assert!(world.get::<RelationTargets<Targets>>(a).is_none());
+        assert!(world.get::<RelationSources<Targets>>(b).is_none());
+    }
+
+    #[test]
+    fn relating_to_missing_entity_changes_nothing() {
+        let mut world = World::default();
+        let source = world.spawn_empty().id();
+        let target = world.spawn_empty().id();
+        world.despawn(target);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            world.entity_mut(source).relate::<Targets>(target);
+        }));
+        assert!(result.is_err());
+        assert!(targets::<Targets>(&world, source).is_empty());
+    }
+
+    #[test]
+    fn despawning_source_updates_targets() {
+        let mut world = World::default();
+        let [a, b, c] = [(); 3].map(|_| world.spawn_empty().id());
+        world.entity_mut(a).relate::<Targets>(c);
+        world.entity_mut(b).relate::<Targets>(c);
+
+        world.despawn(a);
+        assert_eq!(sources::<Targets>(&world, c), [b]);
+    }
+
+    #[test]
+    fn despawn_policy_unlink() {
+        let mut world = World::default();
+        let [a, b, c] = [(); 3].map(|_| world.spawn_empty().id());
+        world
+            .entity_mut(a)
+            .relate::<Targets>(b)
+            .relate::<Targets>(c);
+
+        world.despawn(b);
+        assert_eq!(targets::<Targets>(&world, a), [c]);
+        world.despawn(c);
+        assert!(world.get::<RelationTargets<Targets>>(a).is_none());
+    }
+
+    #[test]
+    fn despawn_policy_cascade() {
+        let mut world = World::default();
+        let [owner, other] = [(); 2].map(|_| world.spawn_empty().id());
+        let item = world.spawn_empty().relate::<OwnedBy>(owner).id();
+        let attachment = world.spawn_empty().relate::<OwnedBy>(item).id();
+        let mut child = None;
+        world.entity_mut(item).with_children(|parent| {
+            child = Some(parent.spawn_empty().id());
+        });
+        world.entity_mut(other).relate::<Targets>(item);
+
+        world.despawn(owner);
+        assert!(world.get_entity(item).is_none());
+        assert!(world.get_entity(attachment).is_none());
+        assert!(world.get_entity(child.unwrap()).is_none());
+        assert!(world.get::<RelationTargets<Targets>>(other).is_none());
+    }
+
+    #[test]
+    fn despawn_policy_orphan() {
+        let mut world = World::default();
+        let [a, b, c] = [(); 3].map(|_| world.spawn_empty().id());
+        world
+            .entity_mut(a)
+            .relate::<ConnectedTo>(b)
+            .relate::<ConnectedTo>(c);
+
+        world.despawn(b);
+        assert!(world.get_entity(a).is_some());
+        assert!(world.get::<RelationTargets<ConnectedTo>>(a).is_none());
+        assert!(world.get::<RelationSources<ConnectedTo>>(c).is_none());
+    }
+
+    #[test]
+    fn query_related_entities() {
+        let mut world = World::default();
+        let [a, b, c, d] = [(); 4].map(|_| world.spawn_empty().id());
+        world
+            .entity_mut(a)
+            .relate::<Targets>(c)
+            .relate::<Targets>(d);
+        world.entity_mut(b).relate::<Targets>(c);
+        world.entity_mut(d).relate::<OwnedBy>(c);
+
+        let mut system_state = SystemState::<(
+            Query<&RelationSources<Targets>>,
+            Query<&RelationTargets<Targets>>,
+        )>::new(&mut world);
+        let (sources, targets) = system_state.get(&world);
+
+        assert_eq!(sources.related_to(c).collect::<Vec<_>>(), [a, b]);
+        assert_eq!(sources.related_to(d).collect::<Vec<_>>(), [a]);
+        assert_eq!(sources.related_to(a).count(), 0);
+        assert_eq!(targets.targets_of(a).collect::<Vec<_>>(), [c, d]);
+        assert_eq!(targets.targets_of(d).count(), 0);
+    }
+}
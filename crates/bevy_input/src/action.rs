@@ -0,0 +1,844 @@
+use crate::{
+    gamepad::{Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
+    keyboard::KeyCode,
+    mouse::MouseButton,
+    Axis, Input, InputSystem,
+};
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::{
+    component::Component,
+    schedule::IntoSystemDescriptor,
+    system::{Query, Res, ResMut, Resource},
+};
+use bevy_math::Vec2;
+use bevy_utils::HashMap;
+use std::{hash::Hash, marker::PhantomData};
+
+/// A user-defined action that can be bound to inputs through an [`InputMap`].
+///
+/// This trait is implemented for every type that fulfills its bounds, which usually means
+/// a fieldless `enum` deriving `Copy`, `Eq` and `Hash`.
+pub trait Actionlike: Copy + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> Actionlike for T {}
+
+/// A single raw input that can be part of a [`UserInput`].
+///
+/// Gamepad inputs are not tied to a specific [`Gamepad`]; the gamepad that is read
+/// is determined by [`InputMap::gamepad`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputKind {
+    /// A key on the keyboard.
+    Keyboard(KeyCode),
+    /// A button on the mouse.
+    Mouse(MouseButton),
+    /// A button on a gamepad.
+    GamepadButton(GamepadButtonType),
+    /// An axis on a gamepad.
+    ///
+    /// The axis counts as pressed whenever its value is not zero.
+    GamepadAxis(GamepadAxisType),
+}
+
+impl From<KeyCode> for InputKind {
+    fn from(key_code: KeyCode) -> Self {
+        InputKind::Keyboard(key_code)
+    }
+}
+
+impl From<MouseButton> for InputKind {
+    fn from(mouse_button: MouseButton) -> Self {
+        InputKind::Mouse(mouse_button)
+    }
+}
+
+impl From<GamepadButtonType> for InputKind {
+    fn from(button_type: GamepadButtonType) -> Self {
+        InputKind::GamepadButton(button_type)
+    }
+}
+
+impl From<GamepadAxisType> for InputKind {
+    fn from(axis_type: GamepadAxisType) -> Self {
+        InputKind::GamepadAxis(axis_type)
+    }
+}
+
+/// A binding that triggers an action of an [`InputMap`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum UserInput {
+    /// A single input.
+    Single(InputKind),
+    /// A combination of inputs that all have to be pressed at the same time.
+    Chord(Vec<InputKind>),
+    /// Two gamepad axes that are read together as an axis pair.
+    DualAxis {
+        /// The axis providing the horizontal value.
+        x: GamepadAxisType,
+        /// The axis providing the vertical value.
+        y: GamepadAxisType,
+    },
+    /// Four inputs that are combined into an axis pair, like the `WASD` keys.
+    VirtualDPad {
+        /// The input pointing in the positive `y` direction.
+        up: InputKind,
+        /// The input pointing in the negative `y` direction.
+        down: InputKind,
+        /// The input pointing in the negative `x` direction.
+        left: InputKind,
+        /// The input pointing in the positive `x` direction.
+        right: InputKind,
+    },
+}
+
+impl UserInput {
+    /// Creates a [`UserInput::Chord`] from the given `inputs`.
+    pub fn chord(inputs: impl IntoIterator<Item = impl Into<InputKind>>) -> Self {
+        UserInput::Chord(inputs.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a [`UserInput::VirtualDPad`] bound to the `W`, `A`, `S` and `D` keys.
+    pub fn wasd() -> Self {
+        UserInput::VirtualDPad {
+            up: KeyCode::W.into(),
+            down: KeyCode::S.into(),
+            left: KeyCode::A.into(),
+            right: KeyCode::D.into(),
+        }
+    }
+
+    /// Creates a [`UserInput::VirtualDPad`] bound to the arrow keys.
+    pub fn arrow_keys() -> Self {
+        UserInput::VirtualDPad {
+            up: KeyCode::Up.into(),
+            down: KeyCode::Down.into(),
+            left: KeyCode::Left.into(),
+            right: KeyCode::Right.into(),
+        }
+    }
+
+    /// Creates a [`UserInput::DualAxis`] bound to the left stick of a gamepad.
+    pub fn left_stick() -> Self {
+        UserInput::DualAxis {
+            x: GamepadAxisType::LeftStickX,
+            y: GamepadAxisType::LeftStickY,
+        }
+    }
+
+    /// Creates a [`UserInput::DualAxis`] bound to the right stick of a gamepad.
+    pub fn right_stick() -> Self {
+        UserInput::DualAxis {
+            x: GamepadAxisType::RightStickX,
+            y: GamepadAxisType::RightStickY,
+        }
+    }
+}
+
+impl From<InputKind> for UserInput {
+    fn from(input: InputKind) -> Self {
+        UserInput::Single(input)
+    }
+}
+
+impl From<KeyCode> for UserInput {
+    fn from(key_code: KeyCode) -> Self {
+        UserInput::Single(key_code.into())
+    }
+}
+
+impl From<MouseButton> for UserInput {
+    fn from(mouse_button: MouseButton) -> Self {
+        UserInput::Single(mouse_button.into())
+    }
+}
+
+impl From<GamepadButtonType> for UserInput {
+    fn from(button_type: GamepadButtonType) -> Self {
+        UserInput::Single(button_type.into())
+    }
+}
+
+impl From<GamepadAxisType> for UserInput {
+    fn from(axis_type: GamepadAxisType) -> Self {
+        UserInput::Single(axis_type.into())
+    }
+}
+
+/// Maps actions of type `A` to the [`UserInput`]s that trigger them.
+///
+/// ## Usage
+///
+/// The map can either be inserted as a resource, in which case it updates the
+/// [`ActionState<A>`] resource, or as a component next to an [`ActionState<A>`] component,
+/// which allows every player to have their own bindings. The update happens inside of
+/// the [`action_state_system`], which is added by the [`InputActionPlugin`].
+///
+/// When the `serialize` feature is enabled the map can be serialized, for example to `RON`,
+/// so that bindings can be changed in a rebind menu and persisted.
+///
+/// ## Gamepads
+///
+/// Use [`InputMap::set_gamepad`] to only read gamepad inputs from a specific [`Gamepad`].
+/// Without an assigned gamepad every connected gamepad can trigger the actions.
+#[derive(Debug, Clone, PartialEq, Resource, Component)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputMap<A: Actionlike> {
+    /// The bindings of each action.
+    bindings: HashMap<A, Vec<UserInput>>,
+    /// The gamepad the gamepad inputs are read from.
+    gamepad: Option<Gamepad>,
+}
+
+impl<A: Actionlike> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: Default::default(),
+            gamepad: None,
+        }
+    }
+}
+
+impl<A: Actionlike> InputMap<A> {
+    /// Creates a new [`InputMap`] from the given `(action, input)` pairs.
+    pub fn new(bindings: impl IntoIterator<Item = (A, impl Into<UserInput>)>) -> Self {
+        let mut input_map = Self::default();
+        for (action, input) in bindings {
+            input_map.insert(action, input);
+        }
+        input_map
+    }
+
+    /// Binds `input` to `action`, keeping the existing bindings of `action`.
+    ///
+    /// Inserting the same input twice for the same action has no effect.
+    pub fn insert(&mut self, action: A, input: impl Into<UserInput>) -> &mut Self {
+        let input = input.into();
+        let inputs = self.bindings.entry(action).or_default();
+        if !inputs.contains(&input) {
+            inputs.push(input);
+        }
+        self
+    }
+
+    /// Removes `input` from the bindings of `action`.
+    ///
+    /// Returns `true` if the input was bound to the action.
+    pub fn remove(&mut self, action: A, input: &UserInput) -> bool {
+        match self.bindings.get_mut(&action) {
+            Some(inputs) => {
+                let len = inputs.len();
+                inputs.retain(|it| it != input);
+                len != inputs.len()
+            }
+            None => false,
+        }
+    }
+
+    /// Removes every binding of `action`, returning the removed bindings.
+    pub fn clear_action(&mut self, action: A) -> Option<Vec<UserInput>> {
+        self.bindings.remove(&action)
+    }
+
+    /// Removes every binding of every action.
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Returns the inputs bound to `action`.
+    pub fn get(&self, action: A) -> &[UserInput] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// An iterator visiting every action and its bindings in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&A, &[UserInput])> {
+        self.bindings
+            .iter()
+            .map(|(action, inputs)| (action, inputs.as_slice()))
+    }
+
+    /// Returns the gamepad the gamepad inputs are read from.
+    ///
+    /// If [`None`] is returned, every connected gamepad is used.
+    pub fn gamepad(&self) -> Option<Gamepad> {
+        self.gamepad
+    }
+
+    /// Only reads the gamepad inputs of this map from `gamepad`.
+    pub fn set_gamepad(&mut self, gamepad: Gamepad) -> &mut Self {
+        self.gamepad = Some(gamepad);
+        self
+    }
+
+    /// Reads the gamepad inputs of this map from every connected gamepad.
+    pub fn clear_gamepad(&mut self) -> &mut Self {
+        self.gamepad = None;
+        self
+    }
+
+    /// Updates `action_state` according to the current state of the `inputs`.
+    fn update(&self, inputs: &InputStreams, action_state: &mut ActionState<A>) {
+        action_state.buttons.clear();
+        action_state.values.clear();
+        action_state.axis_pairs.clear();
+
+        let gamepad = self.gamepad;
+        for (action, bindings) in &self.bindings {
+            let mut pressed = false;
+            let mut value = 0.0f32;
+            let mut axis_pair: Option<Vec2> = None;
+
+            for binding in bindings {
+                pressed |= inputs.input_pressed(binding, gamepad);
+                value = max_magnitude(value, inputs.input_value(binding, gamepad));
+                if let Some(binding_pair) = inputs.input_axis_pair(binding, gamepad) {
+                    match axis_pair {
+                        Some(pair) if pair.length_squared() >= binding_pair.length_squared() => {}
+                        _ => axis_pair = Some(binding_pair),
+                    }
+                }
+            }
+
+            if pressed {
+                action_state.press(*action);
+            } else {
+                action_state.release(*action);
+            }
+            action_state.values.insert(*action, value);
+            if let Some(axis_pair) = axis_pair {
+                action_state.axis_pairs.insert(*action, axis_pair);
+            }
+        }
+
+        // Actions that are no longer bound can't stay pressed.
+        let unbound: Vec<A> = action_state
+            .buttons
+            .get_pressed()
+            .filter(|action| !self.bindings.contains_key(action))
+            .copied()
+            .collect();
+        for action in unbound {
+            action_state.release(action);
+        }
+    }
+}
+
+/// The current state of the actions of type `A`.
+///
+/// ## Usage
+///
+/// This type is used as a resource or component next to an [`InputMap<A>`] and is updated
+/// every frame in the [`action_state_system`]. For a given action:
+///
+/// * [`ActionState::pressed`] will return `true` while any of its bindings is pressed.
+/// * [`ActionState::just_pressed`] will return `true` for one frame after it got pressed.
+/// * [`ActionState::just_released`] will return `true` for one frame after it got released.
+/// * [`ActionState::value`] returns the analog value of the binding with the largest magnitude.
+/// * [`ActionState::axis_pair`] returns the axis pair of [`UserInput::DualAxis`] and
+///   [`UserInput::VirtualDPad`] bindings.
+#[derive(Debug, Clone, Resource, Component)]
+pub struct ActionState<A: Actionlike> {
+    /// The "press" state of every action.
+    buttons: Input<A>,
+    /// The analog value of every bound action.
+    values: HashMap<A, f32>,
+    /// The axis pair of every action bound to an axis pair input.
+    axis_pairs: HashMap<A, Vec2>,
+}
+
+impl<A: Actionlike> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            buttons: Default::default(),
+            values: Default::default(),
+            axis_pairs: Default::default(),
+        }
+    }
+}
+
+impl<A: Actionlike> ActionState<A> {
+    /// Registers a press for the given `action`.
+    ///
+    /// This can be used to trigger actions manually, for example from an on-screen button.
+    pub fn press(&mut self, action: A) {
+        self.buttons.press(action);
+    }
+
+    /// Registers a release for the given `action`.
+    pub fn release(&mut self, action: A) {
+        self.buttons.release(action);
+    }
+
+    /// Returns `true` if the `action` is pressed.
+    pub fn pressed(&self, action: A) -> bool {
+        self.buttons.pressed(action)
+    }
+
+    /// Returns `true` if the `action` is not pressed.
+    pub fn released(&self, action: A) -> bool {
+        !self.buttons.pressed(action)
+    }
+
+    /// Returns `true` if the `action` has just been pressed.
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.buttons.just_pressed(action)
+    }
+
+    /// Returns `true` if the `action` has just been released.
+    pub fn just_released(&self, action: A) -> bool {
+        self.buttons.just_released(action)
+    }
+
+    /// Returns the analog value of the `action`.
+    ///
+    /// Buttons and keys have a value of `1.0` while pressed and `0.0` otherwise.
+    /// Axis pairs report their length.
+    pub fn value(&self, action: A) -> f32 {
+        self.values.get(&action).copied().unwrap_or_default()
+    }
+
+    /// Returns the axis pair of the `action`.
+    ///
+    /// Returns [`None`] if the action isn't bound to a [`UserInput::DualAxis`]
+    /// or [`UserInput::VirtualDPad`].
+    pub fn axis_pair(&self, action: A) -> Option<Vec2> {
+        self.axis_pairs.get(&action).copied()
+    }
+
+    /// An iterator visiting every pressed action in arbitrary order.
+    pub fn get_pressed(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.buttons.get_pressed()
+    }
+
+    /// An iterator visiting every just pressed action in arbitrary order.
+    pub fn get_just_pressed(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.buttons.get_just_pressed()
+    }
+
+    /// An iterator visiting every just released action in arbitrary order.
+    pub fn get_just_released(&self) -> impl ExactSizeIterator<Item = &A> {
+        self.buttons.get_just_released()
+    }
+}
+
+/// The raw input resources an [`InputMap`] is evaluated against.
+struct InputStreams<'a> {
+    keyboard: &'a Input<KeyCode>,
+    mouse: &'a Input<MouseButton>,
+    gamepad_buttons: &'a Input<GamepadButton>,
+    gamepad_button_axes: &'a Axis<GamepadButton>,
+    gamepad_axes: &'a Axis<GamepadAxis>,
+    gamepads: &'a Gamepads,
+}
+
+impl<'a> InputStreams<'a> {
+    /// Returns the gamepads that should be read for the given assigned `gamepad`.
+    fn gamepads(&self, gamepad: Option<Gamepad>) -> impl Iterator<Item = Gamepad> + '_ {
+        gamepad
+            .into_iter()
+            .chain(self.gamepads.iter().filter(move |_| gamepad.is_none()))
+    }
+
+    fn pressed(&self, input: InputKind, gamepad: Option<Gamepad>) -> bool {
+        match input {
+            InputKind::Keyboard(key_code) => self.keyboard.pressed(key_code),
+            InputKind::Mouse(mouse_button) => self.mouse.pressed(mouse_button),
+            InputKind::GamepadButton(button_type) => self.gamepads(gamepad).any(|gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+            InputKind::GamepadAxis(_) => self.value(input, gamepad) != 0.0,
+        }
+    }
+
+    fn value(&self, input: InputKind, gamepad: Option<Gamepad>) -> f32 {
+        match input {
+            InputKind::Keyboard(_) | InputKind::Mouse(_) => {
+                if self.pressed(input, gamepad) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputKind::GamepadButton(button_type) => self
+                .gamepads(gamepad)
+                .map(|gamepad| {
+                    let button = GamepadButton::new(gamepad, button_type);
+                    match self.gamepad_button_axes.get(button) {
+                        Some(value) => value,
+                        None if self.gamepad_buttons.pressed(button) => 1.0,
+                        None => 0.0,
+                    }
+                })
+                .fold(0.0, f32::max),
+            InputKind::GamepadAxis(axis_type) => self
+                .gamepads(gamepad)
+                .filter_map(|gamepad| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+                .fold(0.0, max_magnitude),
+        }
+    }
+
+    fn input_pressed(&self, input: &UserInput, gamepad: Option<Gamepad>) -> bool {
+        match input {
+            UserInput::Single(input) => self.pressed(*input, gamepad),
+            UserInput::Chord(inputs) => {
+                !inputs.is_empty() && inputs.iter().all(|input| self.pressed(*input, gamepad))
+            }
+            UserInput::DualAxis { .. } | UserInput::VirtualDPad { .. } => matches!(
+                self.input_axis_pair(input, gamepad),
+                Some(pair) if pair != Vec2::ZERO
+            ),
+        }
+    }
+
+    fn input_value(&self, input: &UserInput, gamepad: Option<Gamepad>) -> f32 {
+        match input {
+            UserInput::Single(input) => self.value(*input, gamepad),
+            UserInput::Chord(chord) => {
+                if !self.input_pressed(input, gamepad) {
+                    return 0.0;
+                }
+                chord
+                    .iter()
+                    .map(|input| self.value(*input, gamepad))
+                    .fold(0.0, max_magnitude)
+            }
+            UserInput::DualAxis { .. } | UserInput::VirtualDPad { .. } => self
+                .input_axis_pair(input, gamepad)
+                .map_or(0.0, Vec2::length),
+        }
+    }
+
+    fn input_axis_pair(&self, input: &UserInput, gamepad: Option<Gamepad>) -> Option<Vec2> {
+        match input {
+            UserInput::Single(_) | UserInput::Chord(_) => None,
+            UserInput::DualAxis { x, y } => Some(Vec2::new(
+                self.value(InputKind::GamepadAxis(*x), gamepad),
+                self.value(InputKind::GamepadAxis(*y), gamepad),
+            )),
+            UserInput::VirtualDPad {
+                up,
+                down,
+                left,
+                right,
+            } => {
+                let x = self.value(*right, gamepad).abs() - self.value(*left, gamepad).abs();
+                let y = self.value(*up, gamepad).abs() - self.value(*down, gamepad).abs();
+                Some(Vec2::new(x, y).clamp_length_max(1.0))
+            }
+        }
+    }
+}
+
+/// Returns the value with the larger magnitude, preferring `a` on ties.
+fn max_magnitude(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() {
+        b
+    } else {
+        a
+    }
+}
+
+/// Updates every [`ActionState<A>`] according to its [`InputMap<A>`].
+///
+/// The [`InputMap<A>`] resource updates the [`ActionState<A>`] resource, while entities with
+/// both an [`InputMap<A>`] and an [`ActionState<A>`] component get their component updated.
+#[allow(clippy::too_many_arguments)]
+pub fn action_state_system<A: Actionlike>(
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    input_map: Option<Res<InputMap<A>>>,
+    action_state: Option<ResMut<ActionState<A>>>,
+    mut query: Query<(&InputMap<A>, &mut ActionState<A>)>,
+) {
+    let inputs = InputStreams {
+        keyboard: &keyboard,
+        mouse: &mouse,
+        gamepad_buttons: &gamepad_buttons,
+        gamepad_button_axes: &gamepad_button_axes,
+        gamepad_axes: &gamepad_axes,
+        gamepads: &gamepads,
+    };
+
+    if let (Some(input_map), Some(mut action_state)) = (input_map, action_state) {
+        input_map.update(&inputs, &mut action_state);
+    }
+
+    for (input_map, mut action_state) in &mut query {
+        input_map.update(&inputs, &mut action_state);
+    }
+}
+
+/// Adds the [`action_state_system`] for the actions of type `A` to an App.
+///
+/// The plugin initializes the [`ActionState<A>`] resource, but it is up to the user to insert
+/// an [`InputMap<A>`] resource or to spawn entities with [`InputMap<A>`] and
+/// [`ActionState<A>`] components.
+pub struct InputActionPlugin<A: Actionlike>(PhantomData<fn() -> A>);
+
+impl<A: Actionlike> Default for InputActionPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: Actionlike> Plugin for InputActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState<A>>().add_system_to_stage(
+            CoreStage::PreUpdate,
+            action_state_system::<A>
+                .after(InputSystem)
+                .after(crate::gamepad::gamepad_connection_system),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActionState, InputActionPlugin, InputMap, UserInput};
+    use crate::{
+        gamepad::{
+            Gamepad, GamepadAxisType, GamepadButtonType, GamepadEventRaw, GamepadEventType,
+            GamepadInfo,
+        },
+        keyboard::{KeyCode, KeyboardInput},
+        mouse::{MouseButton, MouseButtonInput},
+        ButtonState, InputPlugin,
+    };
+    use bevy_app::App;
+    use bevy_math::Vec2;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+    enum Action {
+        Jump,
+        Shoot,
+        Save,
+        Move,
+    }
+
+    fn test_app(input_map: InputMap<Action>) -> App {
+        let mut app = App::new();
+        app.add_plugin(InputPlugin)
+            .add_plugin(InputActionPlugin::<Action>::default())
+            .insert_resource(input_map);
+        app
+    }
+
+    fn send_key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+        });
+    }
+
+    fn send_gamepad_event(app: &mut App, gamepad: Gamepad, event_type: GamepadEventType) {
+        app.world
+            .send_event(GamepadEventRaw::new(gamepad, event_type));
+    }
+
+    fn connect_gamepad(app: &mut App, gamepad: Gamepad) {
+        send_gamepad_event(
+            app,
+            gamepad,
+            GamepadEventType::Connected(GamepadInfo {
+                name: "Test gamepad".to_string(),
+                has_mapping: true,
+            }),
+        );
+        app.update();
+    }
+
+    #[test]
+    fn press_and_release_action() {
+        let mut app = test_app(InputMap::new([
+            (Action::Jump, KeyCode::Space),
+            (Action::Jump, KeyCode::Up),
+        ]));
+
+        send_key(&mut app, KeyCode::Space, ButtonState::Pressed);
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert!(action_state.pressed(Action::Jump));
+        assert!(action_state.just_pressed(Action::Jump));
+        assert_eq!(action_state.value(Action::Jump), 1.0);
+        assert!(action_state.released(Action::Shoot));
+
+        // A second binding of the same action doesn't trigger another press.
+        send_key(&mut app, KeyCode::Up, ButtonState::Pressed);
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert!(action_state.pressed(Action::Jump));
+        assert!(!action_state.just_pressed(Action::Jump));
+
+        send_key(&mut app, KeyCode::Space, ButtonState::Released);
+        send_key(&mut app, KeyCode::Up, ButtonState::Released);
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert!(!action_state.pressed(Action::Jump));
+        assert!(action_state.just_released(Action::Jump));
+        assert_eq!(action_state.value(Action::Jump), 0.0);
+
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert!(!action_state.just_released(Action::Jump));
+    }
+
+    #[test]
+    fn chord_requires_every_input() {
+        let mut app = test_app(InputMap::new([(
+            Action::Save,
+            UserInput::chord([KeyCode::LControl, KeyCode::S]),
+        )]));
+
+        send_key(&mut app, KeyCode::S, ButtonState::Pressed);
+        app.update();
+        assert!(!app
+            .world
+            .resource::<ActionState<Action>>()
+            .pressed(Action::Save));
+
+        send_key(&mut app, KeyCode::LControl, ButtonState::Pressed);
+        app.update();
+        assert!(app
+            .world
+            .resource::<ActionState<Action>>()
+            .just_pressed(Action::Save));
+    }
+
+    #[test]
+    fn mouse_button_action() {
+        let mut app = test_app(InputMap::new([(Action::Shoot, MouseButton::Left)]));
+
+        app.world.send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state: ButtonState::Pressed,
+        });
+        app.update();
+        assert!(app
+            .world
+            .resource::<ActionState<Action>>()
+            .pressed(Action::Shoot));
+    }
+
+    #[test]
+    fn virtual_dpad_axis_pair() {
+        let mut app = test_app(InputMap::new([(Action::Move, UserInput::wasd())]));
+
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert_eq!(action_state.axis_pair(Action::Move), Some(Vec2::ZERO));
+        assert!(!action_state.pressed(Action::Move));
+
+        send_key(&mut app, KeyCode::W, ButtonState::Pressed);
+        send_key(&mut app, KeyCode::D, ButtonState::Pressed);
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        let axis_pair = action_state.axis_pair(Action::Move).unwrap();
+        assert!(action_state.pressed(Action::Move));
+        assert!((axis_pair - Vec2::ONE.normalize()).length() < 1e-5);
+        assert!((action_state.value(Action::Move) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn gamepad_assignment() {
+        let player_one = Gamepad::new(0);
+        let player_two = Gamepad::new(1);
+
+        let mut app = App::new();
+        app.add_plugin(InputPlugin)
+            .add_plugin(InputActionPlugin::<Action>::default());
+        let mut input_map = InputMap::new([
+            (Action::Jump, UserInput::from(GamepadButtonType::South)),
+            (Action::Move, UserInput::left_stick()),
+        ]);
+        let entity_one = app
+            .world
+            .spawn((
+                input_map.set_gamepad(player_one).clone(),
+                ActionState::<Action>::default(),
+            ))
+            .id();
+        let entity_two = app
+            .world
+            .spawn((
+                input_map.set_gamepad(player_two).clone(),
+                ActionState::<Action>::default(),
+            ))
+            .id();
+
+        connect_gamepad(&mut app, player_one);
+        connect_gamepad(&mut app, player_two);
+
+        send_gamepad_event(
+            &mut app,
+            player_two,
+            GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.0),
+        );
+        send_gamepad_event(
+            &mut app,
+            player_one,
+            GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, 1.0),
+        );
+        app.update();
+
+        let action_state_one = app.world.get::<ActionState<Action>>(entity_one).unwrap();
+        assert!(!action_state_one.pressed(Action::Jump));
+        assert_eq!(
+            action_state_one.axis_pair(Action::Move),
+            Some(Vec2::new(1.0, 0.0))
+        );
+
+        let action_state_two = app.world.get::<ActionState<Action>>(entity_two).unwrap();
+        assert!(action_state_two.just_pressed(Action::Jump));
+        assert_eq!(action_state_two.axis_pair(Action::Move), Some(Vec2::ZERO));
+    }
+
+    #[test]
+    fn unbound_action_is_released() {
+        let mut app = test_app(InputMap::new([(Action::Jump, KeyCode::Space)]));
+
+        send_key(&mut app, KeyCode::Space, ButtonState::Pressed);
+        app.update();
+        assert!(app
+            .world
+            .resource::<ActionState<Action>>()
+            .pressed(Action::Jump));
+
+        app.world
+            .resource_mut::<InputMap<Action>>()
+            .clear_action(Action::Jump);
+        app.update();
+        let action_state = app.world.resource::<ActionState<Action>>();
+        assert!(!action_state.pressed(Action::Jump));
+        assert!(action_state.just_released(Action::Jump));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn input_map_ron_round_trip() {
+        let mut input_map = InputMap::new([
+            (Action::Jump, UserInput::from(KeyCode::Space)),
+            (Action::Jump, UserInput::from(GamepadButtonType::South)),
+            (
+                Action::Save,
+                UserInput::chord([KeyCode::LControl, KeyCode::S]),
+            ),
+            (Action::Move, UserInput::wasd()),
+        ]);
+        input_map.set_gamepad(Gamepad::new(2));
+
+        let serialized = ron::to_string(&input_map).unwrap();
+        let deserialized: InputMap<Action> = ron::from_str(&serialized).unwrap();
+        assert_eq!(input_map, deserialized);
+    }
+}
@@ -2,7 +2,7 @@ use crate::{Axis, Input};
 use bevy_ecs::event::{EventReader, EventWriter};
 use bevy_ecs::system::{Res, ResMut, Resource};
 use bevy_reflect::{std_traits::ReflectDefault, FromReflect, Reflect};
-use bevy_utils::{tracing::info, HashMap};
+use bevy_utils::{tracing::info, Duration, HashMap};
 use thiserror::Error;
 
 /// Errors that occur when setting axis settings for gamepad input.
@@ -97,6 +97,11 @@ impl Gamepad {
 )]
 pub struct GamepadInfo {
     pub name: String,
+    /// Whether the gamepad matched an entry of the SDL mapping database.
+    ///
+    /// Gamepads without a mapping use the layout reported by their driver, which may place
+    /// buttons and axes differently than the standard layout.
+    pub has_mapping: bool,
 }
 
 /// A collection of connected [`Gamepad`]s.
@@ -131,6 +136,11 @@ impl Gamepads {
         self.gamepads.get(&gamepad).map(|g| g.name.as_str())
     }
 
+    /// Returns the [`GamepadInfo`] of the `gamepad`, if it is connected.
+    pub fn info(&self, gamepad: Gamepad) -> Option<&GamepadInfo> {
+        self.gamepads.get(&gamepad)
+    }
+
     /// Registers the `gamepad`, marking it as connected.
     fn register(&mut self, gamepad: Gamepad, info: GamepadInfo) {
         self.gamepads.insert(gamepad, info);
@@ -258,7 +268,7 @@ impl GamepadEvent {
 ///
 /// // Send the gamepad connected event to mark our gamepad as connected.
 /// // This updates the `Gamepads` resource accordingly.
-/// let info = GamepadInfo { name: "Mock Gamepad".into() };
+/// let info = GamepadInfo { name: "Mock Gamepad".into(), has_mapping: true };
 /// app.world.send_event(GamepadEventRaw::new(gamepad, GamepadEventType::Connected(info)));
 ///
 /// // Send the gamepad input event to mark the `South` gamepad button as pressed.
@@ -1257,6 +1267,133 @@ const ALL_AXIS_TYPES: [GamepadAxisType; 6] = [
     GamepadAxisType::RightZ,
 ];
 
+/// The intensity at which a gamepad's force-feedback motors may rumble.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadRumbleIntensity {
+    /// The rumble intensity of the strong gamepad motor.
+    ///
+    /// Ranges from `0.0` to `1.0`.
+    ///
+    /// By convention, this is usually a low-frequency motor on the left-hand
+    /// side of the gamepad, though it may vary across platforms and hardware.
+    pub strong_motor: f32,
+    /// The rumble intensity of the weak gamepad motor.
+    ///
+    /// Ranges from `0.0` to `1.0`.
+    ///
+    /// By convention, this is usually a high-frequency motor on the right-hand
+    /// side of the gamepad, though it may vary across platforms and hardware.
+    pub weak_motor: f32,
+}
+
+impl GamepadRumbleIntensity {
+    /// Rumble both gamepad motors at maximum intensity.
+    pub const MAX: Self = GamepadRumbleIntensity {
+        strong_motor: 1.0,
+        weak_motor: 1.0,
+    };
+
+    /// Rumble the weak motor at maximum intensity.
+    pub const WEAK_MAX: Self = GamepadRumbleIntensity {
+        strong_motor: 0.0,
+        weak_motor: 1.0,
+    };
+
+    /// Rumble the strong motor at maximum intensity.
+    pub const STRONG_MAX: Self = GamepadRumbleIntensity {
+        strong_motor: 1.0,
+        weak_motor: 0.0,
+    };
+
+    /// Creates a new rumble intensity with weak motor intensity set to the given value.
+    pub const fn weak_motor(intensity: f32) -> Self {
+        Self {
+            weak_motor: intensity,
+            strong_motor: 0.0,
+        }
+    }
+
+    /// Creates a new rumble intensity with strong motor intensity set to the given value.
+    pub const fn strong_motor(intensity: f32) -> Self {
+        Self {
+            strong_motor: intensity,
+            weak_motor: 0.0,
+        }
+    }
+}
+
+/// An event that controls force-feedback rumbling of a [`Gamepad`].
+///
+/// # Notes
+///
+/// Does nothing if the gamepad or platform does not support rumble.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_input::gamepad::{Gamepad, Gamepads, GamepadRumbleRequest, GamepadRumbleIntensity};
+/// # use bevy_ecs::prelude::{EventWriter, Res};
+/// # use bevy_utils::Duration;
+/// fn rumble_gamepad_system(
+///     mut rumble_requests: EventWriter<GamepadRumbleRequest>,
+///     gamepads: Res<Gamepads>
+/// ) {
+///     for gamepad in gamepads.iter() {
+///         rumble_requests.send(GamepadRumbleRequest::Add {
+///             gamepad,
+///             intensity: GamepadRumbleIntensity::MAX,
+///             duration: Duration::from_secs_f32(0.5),
+///         });
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadRumbleRequest {
+    /// Add a rumble to the given gamepad.
+    ///
+    /// Simultaneous rumble effects add up to the sum of their strengths.
+    ///
+    /// Consequently, if two rumbles at half intensity are added at the same
+    /// time, their intensities will be added up, and the controller will rumble
+    /// at full intensity until one of the rumbles finishes, then the rumble
+    /// will continue at the intensity of the remaining event.
+    ///
+    /// To replace an existing rumble, use [`GamepadRumbleRequest::Replace`] instead.
+    Add {
+        /// How long the gamepad should rumble.
+        duration: Duration,
+        /// How intense the rumble should be.
+        intensity: GamepadRumbleIntensity,
+        /// The gamepad to rumble.
+        gamepad: Gamepad,
+    },
+    /// Stop all running rumbles on the given gamepad and start a new one.
+    Replace {
+        /// How long the gamepad should rumble.
+        duration: Duration,
+        /// How intense the rumble should be.
+        intensity: GamepadRumbleIntensity,
+        /// The gamepad to rumble.
+        gamepad: Gamepad,
+    },
+    /// Stop all running rumbles on the given [`Gamepad`].
+    Stop {
+        /// The gamepad to stop rumble.
+        gamepad: Gamepad,
+    },
+}
+
+impl GamepadRumbleRequest {
+    /// Get the [`Gamepad`] associated with this request.
+    pub fn gamepad(&self) -> Gamepad {
+        match self {
+            Self::Add { gamepad, .. } | Self::Replace { gamepad, .. } | Self::Stop { gamepad } => {
+                *gamepad
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::gamepad::{AxisSettingsError, ButtonSettingsError};
@@ -0,0 +1,644 @@
+use crate::{
+    touch::{TouchInput, TouchPhase},
+    InputSystem,
+};
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::{
+    event::{EventReader, EventWriter},
+    schedule::{IntoSystemDescriptor, SystemLabel},
+    system::{Local, Res, Resource},
+};
+use bevy_math::Vec2;
+use bevy_reflect::{std_traits::ReflectDefault, FromReflect, Reflect};
+use bevy_time::Time;
+use bevy_utils::{Duration, HashMap};
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// A short touch of a single finger that barely moved.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct TapGesture {
+    /// The position the finger was lifted at.
+    pub position: Vec2,
+    /// The unique identifier of the finger.
+    pub id: u64,
+}
+
+/// Two [`TapGesture`]s in quick succession at roughly the same position.
+///
+/// The second tap still sends its own [`TapGesture`].
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct DoubleTap {
+    /// The position of the second tap.
+    pub position: Vec2,
+}
+
+/// A single finger that has been held down without moving for a while.
+///
+/// The event is sent once while the finger is still down. A long press never turns into a
+/// [`TapGesture`] or a [`Swipe`] when the finger is lifted.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LongPress {
+    /// The position of the finger.
+    pub position: Vec2,
+    /// The unique identifier of the finger.
+    pub id: u64,
+}
+
+/// A fast movement of a single finger that ended by lifting the finger.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Swipe {
+    /// The normalized direction from the start to the end of the swipe.
+    pub direction: Vec2,
+    /// The average speed of the swipe in logical pixels per second.
+    pub velocity: f32,
+    /// The position the finger started the swipe at.
+    pub start_position: Vec2,
+}
+
+/// Two fingers moving towards or away from each other.
+///
+/// The event is sent every frame the distance between the fingers changes once the
+/// [`GestureSettings::pinch_threshold`] has been exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Pinch {
+    /// The relative change of the distance between the fingers since the last frame.
+    ///
+    /// A positive value means the fingers moved apart. To apply the pinch to a zoom level,
+    /// multiply it by `1.0 + scale_delta`.
+    pub scale_delta: f32,
+    /// The point between the two fingers.
+    pub center: Vec2,
+}
+
+/// Two fingers rotating around each other.
+///
+/// The event is sent every frame the angle between the fingers changes once the
+/// [`GestureSettings::rotate_threshold`] has been exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct Rotate {
+    /// The change of the angle between the fingers since the last frame, in radians.
+    pub angle_delta: f32,
+    /// The point between the two fingers.
+    pub center: Vec2,
+}
+
+/// Thresholds used to recognize gestures.
+///
+/// ## Usage
+///
+/// It is used as a resource by the [`gesture_system`]. Distances are in logical pixels.
+#[derive(Resource, Debug, Clone, Reflect, FromReflect)]
+#[reflect(Debug, Default)]
+pub struct GestureSettings {
+    /// The longest a finger may be down to count as a tap.
+    pub tap_max_duration: Duration,
+    /// The farthest a finger may move to count as a tap or a long press.
+    pub tap_max_distance: f32,
+    /// The longest time between two taps that still counts as a double tap.
+    pub double_tap_max_interval: Duration,
+    /// The farthest two taps may be apart to count as a double tap.
+    pub double_tap_max_distance: f32,
+    /// The shortest a finger has to be held down to count as a long press.
+    pub long_press_min_duration: Duration,
+    /// The shortest distance a finger has to move to count as a swipe.
+    pub swipe_min_distance: f32,
+    /// The lowest average speed, in logical pixels per second, that counts as a swipe.
+    pub swipe_min_velocity: f32,
+    /// The relative change of the distance between two fingers that starts a pinch.
+    pub pinch_threshold: f32,
+    /// The change of the angle between two fingers, in radians, that starts a rotation.
+    pub rotate_threshold: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        GestureSettings {
+            tap_max_duration: Duration::from_millis(300),
+            tap_max_distance: 10.0,
+            double_tap_max_interval: Duration::from_millis(300),
+            double_tap_max_distance: 30.0,
+            long_press_min_duration: Duration::from_millis(500),
+            swipe_min_distance: 50.0,
+            swipe_min_velocity: 200.0,
+            pinch_threshold: 0.05,
+            rotate_threshold: 0.1,
+        }
+    }
+}
+
+/// A finger tracked by the [`GestureTracker`].
+#[derive(Debug, Clone, Copy)]
+struct TrackedFinger {
+    start_time: Duration,
+    start_position: Vec2,
+    position: Vec2,
+    /// The position at the end of the previous frame.
+    previous_position: Vec2,
+    /// Set once the finger sent a [`LongPress`] or took part in a pinch or rotation,
+    /// which means it can't be part of any single finger gesture anymore.
+    consumed: bool,
+}
+
+/// The state of a pinch or rotation.
+#[derive(Debug, Clone, Copy)]
+struct TwoFingerGesture {
+    fingers: [u64; 2],
+    /// The vector between the fingers when the second finger touched down.
+    start: Vec2,
+    pinching: bool,
+    rotating: bool,
+}
+
+/// The state of the [`gesture_system`] across frames.
+#[derive(Debug, Default)]
+pub struct GestureTracker {
+    fingers: HashMap<u64, TrackedFinger>,
+    two_fingers: Option<TwoFingerGesture>,
+    /// The time and position of the last tap that can start a double tap.
+    ///
+    /// A tap only completes the double tap if its finger touched down after this time, so
+    /// taps of fingers that were down at the same time are never a double tap.
+    last_tap: Option<(Duration, Vec2)>,
+}
+
+/// Recognizes gestures from the [`TouchInput`] events and sends the corresponding events.
+///
+/// Single finger gestures ([`TapGesture`], [`DoubleTap`], [`LongPress`] and [`Swipe`]) are
+/// recognized for every finger independently, so simultaneous taps of multiple fingers are all
+/// reported. Fingers that take part in a [`Pinch`] or [`Rotate`] gesture don't produce single
+/// finger gestures.
+#[allow(clippy::too_many_arguments)]
+pub fn gesture_system(
+    mut tracker: Local<GestureTracker>,
+    settings: Res<GestureSettings>,
+    time: Res<Time>,
+    mut touch_events: EventReader<TouchInput>,
+    mut taps: EventWriter<TapGesture>,
+    mut double_taps: EventWriter<DoubleTap>,
+    mut long_presses: EventWriter<LongPress>,
+    mut swipes: EventWriter<Swipe>,
+    mut pinches: EventWriter<Pinch>,
+    mut rotations: EventWriter<Rotate>,
+) {
+    let now = time.raw_elapsed();
+    let tracker = &mut *tracker;
+
+    for event in touch_events.iter() {
+        match event.phase {
+            TouchPhase::Started => {
+                tracker.fingers.insert(
+                    event.id,
+                    TrackedFinger {
+                        start_time: now,
+                        start_position: event.position,
+                        position: event.position,
+                        previous_position: event.position,
+                        consumed: false,
+                    },
+                );
+            }
+            TouchPhase::Moved => {
+                if let Some(finger) = tracker.fingers.get_mut(&event.id) {
+                    finger.position = event.position;
+                }
+            }
+            TouchPhase::Ended => {
+                let finger = match tracker.fingers.remove(&event.id) {
+                    Some(finger) if !finger.consumed => finger,
+                    _ => continue,
+                };
+                let duration = now.saturating_sub(finger.start_time);
+                let distance = event.position - finger.start_position;
+
+                if duration <= settings.tap_max_duration
+                    && distance.length() <= settings.tap_max_distance
+                {
+                    taps.send(TapGesture {
+                        position: event.position,
+                        id: event.id,
+                    });
+                    match tracker.last_tap {
+                        Some((time, position))
+                            if finger.start_time > time
+                                && now.saturating_sub(time) <= settings.double_tap_max_interval
+                                && position.distance(event.position)
+                                    <= settings.double_tap_max_distance =>
+                        {
+                            double_taps.send(DoubleTap {
+                                position: event.position,
+                            });
+                            tracker.last_tap = None;
+                        }
+                        _ => tracker.last_tap = Some((now, event.position)),
+                    }
+                } else if distance.length() >= settings.swipe_min_distance {
+                    let velocity = distance.length() / duration.as_secs_f32().max(f32::EPSILON);
+                    if velocity >= settings.swipe_min_velocity {
+                        swipes.send(Swipe {
+                            direction: distance.normalize(),
+                            velocity,
+                            start_position: finger.start_position,
+                        });
+                    }
+                }
+            }
+            TouchPhase::Cancelled => {
+                tracker.fingers.remove(&event.id);
+            }
+        }
+    }
+
+    for (id, finger) in &mut tracker.fingers {
+        if !finger.consumed
+            && now.saturating_sub(finger.start_time) >= settings.long_press_min_duration
+            && finger.position.distance(finger.start_position) <= settings.tap_max_distance
+        {
+            finger.consumed = true;
+            long_presses.send(LongPress {
+                position: finger.position,
+                id: *id,
+            });
+        }
+    }
+
+    track_two_fingers(tracker, &settings, &mut pinches, &mut rotations);
+
+    for finger in tracker.fingers.values_mut() {
+        finger.previous_position = finger.position;
+    }
+}
+
+/// Updates the pinch and rotation state of the `tracker` and sends the corresponding events.
+fn track_two_fingers(
+    tracker: &mut GestureTracker,
+    settings: &GestureSettings,
+    pinches: &mut EventWriter<Pinch>,
+    rotations: &mut EventWriter<Rotate>,
+) {
+    if tracker.fingers.len() != 2 {
+        tracker.two_fingers = None;
+        return;
+    }
+
+    let mut ids = [0; 2];
+    for (id, finger_id) in ids.iter_mut().zip(tracker.fingers.keys()) {
+        *id = *finger_id;
+    }
+    ids.sort_unstable();
+    let first = tracker.fingers[&ids[0]];
+    let second = tracker.fingers[&ids[1]];
+    let current = second.position - first.position;
+    let previous = second.previous_position - first.previous_position;
+    let center = (first.position + second.position) / 2.0;
+
+    let gesture = match &mut tracker.two_fingers {
+        Some(gesture) if gesture.fingers == ids => gesture,
+        two_fingers => {
+            // The gesture starts from the positions at the end of the last frame, so a
+            // finger that touched down and moved within the same frame still counts.
+            *two_fingers = Some(TwoFingerGesture {
+                fingers: ids,
+                start: previous,
+                pinching: false,
+                rotating: false,
+            });
+            two_fingers.as_mut().unwrap()
+        }
+    };
+
+    if gesture.start.length() <= f32::EPSILON || current.length() <= f32::EPSILON {
+        return;
+    }
+
+    if !gesture.pinching
+        && (current.length() / gesture.start.length() - 1.0).abs() >= settings.pinch_threshold
+    {
+        gesture.pinching = true;
+    }
+    if !gesture.rotating && gesture.start.angle_between(current).abs() >= settings.rotate_threshold
+    {
+        gesture.rotating = true;
+    }
+
+    if gesture.pinching || gesture.rotating {
+        for id in ids {
+            if let Some(finger) = tracker.fingers.get_mut(&id) {
+                finger.consumed = true;
+            }
+        }
+    }
+
+    if previous.length() <= f32::EPSILON {
+        return;
+    }
+    let scale_delta = current.length() / previous.length() - 1.0;
+    if gesture.pinching && scale_delta != 0.0 {
+        pinches.send(Pinch {
+            scale_delta,
+            center,
+        });
+    }
+    let angle_delta = previous.angle_between(current);
+    if gesture.rotating && angle_delta != 0.0 {
+        rotations.send(Rotate {
+            angle_delta,
+            center,
+        });
+    }
+}
+
+/// Label for the [`gesture_system`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemLabel)]
+pub struct GestureSystem;
+
+/// Adds touch gesture recognition to an App.
+///
+/// The [`gesture_system`] reads the [`TouchInput`] events and the [`Time`] resource, so the
+/// `InputPlugin` and the `TimePlugin` need to be added as well.
+#[derive(Default)]
+pub struct GesturePlugin;
+
+impl Plugin for GesturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GestureSettings>()
+            .add_event::<TapGesture>()
+            .add_event::<DoubleTap>()
+            .add_event::<LongPress>()
+            .add_event::<Swipe>()
+            .add_event::<Pinch>()
+            .add_event::<Rotate>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gesture_system.label(GestureSystem).after(InputSystem),
+            )
+            .register_type::<GestureSettings>()
+            .register_type::<TapGesture>()
+            .register_type::<DoubleTap>()
+            .register_type::<LongPress>()
+            .register_type::<Swipe>()
+            .register_type::<Pinch>()
+            .register_type::<Rotate>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DoubleTap, GesturePlugin, LongPress, Pinch, Rotate, Swipe, TapGesture};
+    use crate::{
+        touch::{TouchInput, TouchPhase},
+        InputPlugin,
+    };
+    use bevy_app::App;
+    use bevy_ecs::event::Events;
+    use bevy_math::Vec2;
+    use bevy_time::Time;
+    use bevy_utils::{Duration, Instant};
+
+    struct GestureApp {
+        app: App,
+        start: Instant,
+        elapsed: Duration,
+    }
+
+    impl GestureApp {
+        fn new() -> Self {
+            let start = Instant::now();
+            let mut app = App::new();
+            app.add_plugin(InputPlugin)
+                .add_plugin(GesturePlugin)
+                .insert_resource(Time::new(start));
+            let mut gesture_app = Self {
+                app,
+                start,
+                elapsed: Duration::ZERO,
+            };
+            gesture_app.update(0);
+            gesture_app
+        }
+
+        fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) {
+            self.app.world.send_event(TouchInput {
+                phase,
+                position,
+                force: None,
+                id,
+            });
+        }
+
+        /// Advances the time by `millis` and runs the app once.
+        fn update(&mut self, millis: u64) {
+            self.elapsed += Duration::from_millis(millis);
+            self.app
+                .world
+                .resource_mut::<Time>()
+                .update_with_instant(self.start + self.elapsed);
+            self.app.update();
+        }
+
+        fn events<E: bevy_ecs::event::Event + Clone>(&self) -> Vec<E> {
+            self.app
+                .world
+                .resource::<Events<E>>()
+                .iter_current_update_events()
+                .cloned()
+                .collect()
+        }
+    }
+
+    #[test]
+    fn tap_and_double_tap() {
+        let mut app = GestureApp::new();
+        let position = Vec2::new(100.0, 100.0);
+
+        app.touch(0, TouchPhase::Started, position);
+        app.update(16);
+        app.touch(0, TouchPhase::Ended, position + Vec2::new(2.0, 0.0));
+        app.update(100);
+        assert_eq!(
+            app.events::<TapGesture>(),
+            vec![TapGesture {
+                position: position + Vec2::new(2.0, 0.0),
+                id: 0
+            }]
+        );
+        assert!(app.events::<DoubleTap>().is_empty());
+
+        app.touch(1, TouchPhase::Started, position);
+        app.update(100);
+        app.touch(1, TouchPhase::Ended, position);
+        app.update(100);
+        assert_eq!(app.events::<TapGesture>().len(), 1);
+        assert_eq!(app.events::<DoubleTap>(), vec![DoubleTap { position }]);
+    }
+
+    #[test]
+    fn slow_release_is_not_a_tap() {
+        let mut app = GestureApp::new();
+
+        app.touch(0, TouchPhase::Started, Vec2::ZERO);
+        app.update(16);
+        app.update(400);
+        app.touch(0, TouchPhase::Ended, Vec2::ZERO);
+        app.update(16);
+        assert!(app.events::<TapGesture>().is_empty());
+    }
+
+    #[test]
+    fn long_press() {
+        let mut app = GestureApp::new();
+        let position = Vec2::new(10.0, 20.0);
+
+        app.touch(3, TouchPhase::Started, position);
+        app.update(16);
+        app.update(300);
+        assert!(app.events::<LongPress>().is_empty());
+        app.update(300);
+        assert_eq!(
+            app.events::<LongPress>(),
+            vec![LongPress { position, id: 3 }]
+        );
+
+        // Only sent once, and lifting the finger doesn't produce a tap.
+        app.update(300);
+        app.touch(3, TouchPhase::Ended, position);
+        app.update(16);
+        assert!(app.events::<LongPress>().is_empty());
+        assert!(app.events::<TapGesture>().is_empty());
+    }
+
+    #[test]
+    fn swipe() {
+        let mut app = GestureApp::new();
+
+        app.touch(0, TouchPhase::Started, Vec2::ZERO);
+        app.update(16);
+        app.touch(0, TouchPhase::Moved, Vec2::new(100.0, 0.0));
+        app.update(84);
+        app.touch(0, TouchPhase::Ended, Vec2::new(200.0, 0.0));
+        app.update(100);
+
+        let swipes = app.events::<Swipe>();
+        assert_eq!(swipes.len(), 1);
+        assert_eq!(swipes[0].direction, Vec2::X);
+        assert_eq!(swipes[0].start_position, Vec2::ZERO);
+        assert!((swipes[0].velocity - 200.0 / 0.184).abs() < 1.0);
+        assert!(app.events::<TapGesture>().is_empty());
+    }
+
+    #[test]
+    fn pinch_and_rotate() {
+        let mut app = GestureApp::new();
+
+        app.touch(0, TouchPhase::Started, Vec2::new(-10.0, 0.0));
+        app.touch(1, TouchPhase::Started, Vec2::new(10.0, 0.0));
+        app.update(16);
+        assert!(app.events::<Pinch>().is_empty());
+
+        // Spread the fingers to twice their distance.
+        app.touch(0, TouchPhase::Moved, Vec2::new(-20.0, 0.0));
+        app.touch(1, TouchPhase::Moved, Vec2::new(20.0, 0.0));
+        app.update(16);
+        let pinches = app.events::<Pinch>();
+        assert_eq!(pinches.len(), 1);
+        assert!((pinches[0].scale_delta - 1.0).abs() < 1e-5);
+        assert_eq!(pinches[0].center, Vec2::ZERO);
+        assert!(app.events::<Rotate>().is_empty());
+
+        // Rotate the fingers by a quarter turn around their center.
+        app.touch(0, TouchPhase::Moved, Vec2::new(0.0, -20.0));
+        app.touch(1, TouchPhase::Moved, Vec2::new(0.0, 20.0));
+        app.update(16);
+        let rotations = app.events::<Rotate>();
+        assert_eq!(rotations.len(), 1);
+        assert!((rotations[0].angle_delta - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+
+        // Fingers that took part in a pinch don't produce taps.
+        app.touch(0, TouchPhase::Ended, Vec2::new(0.0, -20.0));
+        app.touch(1, TouchPhase::Ended, Vec2::new(0.0, 20.0));
+        app.update(16);
+        assert!(app.events::<TapGesture>().is_empty());
+        assert!(app.events::<Swipe>().is_empty());
+    }
+
+    #[test]
+    fn simultaneous_taps() {
+        let mut app = GestureApp::new();
+
+        app.touch(0, TouchPhase::Started, Vec2::new(0.0, 0.0));
+        app.touch(1, TouchPhase::Started, Vec2::new(500.0, 0.0));
+        app.update(16);
+        app.touch(0, TouchPhase::Ended, Vec2::new(0.0, 0.0));
+        app.touch(1, TouchPhase::Ended, Vec2::new(500.0, 0.0));
+        app.update(16);
+
+        let mut ids: Vec<u64> = app.events::<TapGesture>().iter().map(|it| it.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1]);
+        assert!(app.events::<Pinch>().is_empty());
+    }
+
+    #[test]
+    fn nearby_fingers_are_not_a_double_tap() {
+        let mut app = GestureApp::new();
+        let position = Vec2::new(100.0, 100.0);
+
+        app.touch(0, TouchPhase::Started, position);
+        app.touch(1, TouchPhase::Started, position + Vec2::new(20.0, 0.0));
+        app.update(16);
+        app.touch(0, TouchPhase::Ended, position);
+        app.touch(1, TouchPhase::Ended, position + Vec2::new(20.0, 0.0));
+        app.update(16);
+        assert_eq!(app.events::<TapGesture>().len(), 2);
+        assert!(app.events::<DoubleTap>().is_empty());
+
+        // Overlapping taps lifted on different frames aren't either.
+        app.update(500);
+        app.touch(2, TouchPhase::Started, position);
+        app.touch(3, TouchPhase::Started, position + Vec2::new(20.0, 0.0));
+        app.update(16);
+        app.touch(2, TouchPhase::Ended, position);
+        app.update(16);
+        app.touch(3, TouchPhase::Ended, position + Vec2::new(20.0, 0.0));
+        app.update(16);
+        assert_eq!(app.events::<TapGesture>().len(), 1);
+        assert!(app.events::<DoubleTap>().is_empty());
+    }
+}
@@ -1,5 +1,7 @@
+pub mod action;
 mod axis;
 pub mod gamepad;
+pub mod gesture;
 mod input;
 pub mod keyboard;
 pub mod mouse;
@@ -11,6 +13,7 @@ pub use input::*;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
+        action::{ActionState, InputActionPlugin, InputMap, UserInput},
         gamepad::{
             Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
             GamepadEventType, Gamepads,
@@ -35,7 +38,8 @@ use touch::{touch_screen_input_system, ForceTouch, TouchInput, TouchPhase, Touch
 use gamepad::{
     gamepad_connection_system, gamepad_event_system, AxisSettings, ButtonAxisSettings,
     ButtonSettings, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType,
-    GamepadEvent, GamepadEventRaw, GamepadEventType, GamepadSettings, Gamepads,
+    GamepadEvent, GamepadEventRaw, GamepadEventType, GamepadRumbleRequest, GamepadSettings,
+    Gamepads,
 };
 
 #[cfg(feature = "serialize")]
@@ -71,6 +75,7 @@ impl Plugin for InputPlugin {
             // gamepad
             .add_event::<GamepadEvent>()
             .add_event::<GamepadEventRaw>()
+            .add_event::<GamepadRumbleRequest>()
             .init_resource::<GamepadSettings>()
             .init_resource::<Gamepads>()
             .init_resource::<Input<GamepadButton>>()
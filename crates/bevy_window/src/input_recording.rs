@@ -0,0 +1,459 @@
+use crate::CursorMoved;
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_input::{
+    gamepad::GamepadEventRaw,
+    keyboard::KeyboardInput,
+    mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+    touch::TouchInput,
+    InputSystem,
+};
+
+/// A raw input event captured by the [`InputRecorder`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedInput {
+    /// A [`KeyboardInput`] event.
+    Keyboard(KeyboardInput),
+    /// A [`MouseButtonInput`] event.
+    MouseButton(MouseButtonInput),
+    /// A [`MouseMotion`] event.
+    MouseMotion(MouseMotion),
+    /// A [`MouseWheel`] event.
+    MouseWheel(MouseWheel),
+    /// A [`TouchInput`] event.
+    Touch(TouchInput),
+    /// A [`GamepadEventRaw`] event, as sent by gamepad backends before it is processed.
+    Gamepad(GamepadEventRaw),
+    /// A [`CursorMoved`] event.
+    CursorMoved(CursorMoved),
+}
+
+/// A [`RecordedInput`] together with the frame it was received on.
+///
+/// Frames are counted from the first update after recording or replaying started.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimestampedInput {
+    /// The frame the input was received on.
+    pub frame: u64,
+    /// The recorded input event.
+    pub input: RecordedInput,
+}
+
+/// A sequence of [`TimestampedInput`]s, ordered by frame.
+///
+/// Recordings are produced by the [`InputRecorder`] and played back by the [`InputReplayer`].
+/// When the `serialize` feature is enabled they can be stored on disk, for example to keep the
+/// input of a bug report around as a regression test.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputRecording {
+    inputs: Vec<TimestampedInput>,
+}
+
+impl InputRecording {
+    /// Creates a new, empty [`InputRecording`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `input` to the recording at the given `frame`.
+    ///
+    /// Inputs added on the same frame are replayed in the order they were added.
+    pub fn push(&mut self, frame: u64, input: RecordedInput) {
+        let index = self.inputs.partition_point(|it| it.frame <= frame);
+        self.inputs.insert(index, TimestampedInput { frame, input });
+    }
+
+    /// Returns the recorded inputs, ordered by frame.
+    pub fn inputs(&self) -> &[TimestampedInput] {
+        &self.inputs
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the frame of the last recorded input.
+    pub fn last_frame(&self) -> Option<u64> {
+        self.inputs.last().map(|it| it.frame)
+    }
+}
+
+/// Records every raw input event while this resource exists.
+///
+/// ## Usage
+///
+/// Insert this resource to start recording and remove it, or call [`InputRecorder::take_recording`],
+/// to retrieve the [`InputRecording`]. The events are captured in the [`record_input_system`]
+/// after the input processing systems labeled [`InputSystem`] run, so that the events sent
+/// earlier in [`CoreStage::PreUpdate`] by input backends are recorded on the frame they were
+/// sent.
+#[derive(Resource, Debug, Default)]
+pub struct InputRecorder {
+    recording: InputRecording,
+    frame: u64,
+    paused: bool,
+}
+
+impl InputRecorder {
+    /// Returns the inputs recorded so far.
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Takes the inputs recorded so far, leaving an empty recording behind.
+    ///
+    /// The frame counter is not reset, so recording can continue seamlessly.
+    pub fn take_recording(&mut self) -> InputRecording {
+        std::mem::take(&mut self.recording)
+    }
+
+    /// Returns the number of frames since recording started.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Stops capturing events until [`InputRecorder::resume`] is called.
+    ///
+    /// Frames keep being counted while paused.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes capturing events after a call to [`InputRecorder::pause`].
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if the recorder is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// Replays an [`InputRecording`] while this resource exists.
+///
+/// ## Usage
+///
+/// Every recorded input is sent as its original event on the same frame it was recorded on,
+/// counted from the first update after the replayer was inserted. Because the events go through
+/// the regular input processing systems, resources like [`Input<KeyCode>`] behave exactly like
+/// they did while recording.
+///
+/// For fully deterministic replays, also advance the time by a fixed amount each frame by
+/// inserting `TimeUpdateStrategy::ManualDuration` from `bevy_time`.
+///
+/// [`Input<KeyCode>`]: bevy_input::Input
+#[derive(Resource, Debug)]
+pub struct InputReplayer {
+    recording: InputRecording,
+    frame: u64,
+    next_input: usize,
+}
+
+impl InputReplayer {
+    /// Creates a new [`InputReplayer`] that plays back the given `recording`.
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            frame: 0,
+            next_input: 0,
+        }
+    }
+
+    /// Returns the number of frames since replaying started.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns `true` once every recorded input has been sent.
+    pub fn is_finished(&self) -> bool {
+        self.next_input >= self.recording.inputs.len()
+    }
+}
+
+/// Captures the raw input events into the [`InputRecorder`] resource, if it exists.
+#[allow(clippy::too_many_arguments)]
+pub fn record_input_system(
+    recorder: Option<ResMut<InputRecorder>>,
+    mut keyboard: EventReader<KeyboardInput>,
+    mut mouse_button: EventReader<MouseButtonInput>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut touch: EventReader<TouchInput>,
+    mut gamepad: EventReader<GamepadEventRaw>,
+    mut cursor_moved: EventReader<CursorMoved>,
+) {
+    let mut recorder = match recorder {
+        Some(recorder) if !recorder.paused => recorder,
+        recorder => {
+            if let Some(mut recorder) = recorder {
+                recorder.frame += 1;
+            }
+            // Skip the events, so they don't get recorded once recording starts or resumes.
+            keyboard.clear();
+            mouse_button.clear();
+            mouse_motion.clear();
+            mouse_wheel.clear();
+            touch.clear();
+            gamepad.clear();
+            cursor_moved.clear();
+            return;
+        }
+    };
+    let frame = recorder.frame;
+    let recording = &mut recorder.recording;
+
+    for event in keyboard.iter() {
+        recording.push(frame, RecordedInput::Keyboard(*event));
+    }
+    for event in mouse_button.iter() {
+        recording.push(frame, RecordedInput::MouseButton(*event));
+    }
+    for event in mouse_motion.iter() {
+        recording.push(frame, RecordedInput::MouseMotion(*event));
+    }
+    for event in mouse_wheel.iter() {
+        recording.push(frame, RecordedInput::MouseWheel(*event));
+    }
+    for event in touch.iter() {
+        recording.push(frame, RecordedInput::Touch(*event));
+    }
+    for event in gamepad.iter() {
+        recording.push(frame, RecordedInput::Gamepad(event.clone()));
+    }
+    for event in cursor_moved.iter() {
+        recording.push(frame, RecordedInput::CursorMoved(event.clone()));
+    }
+
+    recorder.frame += 1;
+}
+
+/// Sends the inputs of the [`InputReplayer`] resource that are due this frame, if it exists.
+#[allow(clippy::too_many_arguments)]
+pub fn replay_input_system(
+    replayer: Option<ResMut<InputReplayer>>,
+    mut keyboard: EventWriter<KeyboardInput>,
+    mut mouse_button: EventWriter<MouseButtonInput>,
+    mut mouse_motion: EventWriter<MouseMotion>,
+    mut mouse_wheel: EventWriter<MouseWheel>,
+    mut touch: EventWriter<TouchInput>,
+    mut gamepad: EventWriter<GamepadEventRaw>,
+    mut cursor_moved: EventWriter<CursorMoved>,
+) {
+    let mut replayer = match replayer {
+        Some(replayer) => replayer,
+        None => return,
+    };
+    let replayer = &mut *replayer;
+
+    while let Some(input) = replayer.recording.inputs.get(replayer.next_input) {
+        if input.frame > replayer.frame {
+            break;
+        }
+        match &input.input {
+            RecordedInput::Keyboard(event) => keyboard.send(*event),
+            RecordedInput::MouseButton(event) => mouse_button.send(*event),
+            RecordedInput::MouseMotion(event) => mouse_motion.send(*event),
+            RecordedInput::MouseWheel(event) => mouse_wheel.send(*event),
+            RecordedInput::Touch(event) => touch.send(*event),
+            RecordedInput::Gamepad(event) => gamepad.send(event.clone()),
+            RecordedInput::CursorMoved(event) => cursor_moved.send(event.clone()),
+        }
+        replayer.next_input += 1;
+    }
+
+    replayer.frame += 1;
+}
+
+/// Label for the systems of the [`InputRecordingPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum InputRecordingSystem {
+    /// Captures the raw input events, see [`record_input_system`].
+    Record,
+    /// Sends the replayed input events, see [`replay_input_system`].
+    Replay,
+}
+
+/// Adds input recording and replaying to an App.
+///
+/// The systems only do work while an [`InputRecorder`] or an [`InputReplayer`] resource exists.
+/// Both run in [`CoreStage::PreUpdate`]: replaying before [`InputSystem`], so replayed events are
+/// processed on the same frame they were recorded on, and recording after it, so events sent
+/// earlier in the stage by input backends such as `bevy_gilrs` are recorded on the frame they
+/// were sent.
+///
+/// This plugin requires the `InputPlugin` and the [`WindowPlugin`](crate::WindowPlugin),
+/// or at least their events, to be added.
+#[derive(Default)]
+pub struct InputRecordingPlugin;
+
+impl Plugin for InputRecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            replay_input_system
+                .label(InputRecordingSystem::Replay)
+                .before(InputSystem),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            record_input_system
+                .label(InputRecordingSystem::Record)
+                .after(InputSystem),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WindowId;
+    use bevy_input::{
+        gamepad::{Gamepad, GamepadButtonType, GamepadEventType},
+        keyboard::KeyCode,
+        ButtonState, Input, InputPlugin,
+    };
+    use bevy_math::Vec2;
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(InputPlugin)
+            .add_event::<CursorMoved>()
+            .add_plugin(InputRecordingPlugin);
+        app
+    }
+
+    fn key_event(state: ButtonState) -> KeyboardInput {
+        KeyboardInput {
+            scan_code: 0,
+            key_code: Some(KeyCode::Space),
+            state,
+        }
+    }
+
+    #[test]
+    fn record_frames() {
+        let mut app = test_app();
+        app.init_resource::<InputRecorder>();
+
+        app.update();
+        app.world.send_event(key_event(ButtonState::Pressed));
+        app.world.send_event(CursorMoved {
+            id: WindowId::primary(),
+            position: Vec2::new(1.0, 2.0),
+        });
+        app.update();
+        app.update();
+        app.world.send_event(key_event(ButtonState::Released));
+        app.update();
+
+        let recording = app.world.resource::<InputRecorder>().recording().clone();
+        let frames: Vec<u64> = recording.inputs().iter().map(|it| it.frame).collect();
+        assert_eq!(frames, vec![1, 1, 3]);
+        assert_eq!(
+            recording.inputs()[0].input,
+            RecordedInput::Keyboard(key_event(ButtonState::Pressed))
+        );
+        assert_eq!(recording.last_frame(), Some(3));
+    }
+
+    #[test]
+    fn record_gamepad_events_sent_in_the_same_frame() {
+        fn send_gamepad_event(mut events: EventWriter<GamepadEventRaw>) {
+            events.send(GamepadEventRaw::new(
+                Gamepad::new(0),
+                GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.0),
+            ));
+        }
+
+        let mut app = test_app();
+        // Sends the event like the gilrs backend does, in the same stage as the recording.
+        app.add_system_to_stage(CoreStage::PreUpdate, send_gamepad_event.before(InputSystem))
+            .init_resource::<InputRecorder>();
+        app.update();
+
+        let recording = app.world.resource::<InputRecorder>().recording();
+        assert_eq!(
+            recording.inputs(),
+            [TimestampedInput {
+                frame: 0,
+                input: RecordedInput::Gamepad(GamepadEventRaw::new(
+                    Gamepad::new(0),
+                    GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.0),
+                )),
+            }]
+        );
+    }
+
+    #[test]
+    fn paused_recorder_skips_events() {
+        let mut app = test_app();
+        app.init_resource::<InputRecorder>();
+
+        app.world.resource_mut::<InputRecorder>().pause();
+        app.world.send_event(key_event(ButtonState::Pressed));
+        app.update();
+        app.world.resource_mut::<InputRecorder>().resume();
+        app.world.send_event(key_event(ButtonState::Released));
+        app.update();
+
+        let recorder = app.world.resource::<InputRecorder>();
+        assert_eq!(recorder.frame(), 2);
+        assert_eq!(recorder.recording().inputs().len(), 1);
+        assert_eq!(recorder.recording().inputs()[0].frame, 1);
+    }
+
+    #[test]
+    fn replay_through_input_systems() {
+        let mut recording = InputRecording::new();
+        recording.push(2, RecordedInput::Keyboard(key_event(ButtonState::Released)));
+        recording.push(1, RecordedInput::Keyboard(key_event(ButtonState::Pressed)));
+
+        let mut app = test_app();
+        app.insert_resource(InputReplayer::new(recording));
+
+        app.update();
+        assert!(!app
+            .world
+            .resource::<Input<KeyCode>>()
+            .pressed(KeyCode::Space));
+
+        app.update();
+        let keyboard = app.world.resource::<Input<KeyCode>>();
+        assert!(keyboard.just_pressed(KeyCode::Space));
+        assert!(!app.world.resource::<InputReplayer>().is_finished());
+
+        app.update();
+        let keyboard = app.world.resource::<Input<KeyCode>>();
+        assert!(keyboard.just_released(KeyCode::Space));
+        assert!(app.world.resource::<InputReplayer>().is_finished());
+    }
+
+    #[test]
+    fn replayed_input_matches_recording() {
+        let mut app = test_app();
+        app.init_resource::<InputRecorder>();
+        app.world.send_event(key_event(ButtonState::Pressed));
+        app.update();
+        app.update();
+        app.world.send_event(key_event(ButtonState::Released));
+        app.update();
+        let recording = app.world.resource_mut::<InputRecorder>().take_recording();
+
+        let mut app = test_app();
+        app.init_resource::<InputRecorder>();
+        app.insert_resource(InputReplayer::new(recording.clone()));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.resource::<InputRecorder>().recording(),
+            &recording
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #[warn(missing_docs)]
 mod cursor;
 mod event;
+mod input_recording;
 mod raw_handle;
 mod system;
 mod window;
@@ -9,6 +10,7 @@ mod windows;
 pub use crate::raw_handle::*;
 pub use cursor::*;
 pub use event::*;
+pub use input_recording::*;
 pub use system::*;
 pub use window::*;
 pub use windows::*;